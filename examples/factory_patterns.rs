@@ -50,7 +50,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _provider_memory = StorageProvider::memory();
     println!("   • StorageProvider::memory() - created");
     
-    let _provider_lmdb = StorageProvider::lmdb("/tmp/provider_lmdb", StorageMode::ReadOnly, None);
+    let _provider_lmdb = StorageProvider::lmdb("/tmp/provider_lmdb", StorageMode::ReadWrite, None);
     println!("   • StorageProvider::lmdb() - created");
     
     let _provider_remote = StorageProvider::remote("127.0.0.1:8080");
@@ -85,7 +85,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ("LMDB", StorageConfig::Lmdb { 
             path: "/tmp/config_lmdb".to_string(), 
             mode: StorageMode::ReadWrite, 
-            max_read_counter_reopen: Some(500) 
+            max_read_counter_reopen: Some(500),
+            slow_op_threshold: None,
+            map_size_bytes: None,
         }),
         ("Remote", StorageConfig::Remote { 
             address: "127.0.0.1:8080".to_string() 
@@ -153,7 +155,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut provider_generic_memory = StorageProvider::memory_generic();
     println!("   • StorageProvider::memory_generic() - VMemoryStorage");
     
-    let provider_generic_lmdb = StorageProvider::lmdb_generic("/tmp/generic_provider", StorageMode::ReadOnly, None);
+    let provider_generic_lmdb = StorageProvider::lmdb_generic("/tmp/generic_provider", StorageMode::ReadWrite, None);
     println!("   • StorageProvider::lmdb_generic() - VLMDBStorage");
     
     let provider_generic_remote = StorageProvider::remote_generic("127.0.0.1:8080");
@@ -10,7 +10,7 @@ fn main() {
     println!("=== Unified API: LMDB vs MDBX ===\n");
     
     // Both instances have identical API!
-    let mut lmdb = LmdbInstance::new("/tmp/api-lmdb/", StorageMode::ReadWrite);
+    let mut lmdb = LmdbInstance::new("/tmp/api-lmdb/", StorageMode::ReadWrite, None);
     let mut mdbx = MdbxInstance::new("/tmp/api-mdbx/", StorageMode::ReadWrite);
     
     println!("1. LMDB:");
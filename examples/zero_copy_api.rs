@@ -22,7 +22,7 @@ fn main() {
 
 fn lmdb_zero_copy_example() {
     // Create LMDB instance
-    let mut instance = LmdbInstance::new("/tmp/zero-copy-lmdb/", StorageMode::ReadWrite);
+    let mut instance = LmdbInstance::new("/tmp/zero-copy-lmdb/", StorageMode::ReadWrite, None);
     
     // Put some data
     let test_data = b"Hello, LMDB! This is test data without copying!";
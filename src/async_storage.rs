@@ -0,0 +1,239 @@
+// async_storage.rs
+
+use v_individual_model::onto::individual::Individual;
+use crate::common::{StorageId, StorageResult};
+
+/// Асинхронный аналог Storage для сетевых бэкендов
+///
+/// Синхронные реализации Storage для Tarantool и удаленного клиента блокируют
+/// поток (block_on / ожидание ответа nng), что останавливает асинхронный код,
+/// вызывающий их из рантайма. Реализации этого трейта не занимают рабочий поток
+/// рантайма на время запроса: TTStorage ожидает ответ без блокировки, а
+/// AsyncStorageROClient ожидает ответ nng через Aio.
+/// Футуры не обязаны быть Send.
+#[allow(async_fn_in_trait)]
+pub trait AsyncStorage {
+    async fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()>;
+    async fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String>;
+    async fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>>;
+    async fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()>;
+    async fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()>;
+    async fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()>;
+    async fn count(&mut self, storage: StorageId) -> StorageResult<usize>;
+}
+
+pub use remote::AsyncStorageROClient;
+
+mod remote {
+    use std::time::Duration;
+    use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+    use futures::StreamExt;
+    use nng::{Aio, AioResult, Context, Error, Message, Protocol, Socket};
+    use v_individual_model::onto::individual::Individual;
+    use super::AsyncStorage;
+    use crate::common::{utf8_value, StorageId, StorageResult};
+    use crate::remote_storage_client::{count_reply, count_request, individual_reply, is_transient, key_request};
+
+    /// Асинхронный клиент storage_manager на асинхронном вводе-выводе nng (Aio)
+    ///
+    /// Каждый запрос выполняется в собственном контексте Req0 на общем сокете, поэтому
+    /// клоны клиента могут выполнять запросы одновременно, а ожидание ответа не занимает
+    /// ни рабочий, ни блокирующий поток: футура просыпается из колбэка nng и не зависит
+    /// от конкретного рантайма. Сокет подключается через dial_async один раз, обрыв
+    /// соединения nng восстанавливает сам. Протокол тот же, что у StorageROClient.
+    #[derive(Clone)]
+    pub struct AsyncStorageROClient {
+        soc: Socket,
+        addr: String,
+        dialed: bool,
+        recv_timeout: Option<Duration>,
+        max_retries: u32,
+    }
+
+    impl AsyncStorageROClient {
+        pub fn new(addr: &str) -> Self {
+            let mut client = AsyncStorageROClient {
+                soc: Socket::new(Protocol::Req0).unwrap(),
+                addr: addr.to_string(),
+                dialed: false,
+                recv_timeout: None,
+                max_retries: 0,
+            };
+            client.dial();
+            client
+        }
+
+        /// Клиент с таймаутом ожидания ответа: при таймауте и других временных ошибках
+        /// запрос повторяется до max_retries раз, после чего возвращается NotReady
+        pub fn with_options(addr: &str, recv_timeout: Duration, max_retries: u32) -> Self {
+            let mut client = AsyncStorageROClient::new(addr);
+            client.recv_timeout = Some(recv_timeout);
+            client.max_retries = max_retries;
+            client
+        }
+
+        fn dial(&mut self) -> bool {
+            if !self.dialed {
+                match self.soc.dial_async(&self.addr) {
+                    Ok(()) => self.dialed = true,
+                    Err(e) => error!("fail connect to storage_manager ({}), err={:?}", self.addr, e),
+                }
+            }
+            self.dialed
+        }
+
+        pub async fn get_individual_raw_from_db(&mut self, db_id: StorageId, id: &str) -> StorageResult<Vec<u8>> {
+            self.request(key_request("", db_id, id)).await.map(|msg| msg.as_slice().to_vec())
+        }
+
+        pub async fn get_raw_value_from_db(&mut self, db_id: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+            self.request(key_request("v,", db_id, key)).await.map(|msg| msg.as_slice().to_vec())
+        }
+
+        pub async fn count_in_db(&mut self, db_id: StorageId) -> StorageResult<usize> {
+            self.request(count_request(&db_id)).await.and_then(|msg| count_reply(&msg, &db_id))
+        }
+
+        // Отправка запроса и ожидание ответа; "[]" означает отсутствие ключа
+        async fn request(&mut self, req: String) -> StorageResult<Message> {
+            if !self.dial() {
+                return StorageResult::NotReady;
+            }
+
+            let mut attempt = 0;
+            loop {
+                match self.send_recv(req.as_bytes()).await {
+                    Ok(msg) if msg.as_slice() == b"[]" => return StorageResult::NotFound,
+                    Ok(msg) => return StorageResult::Ok(msg),
+                    Err(e) if is_transient(e) && attempt < self.max_retries => {
+                        attempt += 1;
+                        warn!("REMOTE STORAGE: retry {}/{} after err={:?}", attempt, self.max_retries, e);
+                    },
+                    Err(e) => {
+                        error!("REMOTE STORAGE: request to storage_manager ({}) failed, err={:?}", self.addr, e);
+                        return StorageResult::NotReady;
+                    },
+                }
+            }
+        }
+
+        async fn send_recv(&self, req: &[u8]) -> Result<Message, Error> {
+            let ctx = Context::new(&self.soc)?;
+            let (aio, mut results) = self.aio()?;
+
+            ctx.send(&aio, Message::from(req)).map_err(|(_, e)| e)?;
+            match results.next().await {
+                Some(AioResult::Send(Ok(()))) => {},
+                Some(AioResult::Send(Err((_, e)))) => return Err(e),
+                _ => return Err(Error::Canceled),
+            }
+
+            ctx.recv(&aio)?;
+            match results.next().await {
+                Some(AioResult::Recv(res)) => res,
+                _ => Err(Error::Canceled),
+            }
+        }
+
+        // Aio, результаты которого приходят в канал; таймаут действует на каждую операцию
+        fn aio(&self) -> Result<(Aio, UnboundedReceiver<AioResult>), Error> {
+            let (tx, rx) = unbounded();
+            let aio = Aio::new(move |_, res| {
+                let _ = tx.unbounded_send(res);
+            })?;
+            aio.set_timeout(self.recv_timeout)?;
+            Ok((aio, rx))
+        }
+    }
+
+    impl AsyncStorage for AsyncStorageROClient {
+        async fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+            self.request(key_request("", storage, id)).await.and_then(|msg| individual_reply(&msg, id, iraw))
+        }
+
+        async fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+            utf8_value(self.get_raw_value_from_db(storage, key).await)
+        }
+
+        async fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+            self.get_raw_value_from_db(storage, key).await
+        }
+
+        async fn put_value(&mut self, _storage: StorageId, _key: &str, _val: &str) -> StorageResult<()> {
+            StorageResult::Error("Remote storage is read-only".to_string())
+        }
+
+        async fn put_raw_value(&mut self, _storage: StorageId, _key: &str, _val: Vec<u8>) -> StorageResult<()> {
+            StorageResult::Error("Remote storage is read-only".to_string())
+        }
+
+        async fn remove_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<()> {
+            StorageResult::Error("Remote storage is read-only".to_string())
+        }
+
+        async fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+            self.count_in_db(storage).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use v_individual_model::onto::individual::Individual;
+    use std::time::Duration;
+    use futures::executor::block_on;
+    use futures::future::join;
+    use nng::{Message, Protocol, Socket};
+
+    #[test]
+    fn test_async_remote_client() {
+        let addr = format!("inproc://test-async-remote-{}", std::process::id());
+
+        // Mock storage_manager: a value, a count and a miss
+        let server = Socket::new(Protocol::Rep0).unwrap();
+        server.listen(&addr).unwrap();
+        let responder = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let req = server.recv().unwrap();
+                let reply: &[u8] = match req.as_slice() {
+                    b"v,i,key" => b"value",
+                    b"c,t" => b"42",
+                    _ => b"[]",
+                };
+                server.send(Message::from(reply)).unwrap();
+            }
+            server.close();
+        });
+
+        let mut client = AsyncStorageROClient::new(&addr);
+        block_on(async {
+            assert_eq!(client.get_value(StorageId::Individuals, "key").await, StorageResult::Ok("value".to_string()));
+            assert_eq!(client.count(StorageId::Tickets).await, StorageResult::Ok(42));
+            assert_eq!(client.get_individual(StorageId::Individuals, "missing", &mut Individual::default()).await, StorageResult::NotFound);
+            assert!(client.put_value(StorageId::Individuals, "key", "value").await.is_error());
+        });
+        responder.join().unwrap();
+
+        // Concurrent requests from clones run in separate contexts of one socket
+        let addr = format!("{}-concurrent", addr);
+        let server = Socket::new(Protocol::Rep0).unwrap();
+        server.listen(&addr).unwrap();
+        let responder = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let req = server.recv().unwrap();
+                let reply = if req.as_slice() == b"c,i" { b"1".as_slice() } else { b"2".as_slice() };
+                server.send(Message::from(reply)).unwrap();
+            }
+            server.close();
+        });
+        let mut first = AsyncStorageROClient::new(&addr);
+        let mut second = first.clone();
+        let (a, b) = block_on(join(first.count(StorageId::Individuals), second.count(StorageId::Tickets)));
+        assert_eq!((a, b), (StorageResult::Ok(1), StorageResult::Ok(2)));
+        responder.join().unwrap();
+
+        let mut unreachable = AsyncStorageROClient::with_options(&format!("{}-missing", addr), Duration::from_millis(100), 1);
+        assert_eq!(block_on(unreachable.count(StorageId::Individuals)), StorageResult::NotReady);
+    }
+}
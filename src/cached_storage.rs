@@ -0,0 +1,237 @@
+// cached_storage.rs
+
+use std::collections::HashMap;
+use v_individual_model::onto::individual::Individual;
+use crate::common::{decorator_inner, forward_to_inner, parse_individual_value, utf8_value, ScanEntry, SectionEntry, SnapshotReader, Storage, StorageId, StorageResult};
+use crate::memory_storage::MemoryStorage;
+
+/// Декоратор, держащий ограниченный кеш значений в памяти перед медленным
+/// хранилищем (удаленный клиент, LMDB)
+///
+/// Чтение сначала ищет ключ в кеше, при промахе читает внутреннее хранилище и
+/// кладет значение в кеш. Запись проходит во внутреннее хранилище и обновляет
+/// запись кеша, а при ошибке записи удаляет ее. Отсутствие ключа не кешируется.
+/// В каждой секции кеша не больше capacity записей, вытесняется ключ, к которому
+/// дольше всего не обращались. Изменения, сделанные в обход декоратора, видны
+/// только после вытеснения ключа из кеша.
+pub struct CachedStorage<S: Storage> {
+    inner: S,
+    cache: MemoryStorage,
+}
+
+impl<S: Storage> CachedStorage<S> {
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: MemoryStorage::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cache.capacity().unwrap_or_default()
+    }
+
+    decorator_inner!(S);
+
+    /// Обновляет запись кеша после записи во внутреннее хранилище
+    fn cache_written(&mut self, storage: StorageId, key: &str, val: Vec<u8>, res: &StorageResult<()>) {
+        if res.is_ok() {
+            self.cache.put_raw_value(storage, key, val);
+        } else {
+            self.forget(storage, key);
+        }
+    }
+
+    fn forget(&mut self, storage: StorageId, key: &str) {
+        self.cache.remove_value(storage, key);
+    }
+
+    fn forget_section(&mut self, storage: StorageId) {
+        self.cache.clear(storage);
+    }
+}
+
+impl<S: Storage> Storage for CachedStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, uri), iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        if let StorageResult::Ok(val) = self.cache.get_raw_value(storage.clone(), key) {
+            return StorageResult::Ok(val);
+        }
+
+        let res = self.inner.get_raw_value(storage.clone(), key);
+        if let StorageResult::Ok(val) = &res {
+            self.cache.put_raw_value(storage, key, val.clone());
+        }
+        res
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        let res = self.inner.put_value(storage.clone(), key, val);
+        self.cache_written(storage, key, val.as_bytes().to_vec(), &res);
+        res
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let res = self.inner.put_raw_value(storage.clone(), key, val.clone());
+        self.cache_written(storage, key, val, &res);
+        res
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        self.forget(storage.clone(), key);
+        self.inner.remove_value(storage, key)
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        for (key, _) in entries {
+            self.forget(storage.clone(), key);
+        }
+        self.inner.put_many(storage, entries)
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        for (key, _) in pairs {
+            self.forget(storage.clone(), key);
+        }
+        self.inner.put_values_many(storage, pairs)
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        self.forget_section(storage.clone());
+        self.inner.replace_section(storage, entries)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.forget_section(storage.clone());
+        self.inner.clear(storage)
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        self.forget_section(storage.clone());
+        self.inner.remove_matching(storage, pred)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        self.forget_section(storage.clone());
+        self.inner.remove_prefix(storage, prefix)
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        self.forget(storage.clone(), key);
+        self.inner.increment_checked(storage, key, delta, limit)
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        self.forget(storage.clone(), key);
+        self.inner.compare_and_swap(storage, key, expected, new)
+    }
+
+    /// Ключ из кеша считается существующим без обращения к внутреннему хранилищу
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        if let StorageResult::Ok(true) = self.cache.contains(storage.clone(), key) {
+            return StorageResult::Ok(true);
+        }
+        self.inner.contains(storage, key)
+    }
+
+    fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        self.inner.non_empty_sections()
+    }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        self.inner.count_by_prefix(storage, depth_separator)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.inner.keys_with_prefix(storage, prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.inner.range(storage, start, end)
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        self.inner.iter_all()
+    }
+
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.inner.scan(storage)
+    }
+
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        self.inner.snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observe::{CountingObserver, ObservedStorage};
+    use crate::storage_factory::{DefaultStorageFactory, StorageConfig};
+
+    fn counted() -> ObservedStorage<MemoryStorage, CountingObserver> {
+        ObservedStorage::new(MemoryStorage::new(), CountingObserver::default())
+    }
+
+    #[test]
+    fn test_second_read_served_from_cache() {
+        let mut inner = counted();
+        assert!(inner.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(inner.put_value(StorageId::Individuals, "b", "2").is_ok());
+
+        let mut storage = CachedStorage::new(inner, 1);
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(storage.inner().observer().gets, 1);
+
+        // Misses are not cached
+        assert_eq!(storage.get_value(StorageId::Individuals, "missing"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Individuals, "missing"), StorageResult::NotFound);
+        assert_eq!(storage.inner().observer().misses, 2);
+
+        // "b" evicts "a" from the single-entry section
+        assert_eq!(storage.get_value(StorageId::Individuals, "b"), StorageResult::Ok("2".to_string()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(storage.inner().observer().gets, 3);
+    }
+
+    #[test]
+    fn test_writes_update_cache() {
+        let mut storage = CachedStorage::new(counted(), 16);
+
+        assert!(storage.put_value(StorageId::Tickets, "t", "old").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t", "new").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "t"), StorageResult::Ok("new".to_string()));
+        assert_eq!(storage.inner().observer().gets, 0);
+
+        assert!(storage.remove_value(StorageId::Tickets, "t").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "t"), StorageResult::NotFound);
+
+        // A rejected write drops the cached value
+        let mut storage = CachedStorage::new(crate::read_only_storage::ReadOnlyStorage::new(MemoryStorage::new()), 16);
+        assert!(storage.put_value(StorageId::Az, "k", "v").is_error());
+        assert_eq!(storage.get_value(StorageId::Az, "k"), StorageResult::NotFound);
+    }
+
+    #[test]
+    fn test_cached_config() {
+        let config = StorageConfig::Cached {
+            inner: Box::new(StorageConfig::Memory),
+            capacity: 8,
+        };
+        let mut storage = DefaultStorageFactory::new().create_storage_from_config(config).unwrap();
+
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(1));
+    }
+}
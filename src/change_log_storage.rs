@@ -0,0 +1,266 @@
+// change_log_storage.rs
+
+use std::collections::VecDeque;
+use v_individual_model::onto::individual::Individual;
+use crate::common::{forward_to_inner, SnapshotReader, Storage, StorageId, StorageResult};
+
+/// Тип изменения в журнале
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Put,
+    Remove,
+    /// Секция изменена целиком (replace_section, remove_matching): клиенту нужна полная пересинхронизация
+    Reset,
+}
+
+/// Запись журнала изменений секции
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeRecord {
+    pub key: String,
+    pub kind: ChangeKind,
+    pub seq: u64,
+}
+
+/// Ограниченный журнал одной секции
+#[derive(Debug, Default)]
+struct SectionLog {
+    records: VecDeque<ChangeRecord>,
+    last_seq: u64,
+    /// seq последней вытесненной записи
+    evicted_through: u64,
+}
+
+/// Декоратор с журналом изменений для опроса (polling) вместо подписки
+///
+/// Для каждой секции хранятся последние `capacity` изменений с монотонным
+/// номером seq. Клиент вызывает `changes_since(section, cursor)` и получает
+/// изменения после cursor и новый курсор; начальный курсор - 0. Если нужные
+/// записи уже вытеснены, возвращается ошибка и клиент должен перечитать секцию
+/// целиком.
+pub struct ChangeLogStorage<S: Storage> {
+    inner: S,
+    capacity: usize,
+    logs: [SectionLog; 3],
+}
+
+impl<S: Storage> ChangeLogStorage<S> {
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity: capacity.max(1),
+            logs: Default::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Изменения секции с seq > cursor и курсор для следующего вызова
+    pub fn changes_since(&mut self, storage: StorageId, cursor: u64) -> StorageResult<(Vec<ChangeRecord>, u64)> {
+        let log = &self.logs[Self::section_index(&storage)];
+        if cursor < log.evicted_through {
+            warn!("ChangeLogStorage: cursor {} for {:?} has been evicted (evicted through {})", cursor, storage, log.evicted_through);
+            return StorageResult::Error(format!("Cursor {} for {:?} has been evicted, full resync required", cursor, storage));
+        }
+
+        let changes: Vec<ChangeRecord> = log.records.iter().filter(|r| r.seq > cursor).cloned().collect();
+        StorageResult::Ok((changes, log.last_seq.max(cursor)))
+    }
+
+    fn section_index(storage: &StorageId) -> usize {
+        match storage {
+            StorageId::Individuals => 0,
+            StorageId::Tickets => 1,
+            StorageId::Az => 2,
+        }
+    }
+
+    fn record(&mut self, storage: &StorageId, key: &str, kind: ChangeKind) {
+        let capacity = self.capacity;
+        let log = &mut self.logs[Self::section_index(storage)];
+        log.last_seq += 1;
+        log.records.push_back(ChangeRecord {
+            key: key.to_string(),
+            kind,
+            seq: log.last_seq,
+        });
+        while log.records.len() > capacity {
+            if let Some(evicted) = log.records.pop_front() {
+                log.evicted_through = evicted.seq;
+            }
+        }
+    }
+
+    fn recorded<T>(&mut self, storage: &StorageId, key: &str, kind: ChangeKind, res: StorageResult<T>) -> StorageResult<T> {
+        if res.is_ok() {
+            self.record(storage, key, kind);
+        }
+        res
+    }
+}
+
+impl<S: Storage> Storage for ChangeLogStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        self.inner.get_individual(storage, id, iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        self.inner.get_value(storage, key)
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.inner.get_raw_value(storage, key)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        let res = self.inner.put_value(storage.clone(), key, val);
+        self.recorded(&storage, key, ChangeKind::Put, res)
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let res = self.inner.put_raw_value(storage.clone(), key, val);
+        self.recorded(&storage, key, ChangeKind::Put, res)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let res = self.inner.remove_value(storage.clone(), key);
+        self.recorded(&storage, key, ChangeKind::Remove, res)
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.inner.contains(storage, key)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        self.inner.read_value(storage, key, f)
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        self.inner.get_individuals(storage, ids)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.inner.keys_with_prefix(storage, prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.inner.range(storage, start, end)
+    }
+
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        self.inner.snapshot()
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        let res = self.inner.put_many(storage.clone(), entries);
+        if res.is_ok() {
+            for (key, _) in entries {
+                self.record(&storage, key, ChangeKind::Put);
+            }
+        }
+        res
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        let res = self.inner.put_values_many(storage.clone(), pairs);
+        if res.is_ok() {
+            for (key, _) in pairs {
+                self.record(&storage, key, ChangeKind::Put);
+            }
+        }
+        res
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        let res = self.inner.replace_section(storage.clone(), entries);
+        self.recorded(&storage, "", ChangeKind::Reset, res)
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        let res = self.inner.remove_matching(storage.clone(), pred);
+        match res {
+            StorageResult::Ok(0) => res,
+            res => self.recorded(&storage, "", ChangeKind::Reset, res),
+        }
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        let res = self.inner.increment_checked(storage.clone(), key, delta, limit);
+        self.recorded(&storage, key, ChangeKind::Put, res)
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        // Only a successful swap changes the value
+        match self.inner.compare_and_swap(storage.clone(), key, expected, new) {
+            StorageResult::Ok(true) => self.recorded(&storage, key, ChangeKind::Put, StorageResult::Ok(true)),
+            res => res,
+        }
+    }
+
+    fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        self.inner.non_empty_sections()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    fn keys(changes: &[ChangeRecord]) -> Vec<(&str, ChangeKind)> {
+        changes.iter().map(|c| (c.key.as_str(), c.kind)).collect()
+    }
+
+    #[test]
+    fn test_incremental_polling() {
+        let mut storage = ChangeLogStorage::new(MemoryStorage::new(), 16);
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "b", "2").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t", "x").is_ok());
+
+        let (changes, cursor) = match storage.changes_since(StorageId::Individuals, 0) {
+            StorageResult::Ok(res) => res,
+            other => panic!("changes_since failed: {:?}", other.map(|_| ())),
+        };
+        assert_eq!(keys(&changes), vec![("a", ChangeKind::Put), ("b", ChangeKind::Put)]);
+        assert_eq!(cursor, 2);
+
+        // Nothing new yet
+        assert_eq!(storage.changes_since(StorageId::Individuals, cursor), StorageResult::Ok((Vec::new(), 2)));
+
+        assert!(storage.put_value(StorageId::Individuals, "a", "3").is_ok());
+        assert!(storage.remove_value(StorageId::Individuals, "b").is_ok());
+        // Failed operations are not logged
+        assert_eq!(storage.remove_value(StorageId::Individuals, "missing"), StorageResult::NotFound);
+
+        let (changes, cursor) = match storage.changes_since(StorageId::Individuals, cursor) {
+            StorageResult::Ok(res) => res,
+            other => panic!("changes_since failed: {:?}", other.map(|_| ())),
+        };
+        assert_eq!(keys(&changes), vec![("a", ChangeKind::Put), ("b", ChangeKind::Remove)]);
+        assert_eq!(changes.iter().map(|c| c.seq).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(cursor, 4);
+    }
+
+    #[test]
+    fn test_evicted_cursor_requires_resync() {
+        let mut storage = ChangeLogStorage::new(MemoryStorage::new(), 2);
+        for key in ["a", "b", "c", "d"] {
+            assert!(storage.put_value(StorageId::Az, key, "v").is_ok());
+        }
+
+        // Records 1 and 2 are gone
+        assert!(storage.changes_since(StorageId::Az, 0).is_error());
+        assert!(storage.changes_since(StorageId::Az, 1).is_error());
+
+        let res = storage.changes_since(StorageId::Az, 2).map(|(changes, cursor)| (changes.len(), cursor));
+        assert_eq!(res, StorageResult::Ok((2, 4)));
+    }
+}
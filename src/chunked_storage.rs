@@ -0,0 +1,343 @@
+// chunked_storage.rs
+
+use v_individual_model::onto::individual::Individual;
+use crate::common::{decorator_inner, forward_to_inner, parse_individual_value, utf8_value, Storage, StorageId, StorageResult};
+
+/// Сигнатура манифеста, который хранится под исходным ключом вместо большого значения
+const MANIFEST_MAGIC: [u8; 4] = [0x00, b'V', b'C', b'H'];
+/// Сигнатура + общая длина (u64 LE) + число чанков (u32 LE) + поколение (u32 LE)
+const MANIFEST_LEN: usize = MANIFEST_MAGIC.len() + 8 + 4 + 4;
+
+/// Разделитель между исходным ключом и номером чанка
+const CHUNK_SEPARATOR: char = '\u{1f}';
+
+/// Ошибка обращения по ключу, содержащему зарезервированный разделитель чанков
+pub const CHUNK_KEY_ERROR: &str = "key contains the reserved chunk separator";
+
+/// Описание значения, разбитого на чанки
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ChunkManifest {
+    total_len: u64,
+    chunks: u32,
+    /// Номер версии значения: чанки новой версии пишутся под новыми ключами
+    generation: u32,
+}
+
+impl ChunkManifest {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(MANIFEST_LEN);
+        buf.extend_from_slice(&MANIFEST_MAGIC);
+        buf.extend_from_slice(&self.total_len.to_le_bytes());
+        buf.extend_from_slice(&self.chunks.to_le_bytes());
+        buf.extend_from_slice(&self.generation.to_le_bytes());
+        buf
+    }
+
+    /// Похоже ли значение на манифест: такие значения пользователя тоже разбиваются
+    /// на чанки, иначе при чтении они были бы приняты за манифест
+    fn is_framed(data: &[u8]) -> bool {
+        data.len() == MANIFEST_LEN && data.starts_with(&MANIFEST_MAGIC)
+    }
+
+    /// Манифест, общая длина которого помещается в его чанки размера chunk_size
+    fn decode(data: &[u8], chunk_size: usize) -> Option<Self> {
+        if !Self::is_framed(data) {
+            return None;
+        }
+        let total_len = u64::from_le_bytes(data[4..12].try_into().ok()?);
+        let chunks = u32::from_le_bytes(data[12..16].try_into().ok()?);
+        let generation = u32::from_le_bytes(data[16..20].try_into().ok()?);
+        if total_len > u64::from(chunks).saturating_mul(chunk_size as u64) {
+            return None;
+        }
+        Some(ChunkManifest { total_len, chunks, generation })
+    }
+}
+
+fn check_key(key: &str) -> StorageResult<()> {
+    if key.contains(CHUNK_SEPARATOR) {
+        error!("ChunkedStorage: key=[{}] contains the reserved chunk separator", key.escape_debug());
+        return StorageResult::Error(CHUNK_KEY_ERROR.to_string());
+    }
+    StorageResult::Ok(())
+}
+
+/// Декоратор, разбивающий большие значения на чанки фиксированного размера
+///
+/// Значения длиннее `chunk_size` сохраняются под ключами `<key>\x1f<поколение>.0`,
+/// `<key>\x1f<поколение>.1`, ..., а под исходным ключом записывается небольшой манифест.
+/// Короткое значение, совпадающее по формату с манифестом, тоже пишется через чанки.
+/// При чтении значение собирается прозрачно, `remove_value` удаляет все чанки.
+/// Перезапись сначала пишет чанки нового поколения, затем манифест и только потом
+/// удаляет чанки прежнего значения, поэтому сбой посередине оставляет читаемой
+/// одну из версий. Символ `\x1f` зарезервирован: ключи с ним отклоняются с CHUNK_KEY_ERROR.
+///
+/// Замечание: `count` делегируется внутреннему хранилищу и учитывает записи чанков.
+pub struct ChunkedStorage<S: Storage> {
+    inner: S,
+    chunk_size: usize,
+}
+
+impl<S: Storage> ChunkedStorage<S> {
+    /// Создает декоратор; значения длиннее chunk_size будут разбиты на чанки
+    pub fn new(inner: S, chunk_size: usize) -> Self {
+        Self {
+            inner,
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    decorator_inner!(S);
+
+    fn chunk_key(key: &str, generation: u32, idx: u32) -> String {
+        format!("{}{}{}.{}", key, CHUNK_SEPARATOR, generation, idx)
+    }
+
+    fn read_manifest(&mut self, storage: &StorageId, key: &str) -> Option<ChunkManifest> {
+        match self.inner.get_raw_value(storage.clone(), key) {
+            StorageResult::Ok(data) => ChunkManifest::decode(&data, self.chunk_size),
+            _ => None,
+        }
+    }
+
+    fn remove_chunks(&mut self, storage: &StorageId, key: &str, manifest: ChunkManifest) -> StorageResult<()> {
+        for idx in 0..manifest.chunks {
+            match self.inner.remove_value(storage.clone(), &Self::chunk_key(key, manifest.generation, idx)) {
+                StorageResult::Ok(()) | StorageResult::NotFound => {},
+                other => return other,
+            }
+        }
+        StorageResult::Ok(())
+    }
+
+    fn assemble(&mut self, storage: &StorageId, key: &str, manifest: ChunkManifest) -> StorageResult<Vec<u8>> {
+        // decode ограничивает total_len емкостью чанков, но не доверяет ему сверх этого
+        let mut data = Vec::with_capacity(usize::try_from(manifest.total_len).unwrap_or(0));
+        for idx in 0..manifest.chunks {
+            let chunk_key = Self::chunk_key(key, manifest.generation, idx);
+            match self.inner.get_raw_value(storage.clone(), &chunk_key) {
+                StorageResult::Ok(chunk) => data.extend_from_slice(&chunk),
+                StorageResult::NotFound => {
+                    error!("ChunkedStorage: missing chunk [{}] of key=[{}]", chunk_key, key);
+                    return StorageResult::Error(format!("Missing chunk {} of key {}", idx, key));
+                },
+                other => return other,
+            }
+        }
+
+        if data.len() as u64 != manifest.total_len {
+            error!("ChunkedStorage: length mismatch for key=[{}], expected={}, got={}", key, manifest.total_len, data.len());
+            return StorageResult::Error(format!("Corrupted chunked value for key {}", key));
+        }
+
+        StorageResult::Ok(data)
+    }
+
+    fn put_bytes(&mut self, storage: StorageId, key: &str, val: &[u8]) -> StorageResult<()> {
+        let res = check_key(key);
+        if !res.is_ok() {
+            return res;
+        }
+
+        let old = self.read_manifest(&storage, key);
+        let res = if val.len() <= self.chunk_size && !ChunkManifest::is_framed(val) {
+            self.inner.put_raw_value(storage.clone(), key, val.to_vec())
+        } else {
+            let manifest = ChunkManifest {
+                total_len: val.len() as u64,
+                chunks: 0,
+                generation: old.map_or(0, |old| old.generation.wrapping_add(1)),
+            };
+            self.put_chunked(&storage, key, val, manifest)
+        };
+        if !res.is_ok() {
+            return res;
+        }
+
+        // Чанки прежнего значения больше не нужны: манифест уже указывает на новые
+        match old {
+            Some(old) => self.remove_chunks(&storage, key, old),
+            None => StorageResult::Ok(()),
+        }
+    }
+
+    /// Пишет чанки нового поколения и затем манифест; при ошибке записанные чанки удаляются
+    fn put_chunked(&mut self, storage: &StorageId, key: &str, val: &[u8], mut manifest: ChunkManifest) -> StorageResult<()> {
+        for chunk in val.chunks(self.chunk_size) {
+            let res = self.inner.put_raw_value(storage.clone(), &Self::chunk_key(key, manifest.generation, manifest.chunks), chunk.to_vec());
+            if !res.is_ok() {
+                let _ = self.remove_chunks(storage, key, manifest);
+                return res;
+            }
+            manifest.chunks += 1;
+        }
+
+        let res = self.inner.put_raw_value(storage.clone(), key, manifest.encode());
+        if !res.is_ok() {
+            let _ = self.remove_chunks(storage, key, manifest);
+        }
+        res
+    }
+}
+
+impl<S: Storage> Storage for ChunkedStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, uri), iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let res = check_key(key);
+        if !res.is_ok() {
+            return res.map(|_| Vec::new());
+        }
+
+        match self.inner.get_raw_value(storage.clone(), key) {
+            StorageResult::Ok(data) => match ChunkManifest::decode(&data, self.chunk_size) {
+                Some(manifest) => self.assemble(&storage, key, manifest),
+                None => StorageResult::Ok(data),
+            },
+            other => other,
+        }
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        self.put_bytes(storage, key, val.as_bytes())
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        self.put_bytes(storage, key, &val)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let res = check_key(key);
+        if !res.is_ok() {
+            return res;
+        }
+
+        if let Some(manifest) = self.read_manifest(&storage, key) {
+            let res = self.remove_chunks(&storage, key, manifest);
+            if !res.is_ok() {
+                return res;
+            }
+        }
+        self.inner.remove_value(storage, key)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.inner.clear(storage)
+    }
+
+    /// Манифест хранится под исходным ключом, поэтому чанки не читаются
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        check_key(key).and_then(|_| self.inner.contains(storage, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_chunked_roundtrip_and_remove() {
+        let mut storage = ChunkedStorage::new(MemoryStorage::new(), 4);
+
+        let big: Vec<u8> = (0..10u8).collect();
+        assert!(storage.put_raw_value(StorageId::Individuals, "big", big.clone()).is_ok());
+
+        // 3 chunks + manifest
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(4));
+        let first = ChunkedStorage::<MemoryStorage>::chunk_key("big", 0, 0);
+        let last = ChunkedStorage::<MemoryStorage>::chunk_key("big", 0, 2);
+        assert_eq!(storage.inner_mut().get_raw_value(StorageId::Individuals, &first), StorageResult::Ok(vec![0, 1, 2, 3]));
+        assert_eq!(storage.inner_mut().get_raw_value(StorageId::Individuals, &last), StorageResult::Ok(vec![8, 9]));
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "big"), StorageResult::Ok(big));
+
+        assert!(storage.remove_value(StorageId::Individuals, "big").is_ok());
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "big"), StorageResult::NotFound);
+        for idx in 0..3 {
+            let chunk_key = ChunkedStorage::<MemoryStorage>::chunk_key("big", 0, idx);
+            assert_eq!(storage.inner_mut().get_raw_value(StorageId::Individuals, &chunk_key), StorageResult::NotFound);
+        }
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+    }
+
+    #[test]
+    fn test_chunked_small_and_overwrite() {
+        let mut storage = ChunkedStorage::new(MemoryStorage::new(), 8);
+
+        // Small values are stored as is
+        assert!(storage.put_value(StorageId::Tickets, "small", "abc").is_ok());
+        assert_eq!(storage.inner_mut().get_value(StorageId::Tickets, "small"), StorageResult::Ok("abc".to_string()));
+
+        // Overwriting a chunked value with a small one drops stale chunks
+        assert!(storage.put_value(StorageId::Tickets, "key", "0123456789abcdefXYZ").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "key"), StorageResult::Ok("0123456789abcdefXYZ".to_string()));
+        assert!(storage.put_value(StorageId::Tickets, "key", "short").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "key"), StorageResult::Ok("short".to_string()));
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(2));
+    }
+
+    #[test]
+    fn test_chunked_overwrite_keeps_old_value_until_manifest() {
+        let mut storage = ChunkedStorage::new(MemoryStorage::new(), 4);
+        assert!(storage.put_value(StorageId::Az, "key", "0123456789").is_ok());
+        assert!(storage.put_value(StorageId::Az, "key", "abcdefgh").is_ok());
+        assert_eq!(storage.get_value(StorageId::Az, "key"), StorageResult::Ok("abcdefgh".to_string()));
+
+        // The new value went to generation 1, every chunk of generation 0 is gone
+        let mut keys = storage.inner_mut().keys_with_prefix(StorageId::Az, "key").unwrap();
+        keys.sort();
+        let expected: Vec<String> = ["key".to_string(), ChunkedStorage::<MemoryStorage>::chunk_key("key", 1, 0), ChunkedStorage::<MemoryStorage>::chunk_key("key", 1, 1)].into();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_value_that_looks_like_a_manifest() {
+        let mut storage = ChunkedStorage::new(MemoryStorage::new(), 64);
+
+        // A short user value with the manifest signature and a huge length
+        let mut val = MANIFEST_MAGIC.to_vec();
+        val.extend_from_slice(&u64::MAX.to_le_bytes());
+        val.extend_from_slice(&u32::MAX.to_le_bytes());
+        val.extend_from_slice(&0u32.to_le_bytes());
+        assert_eq!(val.len(), 20);
+
+        assert!(storage.put_raw_value(StorageId::Individuals, "v", val.clone()).is_ok());
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "v"), StorageResult::Ok(val.clone()));
+        assert!(storage.put_value(StorageId::Individuals, "v", "plain").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "v"), StorageResult::Ok("plain".to_string()));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(1));
+
+        // Written past the decorator, such a manifest is not trusted
+        assert!(storage.inner_mut().put_raw_value(StorageId::Individuals, "raw", val.clone()).is_ok());
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "raw"), StorageResult::Ok(val));
+        assert!(storage.remove_value(StorageId::Individuals, "raw").is_ok());
+    }
+
+    #[test]
+    fn test_chunk_separator_is_reserved() {
+        let mut storage = ChunkedStorage::new(MemoryStorage::new(), 4);
+        let reserved = StorageResult::Error(CHUNK_KEY_ERROR.to_string());
+        let chunk_key = ChunkedStorage::<MemoryStorage>::chunk_key("big", 0, 0);
+
+        assert_eq!(storage.put_value(StorageId::Individuals, &chunk_key, "v"), reserved);
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, &chunk_key).map(|_| ()), reserved);
+        assert_eq!(storage.remove_value(StorageId::Individuals, &chunk_key), reserved);
+        assert_eq!(storage.contains(StorageId::Individuals, &chunk_key).map(|_| ()), reserved);
+
+        // '#' is an ordinary character in user keys
+        assert!(storage.put_value(StorageId::Individuals, "owl#Thing", "v").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "owl#Thing"), StorageResult::Ok("v".to_string()));
+    }
+}
@@ -1,19 +1,201 @@
 use v_individual_model::onto::individual::Individual;
+use crate::storage_factory::StorageError;
 use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum StorageMode {
     ReadOnly,
     ReadWrite,
 }
 
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum StorageId {
     Individuals,
     Tickets,
     Az,
 }
 
+impl StorageId {
+    /// Все секции хранилища
+    pub fn all() -> [StorageId; 3] {
+        [StorageId::Individuals, StorageId::Tickets, StorageId::Az]
+    }
+
+    /// Имя секции: "individuals", "tickets" или "az" (совпадает с именем в serde)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageId::Individuals => "individuals",
+            StorageId::Tickets => "tickets",
+            StorageId::Az => "az",
+        }
+    }
+}
+
+/// Разбор имени секции из конфигурации или командной строки, см. StorageId::as_str
+impl std::str::FromStr for StorageId {
+    type Err = StorageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StorageId::all()
+            .into_iter()
+            .find(|id| id.as_str() == s)
+            .ok_or_else(|| StorageError::InvalidConfiguration(format!("Unknown storage id: {}", s)))
+    }
+}
+
+impl std::fmt::Display for StorageId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Статистика секции хранилища (см. Storage::stats)
+///
+/// size_bytes - размер занятых страниц для LMDB/MDBX и оценка по длинам ключей
+/// и значений для хранилища в памяти; depth - высота B-дерева,
+/// overflow_pages - страницы больших значений.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    pub entries: usize,
+    pub size_bytes: u64,
+    pub depth: u32,
+    pub overflow_pages: u64,
+}
+
+/// Статистика транзакций бэкенда (LMDB/MDBX)
+///
+/// reads - открытые транзакции чтения, writes - открытые транзакции записи;
+/// committed/aborted относятся только к транзакциям записи. Транзакция записи,
+/// удаленная без фиксации, считается прерванной; неудачное открытие транзакции
+/// не учитывается. slow - операции дольше порога медленных операций (LMDB),
+/// считаются только при заданном пороге.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxnStats {
+    pub begun: u64,
+    pub committed: u64,
+    pub aborted: u64,
+    pub reads: u64,
+    pub writes: u64,
+    pub slow: u64,
+}
+
+impl std::ops::Add for TxnStats {
+    type Output = TxnStats;
+
+    fn add(self, other: TxnStats) -> TxnStats {
+        TxnStats {
+            begun: self.begun + other.begun,
+            committed: self.committed + other.committed,
+            aborted: self.aborted + other.aborted,
+            reads: self.reads + other.reads,
+            writes: self.writes + other.writes,
+            slow: self.slow + other.slow,
+        }
+    }
+}
+
+/// Счетчики транзакций, которые ведут LMDB/MDBX инстансы
+#[derive(Debug, Default)]
+pub(crate) struct TxnCounters {
+    begun: AtomicU64,
+    committed: AtomicU64,
+    aborted: AtomicU64,
+    reads: AtomicU64,
+    writes: AtomicU64,
+    slow: AtomicU64,
+}
+
+impl TxnCounters {
+    pub(crate) fn record_read(&self) {
+        self.begun.fetch_add(1, Ordering::Relaxed);
+        self.reads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Учесть открытую транзакцию записи; исход фиксируется через возвращенную запись
+    pub(crate) fn begin_write(&self) -> WriteTxnRecord<'_> {
+        self.begun.fetch_add(1, Ordering::Relaxed);
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        WriteTxnRecord {
+            counters: self,
+            committed: false,
+        }
+    }
+
+    pub(crate) fn record_slow(&self) {
+        self.slow.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> TxnStats {
+        TxnStats {
+            begun: self.begun.load(Ordering::Relaxed),
+            committed: self.committed.load(Ordering::Relaxed),
+            aborted: self.aborted.load(Ordering::Relaxed),
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            slow: self.slow.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Учет одной транзакции записи: без вызова commit при удалении считается прерванной
+pub(crate) struct WriteTxnRecord<'a> {
+    counters: &'a TxnCounters,
+    committed: bool,
+}
+
+impl WriteTxnRecord<'_> {
+    /// Транзакция успешно зафиксирована
+    pub(crate) fn commit(mut self) {
+        self.committed = true;
+        self.counters.committed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for WriteTxnRecord<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.counters.aborted.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Span операции LMDB/MDBX, активный до удаления guard (feature tracing)
+#[cfg(feature = "tracing")]
+pub(crate) type TxnSpan = tracing::span::EnteredSpan;
+
+/// Без feature tracing span не создается
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct TxnSpan;
+
+/// Открывает span storage_txn с видом хранилища, операцией, путем и длиной ключа
+#[cfg(feature = "tracing")]
+pub(crate) fn txn_span(kind: &'static str, op: &'static str, path: &str, key_len: usize) -> TxnSpan {
+    tracing::debug_span!("storage_txn", kind, op, path, key_len).entered()
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn txn_span(_kind: &'static str, _op: &'static str, _path: &str, _key_len: usize) -> TxnSpan {
+    TxnSpan
+}
+
+/// Событие о завершении операции в текущем span
+pub(crate) fn txn_done(_ok: bool) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(ok = _ok, "storage txn done");
+}
+
+/// Событие о повторе операции после неудачной попытки
+pub(crate) fn txn_retry(_kind: &'static str, _op: &'static str, _path: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(kind = _kind, op = _op, path = _path, "storage txn retry");
+}
+
 /// Унифицированный результат операций с хранилищем
 #[derive(Debug, Clone, PartialEq)]
 pub enum StorageResult<T> {
@@ -68,6 +250,108 @@ impl<T> StorageResult<T> {
             StorageResult::Error(msg) => StorageResult::Error(msg),
         }
     }
+
+    /// Значение при Ok, иначе паника с вариантом и текстом ошибки.
+    /// Для тестов и прототипов
+    #[track_caller]
+    pub fn unwrap(self) -> T {
+        match self {
+            StorageResult::Ok(value) => value,
+            other => panic!("called `StorageResult::unwrap()` on a `{:?}` value", other.map(|_| ())),
+        }
+    }
+
+    /// Значение при Ok, иначе паника с сообщением msg, вариантом и текстом ошибки
+    #[track_caller]
+    pub fn expect(self, msg: &str) -> T {
+        match self {
+            StorageResult::Ok(value) => value,
+            other => panic!("{}: {:?}", msg, other.map(|_| ())),
+        }
+    }
+
+    /// Значение при Ok, иначе None
+    pub fn ok(self) -> Option<T> {
+        match self {
+            StorageResult::Ok(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Преобразует в std::result::Result для использования с оператором ?
+    pub fn into_result(self) -> Result<T, StorageError> {
+        match self {
+            StorageResult::Ok(value) => Ok(value),
+            StorageResult::NotFound => Err(StorageError::NotFound),
+            StorageResult::NotReady => Err(StorageError::NotReady),
+            StorageResult::UnprocessableEntity => Err(StorageError::Unprocessable),
+            StorageResult::Error(msg) => Err(StorageError::Backend(msg)),
+        }
+    }
+
+    /// Преобразует в Result с собственным типом ошибки: f получает неуспешный
+    /// статус без значения, например `get_value(..).ok_or_else(|_| MyError)?`
+    pub fn ok_or_else<E, F: FnOnce(StorageResult<()>) -> E>(self, f: F) -> Result<T, E> {
+        match self {
+            StorageResult::Ok(value) => Ok(value),
+            other => Err(f(other.map(|_| ()))),
+        }
+    }
+}
+
+/// Сообщение об ошибке для пустого ключа
+///
+/// Пустые ключи запрещены во всех бэкендах: LMDB не принимает ключи нулевой
+/// длины, поэтому остальные бэкенды отклоняют их так же, а не по-разному.
+pub const EMPTY_KEY_ERROR: &str = "empty key";
+
+pub(crate) fn empty_key_error<T>() -> StorageResult<T> {
+    StorageResult::Error(EMPTY_KEY_ERROR.to_string())
+}
+
+/// Ошибка записи в хранилище, открытое в режиме StorageMode::ReadOnly
+pub const READ_ONLY_ERROR: &str = "storage is read-only";
+
+pub(crate) fn read_only_error<T>() -> StorageResult<T> {
+    StorageResult::Error(READ_ONLY_ERROR.to_string())
+}
+
+/// Ошибка обращения к данным, блокировка которых отравлена паникой другого потока
+pub const LOCK_POISONED_ERROR: &str = "lock poisoned";
+
+pub(crate) fn lock_poisoned_error<T>() -> StorageResult<T> {
+    StorageResult::Error(LOCK_POISONED_ERROR.to_string())
+}
+
+fn namespaces_unsupported<T>() -> StorageResult<T> {
+    StorageResult::Error("named namespaces are not supported by this storage".to_string())
+}
+
+/// Число попыток открыть окружение LMDB или базу MDBX, после которых
+/// конструктор возвращает ошибку
+pub const DEFAULT_OPEN_ATTEMPTS: u32 = 5;
+
+// Пауза между попытками открыть окружение
+pub(crate) const OPEN_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Some - Ok, None - NotFound
+impl<T> From<Option<T>> for StorageResult<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => StorageResult::Ok(value),
+            None => StorageResult::NotFound,
+        }
+    }
+}
+
+/// Ошибка превращается в Error с текстом ошибки
+impl<T, E: std::fmt::Display> From<Result<T, E>> for StorageResult<T> {
+    fn from(value: Result<T, E>) -> Self {
+        match value {
+            Ok(value) => StorageResult::Ok(value),
+            Err(e) => StorageResult::Error(e.to_string()),
+        }
+    }
 }
 
 impl<T> From<StorageResult<T>> for bool {
@@ -76,6 +360,152 @@ impl<T> From<StorageResult<T>> for bool {
     }
 }
 
+/// Элемент сквозного обхода: секция, ключ, значение
+pub type SectionEntry = (StorageId, String, Vec<u8>);
+
+/// Элемент обхода одной секции (см. Storage::scan): ключ и значение
+pub type ScanEntry = (Vec<u8>, Vec<u8>);
+
+/// Загрузчик содержимого одной секции, вызывается при переходе итератора к ней
+pub(crate) type SectionLoader = Box<dyn FnOnce() -> StorageResult<Vec<(String, Vec<u8>)>>>;
+
+/// Итератор по нескольким секциям подряд: каждая секция читается только
+/// когда итератор до нее доходит. Если секцию не удалось прочитать, обход
+/// на ней заканчивается, а не переходит молча к следующей
+pub(crate) struct SectionChain {
+    pending: VecDeque<(StorageId, SectionLoader)>,
+    current: Option<StorageId>,
+    entries: std::vec::IntoIter<(String, Vec<u8>)>,
+}
+
+impl SectionChain {
+    pub(crate) fn new(sections: Vec<(StorageId, SectionLoader)>) -> Self {
+        Self {
+            pending: sections.into(),
+            current: None,
+            entries: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Iterator for SectionChain {
+    type Item = SectionEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(storage) = &self.current
+                && let Some((key, val)) = self.entries.next()
+            {
+                return Some((storage.clone(), key, val));
+            }
+
+            let (storage, loader) = self.pending.pop_front()?;
+            match loader() {
+                StorageResult::Ok(entries) => self.entries = entries.into_iter(),
+                _ => {
+                    self.pending.clear();
+                    return None;
+                },
+            }
+            self.current = Some(storage);
+        }
+    }
+}
+
+/// Записи одной секции поверх iter_all, который обходит секции по порядку:
+/// предыдущие секции пропускаются, последующие не читаются
+pub(crate) fn section_entries<S: Storage + ?Sized>(
+    storage: &mut S,
+    section: StorageId,
+) -> StorageResult<Box<dyn Iterator<Item = (String, Vec<u8>)>>> {
+    storage.iter_all().map(|entries| {
+        let before = section.clone();
+        let entries = entries
+            .skip_while(move |(s, _, _)| *s != before)
+            .take_while(move |(s, _, _)| *s == section)
+            .map(|(_, key, val)| (key, val));
+        Box::new(entries) as Box<dyn Iterator<Item = (String, Vec<u8>)>>
+    })
+}
+
+/// Учитывает ключ в группе по подстроке до первого separator (ключ без separator - своя группа)
+pub(crate) fn add_prefix_count(counts: &mut HashMap<String, usize>, key: &str, separator: char) {
+    let prefix = key.split(separator).next().unwrap_or(key);
+    match counts.get_mut(prefix) {
+        Some(count) => *count += 1,
+        None => {
+            counts.insert(prefix.to_string(), 1);
+        },
+    }
+}
+
+/// Разбирает прочитанное значение в iraw; ошибка разбора - UnprocessableEntity.
+/// Общая реализация get_individual поверх get_raw_value для декораторов
+pub(crate) fn parse_individual_value(value: StorageResult<Vec<u8>>, iraw: &mut Individual) -> StorageResult<()> {
+    value.and_then(|data| {
+        iraw.set_raw(&data);
+        if v_individual_model::onto::parser::parse_raw(iraw).is_ok() {
+            StorageResult::Ok(())
+        } else {
+            StorageResult::UnprocessableEntity
+        }
+    })
+}
+
+/// Прочитанное значение как строка; значение не в UTF-8 - ошибка.
+/// Общая реализация get_value поверх get_raw_value для декораторов
+pub(crate) fn utf8_value(value: StorageResult<Vec<u8>>) -> StorageResult<String> {
+    value.and_then(|data| match String::from_utf8(data) {
+        Ok(value) => StorageResult::Ok(value),
+        Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+    })
+}
+
+/// Методы inner, inner_mut и into_inner декоратора, хранящего обернутое хранилище в поле inner
+macro_rules! decorator_inner {
+    ($inner:ty) => {
+        pub fn inner(&self) -> &$inner {
+            &self.inner
+        }
+
+        pub fn inner_mut(&mut self) -> &mut $inner {
+            &mut self.inner
+        }
+
+        pub fn into_inner(self) -> $inner {
+            self.inner
+        }
+    };
+}
+pub(crate) use decorator_inner;
+
+/// count, stats, is_persistent, sync и health_check декоратора, передаваемые
+/// хранилищу в поле inner без изменений
+macro_rules! forward_to_inner {
+    () => {
+        fn count(&mut self, storage: $crate::common::StorageId) -> $crate::common::StorageResult<usize> {
+            self.inner.count(storage)
+        }
+
+        fn stats(&mut self, storage: $crate::common::StorageId) -> $crate::common::StorageResult<$crate::common::StorageStats> {
+            self.inner.stats(storage)
+        }
+
+        fn is_persistent(&self) -> bool {
+            self.inner.is_persistent()
+        }
+
+        fn sync(&mut self, force: bool) -> $crate::common::StorageResult<()> {
+            self.inner.sync(force)
+        }
+
+        fn health_check(&mut self) -> $crate::common::StorageResult<()> {
+            self.inner.health_check()
+        }
+    };
+}
+pub(crate) use forward_to_inner;
+
 pub trait Storage {
     fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()>;
     fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String>;
@@ -85,6 +515,410 @@ pub trait Storage {
     fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()>;
     fn count(&mut self, storage: StorageId) -> StorageResult<usize>;
 
+    /// Размер и заполненность секции. По умолчанию известно только число записей.
+    fn stats(&mut self, storage: StorageId) -> StorageResult<StorageStats> {
+        self.count(storage).map(|entries| StorageStats {
+            entries,
+            ..StorageStats::default()
+        })
+    }
+
+    /// Сохраняются ли данные между перезапусками процесса.
+    /// По умолчанию false: для неизвестного бэкенда безопаснее считать данные
+    /// эфемерными и выполнить начальное заполнение
+    fn is_persistent(&self) -> bool {
+        false
+    }
+
+    /// Снимок для согласованного чтения нескольких ключей: все чтения через
+    /// снимок видят данные на момент его создания, независимо от последующих
+    /// записей. LMDB/MDBX держат открытые транзакции чтения, память - копию секций.
+    ///
+    /// Согласованность гарантируется только в пределах одной секции: у LMDB/MDBX
+    /// каждая секция - отдельное окружение со своей транзакцией, и транзакции
+    /// открываются по очереди, поэтому запись, пришедшаяся между ними, может
+    /// попасть в одну секцию снимка и не попасть в другую. Пока снимок жив,
+    /// LMDB не может увеличить размер карты, поэтому снимок не стоит держать дольше нужного.
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        StorageResult::Error("snapshots are not supported by this storage".to_string())
+    }
+
+    /// Читает значение и декодирует его как T (например, u64 в little-endian).
+    /// UnprocessableEntity, если значение не удалось декодировать
+    fn get_typed<T: FromMdbValue>(&mut self, storage: StorageId, key: &str) -> StorageResult<T>
+    where
+        Self: Sized,
+    {
+        self.get_raw_value(storage, key).and_then(|data| match T::from_mdb_value(&data) {
+            Some(val) => StorageResult::Ok(val),
+            None => StorageResult::UnprocessableEntity,
+        })
+    }
+
+    /// Записывает строковое значение и возвращает число записанных байт
+    /// (длина ключа плюс длина значения), например для учета объема по арендаторам
+    fn put_value_sized(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<usize> {
+        self.put_value(storage, key, val).map(|_| key.len() + val.len())
+    }
+
+    /// Записывает несколько значений; LMDB/MDBX делают это в одной транзакции
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        for (key, val) in entries {
+            let res = self.put_raw_value(storage.clone(), key, val.to_vec());
+            if !res.is_ok() {
+                return res;
+            }
+        }
+        StorageResult::Ok(())
+    }
+
+    /// Записывает набор строковых пар. LMDB/MDBX пишут их в одной транзакции,
+    /// память - под одной блокировкой: при ошибке не сохраняется ни одна пара.
+    /// Реализация по умолчанию вызывает put_value по очереди и не атомарна
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        for (key, val) in pairs {
+            let res = self.put_value(storage.clone(), key, val);
+            if !res.is_ok() {
+                return res;
+            }
+        }
+        StorageResult::Ok(())
+    }
+
+    /// Атомарно заменяет содержимое секции: очищает ее и записывает entries
+    /// в одной транзакции. Читатели видят либо старый, либо новый набор целиком.
+    fn replace_section(&mut self, storage: StorageId, _entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        StorageResult::Error(format!("replace_section is not supported for {:?}", storage))
+    }
+
+    /// Удаляет все записи секции - replace_section с пустым набором,
+    /// т.е. для LMDB/MDBX одна транзакция записи
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.replace_section(storage, &[])
+    }
+
+    /// Сбрасывает на диск данные зафиксированных транзакций: барьер долговечности
+    /// после пакета записей для бэкендов, открытых без синхронизации при каждой фиксации.
+    /// force - синхронизировать, даже если бэкенд настроен на отложенную запись.
+    /// По умолчанию Ok для хранилищ в памяти и ошибка для постоянных.
+    fn sync(&mut self, _force: bool) -> StorageResult<()> {
+        if self.is_persistent() {
+            StorageResult::Error("sync is not supported".to_string())
+        } else {
+            StorageResult::Ok(())
+        }
+    }
+
+    /// Проверяет, что бэкенд доступен и может обслуживать запросы;
+    /// NotReady, если до него не удается достучаться.
+    /// По умолчанию Ok: хранилищу без внешних ресурсов проверять нечего
+    fn health_check(&mut self) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    /// Возвращает значение, только если оно удовлетворяет предикату:
+    /// Ok(Some(v)) - совпало, Ok(None) - не совпало, NotFound - ключа нет.
+    /// LMDB/MDBX проверяют предикат на заимствованных байтах, не копируя отвергнутое значение.
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        self.get_raw_value(storage, key).map(|val| if pred(&val) { Some(val) } else { None })
+    }
+
+    /// Вызывает f со значением ключа без копирования: LMDB/MDBX передают байты,
+    /// заимствованные из транзакции чтения, память - значение под блокировкой чтения.
+    /// Реализация по умолчанию читает значение через get_raw_value. См. with_value
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        self.get_raw_value(storage, key).map(|val| f(&val))
+    }
+
+    /// Результат f над значением ключа, прочитанным без копирования (см. read_value).
+    /// Для Box<dyn Storage> доступен через реализацию Storage для Box
+    fn with_value<R>(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8]) -> R) -> StorageResult<R>
+    where
+        Self: Sized,
+    {
+        let mut out = None;
+        self.read_value(storage, key, &mut |val| out = Some(f(val)))
+            .and_then(|_| out.map_or(StorageResult::NotFound, StorageResult::Ok))
+    }
+
+    /// Удаляет из секции все записи, для значений которых pred возвращает true,
+    /// и возвращает число удаленных записей. Для LMDB/MDBX выполняется в одной
+    /// транзакции записи, без построения списка ключей в памяти.
+    ///
+    /// Предикат вызывается, пока удерживается транзакция записи (или блокировка
+    /// в памяти), поэтому он не должен обращаться к этому же хранилищу - иначе
+    /// возможна взаимная блокировка.
+    fn remove_matching(&mut self, storage: StorageId, _pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        StorageResult::Error(format!("remove_matching is not supported for {:?}", storage))
+    }
+
+    /// Удаляет из секции все ключи, начинающиеся с prefix, и возвращает число
+    /// удаленных записей. LMDB/MDBX выполняют удаление в одной транзакции записи,
+    /// позиционируя курсор на prefix; по умолчанию ключи удаляются поштучно.
+    /// Пустой префикс отклоняется, для очистки секции есть clear.
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        if prefix.is_empty() {
+            return empty_key_error();
+        }
+
+        let keys = match self.keys_with_prefix(storage.clone(), prefix) {
+            StorageResult::Ok(keys) => keys,
+            other => return other.map(|_| 0),
+        };
+
+        let mut removed = 0;
+        for key in keys {
+            match self.remove_value(storage.clone(), &key) {
+                StorageResult::Ok(()) => removed += 1,
+                StorageResult::NotFound => {},
+                other => return other.map(|_| 0),
+            }
+        }
+        StorageResult::Ok(removed)
+    }
+
+    /// Атомарно увеличивает счетчик (i64, little-endian) на delta в одной транзакции
+    /// и возвращает новое значение и признак достижения limit (value >= limit).
+    /// Отсутствующий ключ считается равным 0.
+    fn increment_checked(&mut self, storage: StorageId, _key: &str, _delta: i64, _limit: i64) -> StorageResult<(i64, bool)> {
+        StorageResult::Error(format!("increment_checked is not supported for {:?}", storage))
+    }
+
+    /// Атомарно увеличивает счетчик (i64, little-endian) на delta и возвращает новое значение.
+    /// Отсутствующий ключ считается равным 0, см. increment_checked.
+    fn increment(&mut self, storage: StorageId, key: &str, delta: i64) -> StorageResult<i64> {
+        self.increment_checked(storage, key, delta, i64::MAX).map(|(value, _)| value)
+    }
+
+    /// Атомарно записывает new, если текущее значение ключа равно expected
+    /// (expected = None - только если ключ отсутствует). Сравнение и запись
+    /// выполняются в одной транзакции записи (или под одной блокировкой в памяти).
+    /// Возвращает Ok(true) при записи и Ok(false), если значение не совпало.
+    fn compare_and_swap(&mut self, storage: StorageId, _key: &str, _expected: Option<&[u8]>, _new: &[u8]) -> StorageResult<bool> {
+        StorageResult::Error(format!("compare_and_swap is not supported for {:?}", storage))
+    }
+
+    /// Загружает individuals с указанными ids в переданный вектор, переиспользуя его память.
+    /// out очищается; отсутствующие и неразбираемые записи пропускаются.
+    /// Возвращает число загруженных individuals.
+    fn fill_individuals(&mut self, storage: StorageId, ids: &[&str], out: &mut Vec<(String, Individual)>) -> StorageResult<usize> {
+        out.clear();
+        out.reserve(ids.len());
+        for id in ids {
+            let mut individual = Individual::default();
+            match self.get_individual(storage.clone(), id, &mut individual) {
+                StorageResult::Ok(()) => out.push((id.to_string(), individual)),
+                StorageResult::NotFound | StorageResult::UnprocessableEntity => {},
+                StorageResult::NotReady => return StorageResult::NotReady,
+                StorageResult::Error(msg) => return StorageResult::Error(msg),
+            }
+        }
+        StorageResult::Ok(out.len())
+    }
+
+    /// Загружает и разбирает individuals с указанными ids. Результаты выровнены
+    /// по позициям с ids, для каждого id - свой результат (NotFound, UnprocessableEntity).
+    /// LMDB/MDBX читают все ids в одной транзакции чтения
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        ids.iter()
+            .map(|id| {
+                let mut individual = Individual::default();
+                let res = self.get_individual(storage.clone(), id, &mut individual).map(|_| individual);
+                (id.to_string(), res)
+            })
+            .collect()
+    }
+
+    /// Проверяет наличие ключа. LMDB/MDBX не копируют значение,
+    /// реализация по умолчанию читает его через get_raw_value
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        match self.get_raw_value(storage, key) {
+            StorageResult::Ok(_) => StorageResult::Ok(true),
+            StorageResult::NotFound => StorageResult::Ok(false),
+            other => other.map(|_| false),
+        }
+    }
+
+    /// Проверяет наличие ключей, результат выровнен по позициям с keys.
+    /// Значения не копируются там, где бэкенд это позволяет
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        let mut found = Vec::with_capacity(keys.len());
+        for key in keys {
+            match self.get_raw_value(storage.clone(), key) {
+                StorageResult::Ok(_) => found.push(true),
+                StorageResult::NotFound => found.push(false),
+                other => return other.map(|_| Vec::new()),
+            }
+        }
+        StorageResult::Ok(found)
+    }
+
+    /// Читает значения набора ключей, результаты выровнены по позициям с keys.
+    /// Бэкенды с транзакциями читают все ключи в одной транзакции
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        keys.iter().map(|key| self.get_value(storage.clone(), key)).collect()
+    }
+
+    /// Копирует значение ключа из одной секции в другую, оригинал сохраняется.
+    /// NotFound, если ключа нет в исходной секции; значение в целевой секции перезаписывается
+    fn copy_to_section(&mut self, from: StorageId, to: StorageId, key: &str) -> StorageResult<()> {
+        match self.get_raw_value(from, key) {
+            StorageResult::Ok(val) => self.put_raw_value(to, key, val),
+            other => other.map(|_| ()),
+        }
+    }
+
+    /// Обход всех записей всех секций за один проход
+    ///
+    /// Порядок - посекционный: сначала Individuals, затем Tickets, затем Az;
+    /// порядок ключей внутри секции определяется бэкендом.
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        StorageResult::Error("iter_all is not supported by this storage".to_string())
+    }
+
+    /// Пары (ключ, значение) одной секции без повторного чтения по каждому ключу.
+    /// LMDB/MDBX собирают пары за один проход курсора, память - копирует их
+    /// под блокировкой чтения. Реализация по умолчанию отбирает секцию из iter_all
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.iter_all().map(|entries| {
+            let pairs = entries.filter(move |(section, _, _)| *section == storage).map(|(_, key, val)| (key.into_bytes(), val));
+            Box::new(pairs) as Box<dyn Iterator<Item = ScanEntry>>
+        })
+    }
+
+    /// Группирует ключи секции по побайтово равным значениям (обратный индекс).
+    /// Держит в памяти все различные значения; для больших секций см. for_each_value_group
+    fn distinct_values(&mut self, storage: StorageId) -> StorageResult<HashMap<Vec<u8>, Vec<String>>> {
+        let mut groups = HashMap::new();
+        self.for_each_value_group(storage, &mut |val, keys| {
+            groups.insert(val.to_vec(), keys.to_vec());
+        })
+        .map(|_| groups)
+    }
+
+    /// Потоковый вариант distinct_values: f вызывается один раз для каждого
+    /// различного значения со списком ключей, которые его содержат.
+    ///
+    /// Первый проход запоминает только хеши значений и ключи, второй перечитывает
+    /// значения внутри каждой группы хешей, так что в памяти одновременно
+    /// находятся значения лишь одной группы.
+    fn for_each_value_group(&mut self, storage: StorageId, f: &mut dyn FnMut(&[u8], &[String])) -> StorageResult<()> {
+        let entries = match section_entries(self, storage.clone()) {
+            StorageResult::Ok(entries) => entries,
+            other => return other.map(|_| ()),
+        };
+
+        let mut by_hash: HashMap<u64, Vec<String>> = HashMap::new();
+        for (key, val) in entries {
+            let mut hasher = DefaultHasher::new();
+            val.hash(&mut hasher);
+            by_hash.entry(hasher.finish()).or_default().push(key);
+        }
+
+        for keys in by_hash.into_values() {
+            // Разделяем возможные коллизии хеша на группы равных значений
+            let mut groups: Vec<(Vec<u8>, Vec<String>)> = Vec::new();
+            for key in keys {
+                let val = match self.get_raw_value(storage.clone(), &key) {
+                    StorageResult::Ok(val) => val,
+                    // Удалено между проходами
+                    StorageResult::NotFound => continue,
+                    other => return other.map(|_| ()),
+                };
+                match groups.iter_mut().find(|(v, _)| *v == val) {
+                    Some((_, group)) => group.push(key),
+                    None => groups.push((val, vec![key])),
+                }
+            }
+            for (val, group) in groups {
+                f(&val, &group);
+            }
+        }
+        StorageResult::Ok(())
+    }
+
+    /// Число ключей секции по группам: подстрока ключа до первого depth_separator.
+    /// Ключи, не являющиеся UTF-8, не учитываются, как и в keys_with_prefix
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        section_entries(self, storage).map(|entries| {
+            let mut counts = HashMap::new();
+            for (key, _) in entries {
+                add_prefix_count(&mut counts, &key, depth_separator);
+            }
+            counts
+        })
+    }
+
+    /// Ключи секции, начинающиеся с prefix, в лексикографическом порядке.
+    /// LMDB/MDBX позиционируют курсор на prefix и не сканируют секцию целиком.
+    /// Ключи, не являющиеся UTF-8 (их может записать в LMDB/MDBX внешний инструмент),
+    /// пропускаются: MemoryStorage таких ключей хранить не может
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        section_entries(self, storage).map(|entries| {
+            let mut keys: Vec<String> = entries.map(|(key, _)| key).filter(|key| key.starts_with(prefix)).collect();
+            keys.sort();
+            keys
+        })
+    }
+
+    /// Записи секции с ключами из полуинтервала [start, end) в лексикографическом порядке,
+    /// например `log:2024-01-01`..`log:2024-02-01`. LMDB/MDBX позиционируют курсор
+    /// на start и останавливаются на первом ключе >= end
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        section_entries(self, storage).map(|entries| {
+            let mut entries: Vec<(String, Vec<u8>)> = entries.filter(|(key, _)| key.as_str() >= start && key.as_str() < end).collect();
+            entries.sort();
+            entries
+        })
+    }
+
+    /// Значение из именованной коллекции. Зарезервированные имена "individuals",
+    /// "tickets" и "az" (см. StorageId::as_str) соответствуют секциям; произвольные
+    /// имена поддерживают память и LMDB, остальные хранилища возвращают ошибку
+    fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        match namespace.parse::<StorageId>() {
+            Ok(storage) => self.get_value(storage, key),
+            Err(_) => namespaces_unsupported(),
+        }
+    }
+
+    /// Запись в именованную коллекцию, см. get_value_ns
+    fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        match namespace.parse::<StorageId>() {
+            Ok(storage) => self.put_value(storage, key, val),
+            Err(_) => namespaces_unsupported(),
+        }
+    }
+
+    /// Удаление из именованной коллекции, см. get_value_ns
+    fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        match namespace.parse::<StorageId>() {
+            Ok(storage) => self.remove_value(storage, key),
+            Err(_) => namespaces_unsupported(),
+        }
+    }
+
+    /// Число записей именованной коллекции; еще не созданная коллекция пуста
+    fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        match namespace.parse::<StorageId>() {
+            Ok(storage) => self.count(storage),
+            Err(_) => namespaces_unsupported(),
+        }
+    }
+
+    /// Список секций, в которых есть данные (count > 0)
+    fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        let mut sections = Vec::new();
+        for storage in StorageId::all() {
+            match self.count(storage.clone()) {
+                StorageResult::Ok(0) => {},
+                StorageResult::Ok(_) => sections.push(storage),
+                other => return other.map(|_| Vec::new()),
+            }
+        }
+        StorageResult::Ok(sections)
+    }
+
     // Deprecated methods for backward compatibility
     #[deprecated(since = "0.1.0", note = "Use get_individual instead")]
     fn get_individual_from_db(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
@@ -120,6 +954,177 @@ pub trait Storage {
     }
 }
 
+// Позволяет использовать Box<dyn Storage> там, где ожидается S: Storage (например, в обертках)
+impl<S: Storage + ?Sized> Storage for Box<S> {
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        (**self).get_individual(storage, id, iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        (**self).get_value(storage, key)
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        (**self).get_raw_value(storage, key)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        (**self).put_value(storage, key, val)
+    }
+
+    fn put_value_sized(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<usize> {
+        (**self).put_value_sized(storage, key, val)
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        (**self).put_raw_value(storage, key, val)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        (**self).remove_value(storage, key)
+    }
+
+    fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        (**self).count(storage)
+    }
+
+    fn stats(&mut self, storage: StorageId) -> StorageResult<StorageStats> {
+        (**self).stats(storage)
+    }
+
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        (**self).get_if(storage, key, pred)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        (**self).read_value(storage, key, f)
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        (**self).put_many(storage, entries)
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        (**self).put_values_many(storage, pairs)
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        (**self).replace_section(storage, entries)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        (**self).clear(storage)
+    }
+
+    fn sync(&mut self, force: bool) -> StorageResult<()> {
+        (**self).sync(force)
+    }
+
+    fn health_check(&mut self) -> StorageResult<()> {
+        (**self).health_check()
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        (**self).remove_matching(storage, pred)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        (**self).remove_prefix(storage, prefix)
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        (**self).increment_checked(storage, key, delta, limit)
+    }
+
+    fn increment(&mut self, storage: StorageId, key: &str, delta: i64) -> StorageResult<i64> {
+        (**self).increment(storage, key, delta)
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        (**self).compare_and_swap(storage, key, expected, new)
+    }
+
+    fn fill_individuals(&mut self, storage: StorageId, ids: &[&str], out: &mut Vec<(String, Individual)>) -> StorageResult<usize> {
+        (**self).fill_individuals(storage, ids, out)
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        (**self).get_individuals(storage, ids)
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        (**self).contains(storage, key)
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        (**self).exists_many(storage, keys)
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        (**self).get_values_many(storage, keys)
+    }
+
+    fn copy_to_section(&mut self, from: StorageId, to: StorageId, key: &str) -> StorageResult<()> {
+        (**self).copy_to_section(from, to, key)
+    }
+
+    fn is_persistent(&self) -> bool {
+        (**self).is_persistent()
+    }
+
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        (**self).snapshot()
+    }
+
+    fn distinct_values(&mut self, storage: StorageId) -> StorageResult<HashMap<Vec<u8>, Vec<String>>> {
+        (**self).distinct_values(storage)
+    }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        (**self).count_by_prefix(storage, depth_separator)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        (**self).keys_with_prefix(storage, prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        (**self).range(storage, start, end)
+    }
+
+    fn for_each_value_group(&mut self, storage: StorageId, f: &mut dyn FnMut(&[u8], &[String])) -> StorageResult<()> {
+        (**self).for_each_value_group(storage, f)
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        (**self).iter_all()
+    }
+
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        (**self).scan(storage)
+    }
+
+    fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        (**self).get_value_ns(namespace, key)
+    }
+
+    fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        (**self).put_value_ns(namespace, key, val)
+    }
+
+    fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        (**self).remove_value_ns(namespace, key)
+    }
+
+    fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        (**self).count_ns(namespace)
+    }
+
+    fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        (**self).non_empty_sections()
+    }
+}
+
 /// Макрос для устранения дублирования кода диспетчеризации
 #[macro_export]
 macro_rules! impl_storage_dispatcher {
@@ -155,6 +1160,141 @@ pub trait StorageDispatcher {
     }
 }
 
+// Trait for types that can be deserialized from MDB value
+// Similar to heed's BytesDecode but simpler for our use case
+pub trait FromMdbValue: Sized {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self>;
+}
+
+// Implement FromMdbValue for common types
+impl FromMdbValue for Vec<u8> {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl FromMdbValue for String {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl FromMdbValue for i64 {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 8] = bytes.try_into().ok()?;
+        Some(i64::from_le_bytes(*arr))
+    }
+}
+
+impl FromMdbValue for u64 {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 8] = bytes.try_into().ok()?;
+        Some(u64::from_le_bytes(*arr))
+    }
+}
+
+impl FromMdbValue for i32 {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 4] = bytes.try_into().ok()?;
+        Some(i32::from_le_bytes(*arr))
+    }
+}
+
+impl FromMdbValue for u32 {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 4] = bytes.try_into().ok()?;
+        Some(u32::from_le_bytes(*arr))
+    }
+}
+
+// A single byte, any nonzero value is true
+impl FromMdbValue for bool {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 1] = bytes.try_into().ok()?;
+        Some(arr[0] != 0)
+    }
+}
+
+impl FromMdbValue for f64 {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 8] = bytes.try_into().ok()?;
+        Some(f64::from_le_bytes(*arr))
+    }
+}
+
+impl FromMdbValue for f32 {
+    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
+        let arr: &[u8; 4] = bytes.try_into().ok()?;
+        Some(f32::from_le_bytes(*arr))
+    }
+}
+
+// Encoding counterpart of FromMdbValue: values written with to_mdb_value
+// are read back by from_mdb_value of the same type
+pub trait ToMdbValue {
+    fn to_mdb_value(&self) -> Vec<u8>;
+}
+
+impl ToMdbValue for Vec<u8> {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+impl ToMdbValue for String {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for str {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for i64 {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for u64 {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for i32 {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for u32 {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for bool {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        vec![u8::from(*self)]
+    }
+}
+
+impl ToMdbValue for f64 {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl ToMdbValue for f32 {
+    fn to_mdb_value(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
 /// Trait for zero-copy database operations
 /// Provides unified interface for LMDB and MDBX with minimal copying
 pub trait ZeroCopyStorage {
@@ -171,3 +1311,24 @@ pub trait ZeroCopyStorage {
     /// Put data into storage (mutable operation)
     fn put(&mut self, key: &str, val: &[u8]) -> bool;
 }
+
+/// Чтение из снимка хранилища, см. Storage::snapshot
+pub trait SnapshotReader {
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>>;
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, id), iraw)
+    }
+}
+
+/// Чтение в наборе транзакций, открытых вместе
+/// get из секции видит снимок на момент открытия ее транзакции; как и у snapshot,
+/// согласованность гарантируется только в пределах одной секции
+pub trait MultiReadOps {
+    /// Копия значения из секции сессии; Error для секции, не запрошенной при открытии
+    fn get(&mut self, section: StorageId, key: &str) -> StorageResult<Vec<u8>>;
+}
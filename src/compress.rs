@@ -0,0 +1,146 @@
+// compress.rs
+
+use v_individual_model::onto::individual::Individual;
+use crate::common::{decorator_inner, forward_to_inner, parse_individual_value, utf8_value, Storage, StorageId, StorageResult};
+
+/// Сигнатура сжатого значения, за ней следует кадр zstd
+const COMPRESSED_MAGIC: [u8; 4] = [0x00, b'V', b'Z', b'S'];
+
+/// Уровень сжатия zstd по умолчанию
+const DEFAULT_LEVEL: i32 = 3;
+
+/// Декоратор, прозрачно сжимающий значения с помощью zstd
+///
+/// Ключи сохраняются без изменений, значения при записи сжимаются и
+/// предваряются сигнатурой. При чтении значения с сигнатурой распаковываются,
+/// а значения без нее возвращаются как есть, поэтому хранилище может
+/// содержать вперемешку сжатые и несжатые данные.
+pub struct CompressedStorage<S: Storage> {
+    inner: S,
+    level: i32,
+}
+
+impl<S: Storage> CompressedStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self::with_level(inner, DEFAULT_LEVEL)
+    }
+
+    /// Создает декоратор с заданным уровнем сжатия zstd
+    pub fn with_level(inner: S, level: i32) -> Self {
+        Self {
+            inner,
+            level,
+        }
+    }
+
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    decorator_inner!(S);
+
+    fn compress(&self, key: &str, val: &[u8]) -> StorageResult<Vec<u8>> {
+        match zstd::stream::encode_all(val, self.level) {
+            Ok(frame) => {
+                let mut data = Vec::with_capacity(COMPRESSED_MAGIC.len() + frame.len());
+                data.extend_from_slice(&COMPRESSED_MAGIC);
+                data.extend_from_slice(&frame);
+                StorageResult::Ok(data)
+            },
+            Err(e) => {
+                error!("CompressedStorage: failed to compress value for key=[{}], err={:?}", key, e);
+                StorageResult::Error(format!("Failed to compress value: {:?}", e))
+            },
+        }
+    }
+
+    fn decompress(key: &str, data: Vec<u8>) -> StorageResult<Vec<u8>> {
+        if !data.starts_with(&COMPRESSED_MAGIC) {
+            return StorageResult::Ok(data);
+        }
+        match zstd::stream::decode_all(&data[COMPRESSED_MAGIC.len()..]) {
+            Ok(val) => StorageResult::Ok(val),
+            Err(e) => {
+                error!("CompressedStorage: failed to decompress value for key=[{}], err={:?}", key, e);
+                StorageResult::UnprocessableEntity
+            },
+        }
+    }
+}
+
+impl<S: Storage> Storage for CompressedStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, uri), iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.inner.get_raw_value(storage, key).and_then(|data| Self::decompress(key, data))
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        self.put_raw_value(storage, key, val.as_bytes().to_vec())
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        match self.compress(key, &val) {
+            StorageResult::Ok(data) => self.inner.put_raw_value(storage, key, data),
+            other => other.map(|_| ()),
+        }
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        self.inner.remove_value(storage, key)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.inner.clear(storage)
+    }
+
+    /// Наличие ключа не зависит от содержимого, значение не распаковывается
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.inner.contains(storage, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        let mut storage = CompressedStorage::new(MemoryStorage::new());
+
+        let big = vec![b'a'; 100 * 1024];
+        assert!(storage.put_raw_value(StorageId::Individuals, "big", big.clone()).is_ok());
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "big"), StorageResult::Ok(big.clone()));
+
+        // Stored bytes carry the header and are much smaller than the original
+        let stored = storage.inner_mut().get_raw_value(StorageId::Individuals, "big").unwrap_or_default();
+        assert!(stored.starts_with(&COMPRESSED_MAGIC));
+        assert!(stored.len() < big.len() / 10);
+
+        assert!(storage.put_value(StorageId::Individuals, "text", "hello").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "text"), StorageResult::Ok("hello".to_string()));
+    }
+
+    #[test]
+    fn test_uncompressed_values_pass_through() {
+        let mut storage = CompressedStorage::new(MemoryStorage::new());
+
+        assert!(storage.inner_mut().put_value(StorageId::Tickets, "plain", "as is").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "plain"), StorageResult::Ok("as is".to_string()));
+
+        // A header followed by garbage is reported as unprocessable, not returned as data
+        let mut broken = COMPRESSED_MAGIC.to_vec();
+        broken.extend_from_slice(b"garbage");
+        assert!(storage.inner_mut().put_raw_value(StorageId::Tickets, "broken", broken).is_ok());
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "broken"), StorageResult::UnprocessableEntity);
+    }
+}
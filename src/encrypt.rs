@@ -0,0 +1,144 @@
+// encrypt.rs
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use v_individual_model::onto::individual::Individual;
+use crate::common::{decorator_inner, forward_to_inner, parse_individual_value, utf8_value, Storage, StorageId, StorageResult};
+
+/// Длина nonce AES-GCM, которым предваряется каждое значение
+const NONCE_LEN: usize = 12;
+
+/// Декоратор, шифрующий значения AES-256-GCM
+///
+/// Каждое значение шифруется со случайным nonce, который сохраняется перед
+/// шифротекстом. Ключи хранятся в открытом виде, поэтому поиск по ключу
+/// работает без изменений. Значение, которое не удалось расшифровать
+/// (чужой ключ, поврежденные данные), возвращается как UnprocessableEntity.
+pub struct EncryptedStorage<S: Storage> {
+    inner: S,
+    cipher: Aes256Gcm,
+}
+
+impl<S: Storage> EncryptedStorage<S> {
+    pub fn new(inner: S, key: [u8; 32]) -> Self {
+        Self {
+            inner,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    decorator_inner!(S);
+
+    fn encrypt(&self, key: &str, val: &[u8]) -> StorageResult<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        match self.cipher.encrypt(&nonce, val) {
+            Ok(ciphertext) => {
+                let mut data = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+                data.extend_from_slice(&nonce);
+                data.extend_from_slice(&ciphertext);
+                StorageResult::Ok(data)
+            },
+            Err(e) => {
+                error!("EncryptedStorage: failed to encrypt value for key=[{}], err={:?}", key, e);
+                StorageResult::Error(format!("Failed to encrypt value: {:?}", e))
+            },
+        }
+    }
+
+    fn decrypt(&self, key: &str, data: &[u8]) -> StorageResult<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            error!("EncryptedStorage: value for key=[{}] is too short, len={}", key, data.len());
+            return StorageResult::UnprocessableEntity;
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        match self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext) {
+            Ok(val) => StorageResult::Ok(val),
+            Err(_) => {
+                error!("EncryptedStorage: failed to decrypt value for key=[{}]", key);
+                StorageResult::UnprocessableEntity
+            },
+        }
+    }
+}
+
+impl<S: Storage> Storage for EncryptedStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, uri), iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        match self.inner.get_raw_value(storage, key) {
+            StorageResult::Ok(data) => self.decrypt(key, &data),
+            other => other,
+        }
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        self.put_raw_value(storage, key, val.as_bytes().to_vec())
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        match self.encrypt(key, &val) {
+            StorageResult::Ok(data) => self.inner.put_raw_value(storage, key, data),
+            other => other.map(|_| ()),
+        }
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        self.inner.remove_value(storage, key)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.inner.clear(storage)
+    }
+
+    /// Ключи не шифруются, значение не расшифровывается
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.inner.contains(storage, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let mut storage = EncryptedStorage::new(MemoryStorage::new(), [7u8; 32]);
+
+        assert!(storage.put_value(StorageId::Individuals, "key", "secret value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("secret value".to_string()));
+
+        // Stored bytes are nonce + ciphertext, never the plaintext
+        let stored = storage.inner_mut().get_raw_value(StorageId::Individuals, "key").unwrap_or_default();
+        assert_ne!(stored, b"secret value".to_vec());
+        assert!(!stored.windows(6).any(|w| w == b"secret"));
+
+        // Same plaintext gets a fresh nonce on every write
+        assert!(storage.put_value(StorageId::Individuals, "key", "secret value").is_ok());
+        let restored = storage.inner_mut().get_raw_value(StorageId::Individuals, "key").unwrap_or_default();
+        assert_ne!(stored, restored);
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let mut storage = EncryptedStorage::new(MemoryStorage::new(), [1u8; 32]);
+        assert!(storage.put_value(StorageId::Tickets, "key", "value").is_ok());
+
+        let mut other = EncryptedStorage::new(storage.into_inner(), [2u8; 32]);
+        assert_eq!(other.get_value(StorageId::Tickets, "key"), StorageResult::UnprocessableEntity);
+        assert_eq!(other.get_individual(StorageId::Tickets, "key", &mut Individual::default()), StorageResult::UnprocessableEntity);
+        assert_eq!(other.get_value(StorageId::Tickets, "missing"), StorageResult::NotFound);
+
+        // Plaintext written around the decorator is rejected too
+        assert!(other.inner_mut().put_value(StorageId::Tickets, "plain", "value").is_ok());
+        assert_eq!(other.get_value(StorageId::Tickets, "plain"), StorageResult::UnprocessableEntity);
+    }
+}
@@ -0,0 +1,321 @@
+// expiring_storage.rs
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use v_individual_model::onto::individual::Individual;
+use crate::common::{decorator_inner, forward_to_inner, parse_individual_value, utf8_value, Storage, StorageId, StorageResult};
+
+/// Размер заголовка значения: момент истечения в миллисекундах Unix (u64, little-endian), 0 - без срока
+const EXPIRY_LEN: usize = 8;
+
+/// Источник текущего времени для ExpiringStorage
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+/// Системные часы (SystemTime::now)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Часы для тестов: время стоит на месте, пока его не сдвинут через advance.
+/// Клоны разделяют одно текущее время, поэтому клон можно передать в
+/// ExpiringStorage и управлять временем снаружи.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    now_ms: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            now_ms: Arc::new(AtomicU64::new(unix_ms(start))),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now_ms.fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.now_ms.load(Ordering::SeqCst))
+    }
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+/// Декоратор, хранящий вместе с каждым значением момент его истечения
+///
+/// Значение хранится во внутреннем хранилище в виде `[8 байт миллисекунд Unix][значение]`,
+/// поэтому срок жизни переживает перезапуск и работает с любым бэкендом. Истекшие
+/// значения не видны через get_*, contains и keys_with_prefix, но остаются во внутреннем
+/// хранилище (и учитываются в count), пока их не удалит purge_expired или новая запись.
+/// Значения, записанные через put_value/put_raw_value, срока не имеют.
+///
+/// Значения, записанные в обход декоратора, заголовка не имеют: их первые 8 байт
+/// будут приняты за момент истечения, а значение короче заголовка вернет ошибку.
+pub struct ExpiringStorage<S: Storage, C: Clock = SystemClock> {
+    inner: S,
+    clock: C,
+}
+
+impl<S: Storage> ExpiringStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self::with_clock(inner, SystemClock)
+    }
+}
+
+impl<S: Storage, C: Clock> ExpiringStorage<S, C> {
+    /// Декоратор с заданным источником времени, например MockClock в тестах
+    pub fn with_clock(inner: S, clock: C) -> Self {
+        Self {
+            inner,
+            clock,
+        }
+    }
+
+    decorator_inner!(S);
+
+    /// Записывает значение, которое перестает быть видимым через ttl
+    pub fn put_value_with_ttl(&mut self, storage: StorageId, key: &str, val: &str, ttl: Duration) -> StorageResult<()> {
+        self.put_raw_value_with_ttl(storage, key, val.as_bytes().to_vec(), ttl)
+    }
+
+    pub fn put_raw_value_with_ttl(&mut self, storage: StorageId, key: &str, val: Vec<u8>, ttl: Duration) -> StorageResult<()> {
+        let data = frame(self.deadline(ttl), &val);
+        self.inner.put_raw_value(storage, key, data)
+    }
+
+    /// Момент истечения ключа: Ok(None) для значений без срока, NotFound для отсутствующих и истекших
+    pub fn expires_at(&mut self, storage: StorageId, key: &str) -> StorageResult<Option<SystemTime>> {
+        self.read(storage, key).map(|(expires, _)| (expires != 0).then(|| UNIX_EPOCH + Duration::from_millis(expires)))
+    }
+
+    /// Продлевает срок жизни существующего ключа до now + ttl, не передавая значение заново.
+    /// Заголовок заменяется через compare_and_swap, поэтому запись, сделанная между чтением
+    /// и заменой, не теряется: в этом случае чтение повторяется. Возвращает Ok(false),
+    /// если ключа нет или он уже истек.
+    pub fn touch(&mut self, storage: StorageId, key: &str, ttl: Duration) -> StorageResult<bool> {
+        loop {
+            let current = match self.inner.get_raw_value(storage.clone(), key) {
+                StorageResult::Ok(data) => data,
+                StorageResult::NotFound => return StorageResult::Ok(false),
+                other => return other.map(|_| false),
+            };
+            let expires = match expiry_of(key, &current) {
+                StorageResult::Ok(expires) => expires,
+                other => return other.map(|_| false),
+            };
+            if self.is_expired(expires) {
+                return StorageResult::Ok(false);
+            }
+
+            let mut updated = current.clone();
+            updated[..EXPIRY_LEN].copy_from_slice(&self.deadline(ttl).to_le_bytes());
+            match self.inner.compare_and_swap(storage.clone(), key, Some(&current), &updated) {
+                StorageResult::Ok(false) => continue,
+                other => return other,
+            }
+        }
+    }
+
+    /// Удаляет из внутреннего хранилища истекшие значения секции, возвращает их число
+    pub fn purge_expired(&mut self, storage: StorageId) -> StorageResult<usize> {
+        let now = unix_ms(self.clock.now());
+        self.inner.remove_matching(storage, &mut |data| {
+            data.len() >= EXPIRY_LEN && {
+                let expires = header(data);
+                expires != 0 && expires <= now
+            }
+        })
+    }
+
+    /// Момент истечения для записи с ttl; 0 зарезервирован за значениями без срока.
+    /// Срок, не помещающийся в u64, означает запись без срока
+    fn deadline(&self, ttl: Duration) -> u64 {
+        u64::try_from(ttl.as_millis())
+            .ok()
+            .and_then(|ttl| unix_ms(self.clock.now()).checked_add(ttl))
+            .map_or(0, |at| at.max(1))
+    }
+
+    fn is_expired(&self, expires: u64) -> bool {
+        expires != 0 && expires <= unix_ms(self.clock.now())
+    }
+
+    /// Момент истечения и значение без заголовка; истекший ключ - NotFound
+    fn read(&mut self, storage: StorageId, key: &str) -> StorageResult<(u64, Vec<u8>)> {
+        let mut data = match self.inner.get_raw_value(storage, key) {
+            StorageResult::Ok(data) => data,
+            other => return other.map(|_| (0, Vec::new())),
+        };
+        let expires = match expiry_of(key, &data) {
+            StorageResult::Ok(expires) => expires,
+            other => return other.map(|_| (0, Vec::new())),
+        };
+        if self.is_expired(expires) {
+            return StorageResult::NotFound;
+        }
+        data.drain(..EXPIRY_LEN);
+        StorageResult::Ok((expires, data))
+    }
+}
+
+fn frame(expires: u64, val: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(EXPIRY_LEN + val.len());
+    data.extend_from_slice(&expires.to_le_bytes());
+    data.extend_from_slice(val);
+    data
+}
+
+fn header(data: &[u8]) -> u64 {
+    let mut expires = [0u8; EXPIRY_LEN];
+    expires.copy_from_slice(&data[..EXPIRY_LEN]);
+    u64::from_le_bytes(expires)
+}
+
+fn expiry_of(key: &str, data: &[u8]) -> StorageResult<u64> {
+    if data.len() < EXPIRY_LEN {
+        error!("ExpiringStorage: value for key=[{}] has no expiry header", key);
+        return StorageResult::Error("Value has no expiry header".to_string());
+    }
+    StorageResult::Ok(header(data))
+}
+
+impl<S: Storage, C: Clock> Storage for ExpiringStorage<S, C> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, uri), iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.read(storage, key).map(|(_, val)| val)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        self.put_raw_value(storage, key, val.as_bytes().to_vec())
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        self.inner.put_raw_value(storage, key, frame(0, &val))
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        self.inner.remove_value(storage, key)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.inner.clear(storage)
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        match self.read(storage, key) {
+            StorageResult::Ok(_) => StorageResult::Ok(true),
+            StorageResult::NotFound => StorageResult::Ok(false),
+            other => other.map(|_| false),
+        }
+    }
+
+    /// Ключи внутреннего хранилища без истекших; заголовок каждого ключа читается отдельно
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        let keys = match self.inner.keys_with_prefix(storage.clone(), prefix) {
+            StorageResult::Ok(keys) => keys,
+            other => return other,
+        };
+        let mut live = Vec::with_capacity(keys.len());
+        for key in keys {
+            match self.contains(storage.clone(), &key) {
+                StorageResult::Ok(true) => live.push(key),
+                StorageResult::Ok(false) => {},
+                other => return other.map(|_| Vec::new()),
+            }
+        }
+        StorageResult::Ok(live)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        self.inner.remove_prefix(storage, prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    fn storage_with_clock() -> (ExpiringStorage<MemoryStorage, MockClock>, MockClock) {
+        let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        (ExpiringStorage::with_clock(MemoryStorage::new(), clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_touch_extends_lifetime() {
+        let (mut storage, clock) = storage_with_clock();
+        let ttl = Duration::from_secs(10);
+
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "session", "payload", ttl).is_ok());
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(storage.touch(StorageId::Tickets, "session", ttl), StorageResult::Ok(true));
+
+        // Past the original deadline the key is still alive and unchanged
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(storage.get_value(StorageId::Tickets, "session"), StorageResult::Ok("payload".to_string()));
+        assert_eq!(storage.expires_at(StorageId::Tickets, "session"), StorageResult::Ok(Some(clock.now() + Duration::from_secs(2))));
+
+        // Past the extended deadline it is gone and can no longer be touched
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(storage.get_value(StorageId::Tickets, "session"), StorageResult::NotFound);
+        assert_eq!(storage.touch(StorageId::Tickets, "session", ttl), StorageResult::Ok(false));
+        assert_eq!(storage.touch(StorageId::Tickets, "missing", ttl), StorageResult::Ok(false));
+    }
+
+    #[test]
+    fn test_huge_ttl_never_expires() {
+        let (mut storage, clock) = storage_with_clock();
+
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "max", "payload", Duration::MAX).is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "u64", "payload", Duration::from_millis(u64::MAX)).is_ok());
+        clock.advance(Duration::from_secs(365 * 24 * 3600));
+
+        for key in ["max", "u64"] {
+            assert_eq!(storage.get_value(StorageId::Tickets, key), StorageResult::Ok("payload".to_string()));
+            assert_eq!(storage.expires_at(StorageId::Tickets, key), StorageResult::Ok(None));
+        }
+        assert_eq!(storage.touch(StorageId::Tickets, "max", Duration::MAX), StorageResult::Ok(true));
+        assert_eq!(storage.purge_expired(StorageId::Tickets), StorageResult::Ok(0));
+    }
+
+    #[test]
+    fn test_expired_keys_hidden_until_purged() {
+        let (mut storage, clock) = storage_with_clock();
+
+        assert!(storage.put_value(StorageId::Individuals, "k:permanent", "p").is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Individuals, "k:short", "s", Duration::from_secs(1)).is_ok());
+        assert_eq!(storage.expires_at(StorageId::Individuals, "k:permanent"), StorageResult::Ok(None));
+        assert_eq!(storage.inner_mut().get_raw_value(StorageId::Individuals, "k:short").unwrap().len(), EXPIRY_LEN + 1);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(storage.contains(StorageId::Individuals, "k:short"), StorageResult::Ok(false));
+        assert_eq!(storage.keys_with_prefix(StorageId::Individuals, "k:"), StorageResult::Ok(vec!["k:permanent".to_string()]));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(2));
+
+        assert_eq!(storage.purge_expired(StorageId::Individuals), StorageResult::Ok(1));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(1));
+        assert_eq!(storage.get_value(StorageId::Individuals, "k:permanent"), StorageResult::Ok("p".to_string()));
+    }
+}
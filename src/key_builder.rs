@@ -0,0 +1,106 @@
+// key_builder.rs
+
+/// Разделитель компонентов ключа по умолчанию
+pub const DEFAULT_KEY_SEPARATOR: &str = ":";
+
+/// Построитель ключей с единой схемой разделителей
+///
+/// Ключи собираются из компонентов через один и тот же разделитель, а префикс
+/// для сканирования всегда заканчивается разделителем, поэтому совпадает только
+/// по границам целых компонентов: префикс `user:1:` не захватывает `user:10:...`.
+/// Компоненты, содержащие разделитель, нарушили бы эту границу и отклоняются.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBuilder {
+    separator: String,
+}
+
+impl Default for KeyBuilder {
+    fn default() -> Self {
+        Self::new(DEFAULT_KEY_SEPARATOR)
+    }
+}
+
+impl KeyBuilder {
+    pub fn new(separator: &str) -> Self {
+        Self {
+            separator: separator.to_string(),
+        }
+    }
+
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    /// Собирает ключ из компонентов: ["user", "1"] -> "user:1";
+    /// None, если какой-либо компонент содержит разделитель
+    pub fn key(&self, components: &[&str]) -> Option<String> {
+        if components.iter().any(|c| c.contains(self.separator.as_str())) {
+            return None;
+        }
+        Some(components.join(&self.separator))
+    }
+
+    /// Префикс для сканирования всех ключей, начинающихся с данных компонентов:
+    /// ["user", "1"] -> "user:1:"; None, если компонент содержит разделитель
+    pub fn prefix_of(&self, components: &[&str]) -> Option<String> {
+        let mut prefix = self.key(components)?;
+        prefix.push_str(&self.separator);
+        Some(prefix)
+    }
+
+    /// Проверяет, что ключ лежит под префиксом из данных компонентов
+    pub fn is_under(&self, key: &str, components: &[&str]) -> bool {
+        self.prefix_of(components).is_some_and(|prefix| key.starts_with(&prefix))
+    }
+
+    /// Разбивает ключ обратно на компоненты
+    pub fn split<'a>(&self, key: &'a str) -> Vec<&'a str> {
+        key.split(self.separator.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_and_split() {
+        let kb = KeyBuilder::default();
+        assert_eq!(kb.key(&["user", "1", "profile"]).unwrap(), "user:1:profile");
+        assert_eq!(kb.split("user:1:profile"), vec!["user", "1", "profile"]);
+
+        let kb = KeyBuilder::new("/");
+        assert_eq!(kb.key(&["a", "b"]).unwrap(), "a/b");
+        assert_eq!(kb.prefix_of(&["a"]).unwrap(), "a/");
+    }
+
+    #[test]
+    fn test_prefix_matches_whole_components() {
+        let kb = KeyBuilder::default();
+        let user1 = kb.key(&["user", "1", "profile"]).unwrap();
+        let user10 = kb.key(&["user", "10", "profile"]).unwrap();
+
+        // Naive prefix without separator matches both
+        assert!(user10.starts_with("user:1"));
+
+        let prefix = kb.prefix_of(&["user", "1"]).unwrap();
+        assert_eq!(prefix, "user:1:");
+        assert!(user1.starts_with(&prefix));
+        assert!(!user10.starts_with(&prefix));
+
+        assert!(kb.is_under(&user1, &["user", "1"]));
+        assert!(!kb.is_under(&user10, &["user", "1"]));
+        assert!(kb.is_under(&user10, &["user"]));
+    }
+
+    #[test]
+    fn test_component_with_separator_is_rejected() {
+        let kb = KeyBuilder::new(":");
+
+        // "user:1:0" would otherwise be found under the prefix of ["user", "1"]
+        assert_eq!(kb.key(&["user", "1:0"]), None);
+        assert_eq!(kb.prefix_of(&["user:1"]), None);
+        assert!(!kb.is_under("user:1:0", &["user:1"]));
+        assert!(kb.is_under("user:1:0", &["user", "1"]));
+    }
+}
@@ -2,11 +2,31 @@
 extern crate log;
 
 pub mod common;
+pub mod async_storage;
 pub mod memory_storage;
 pub mod lmdb_storage;
 pub mod mdbx_storage;
 pub mod remote_storage_client;
 pub mod vstorage;
+pub mod cached_storage;
+pub mod chunked_storage;
+#[cfg(feature = "compression")]
+pub mod compress;
+#[cfg(feature = "encryption")]
+pub mod encrypt;
+pub mod change_log_storage;
+pub mod expiring_storage;
+pub mod key_builder;
+pub mod merge;
+pub mod meta_storage;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod migrate;
+pub mod noop_storage;
+pub mod normalizing_storage;
+pub mod observe;
+pub mod prefixed_storage;
+pub mod read_only_storage;
 #[cfg(any(feature = "tt_2", feature = "tt_3"))]
 pub mod tt_storage;
 #[cfg(any(feature = "tt_2", feature = "tt_3"))]
@@ -16,17 +36,38 @@ pub mod runtime_wrapper;
 pub mod storage_factory;
 
 // Re-export main types
-pub use common::{Storage, StorageId, StorageMode, StorageResult, StorageDispatcher};
-pub use memory_storage::MemoryStorage;
+pub use common::{FromMdbValue, ToMdbValue, Storage, StorageId, StorageMode, StorageResult, StorageDispatcher, MultiReadOps, ScanEntry, SectionEntry, SnapshotReader, StorageStats, TxnStats};
+pub use async_storage::AsyncStorage;
+pub use async_storage::AsyncStorageROClient;
+pub use memory_storage::{MemoryStorage, LoadReport};
 pub use lmdb_storage::LMDBStorage;
 pub use mdbx_storage::MDBXStorage;
 pub use remote_storage_client::StorageROClient;
-pub use vstorage::{VStorage, VStorageGeneric, VStorageEnum, VMemoryStorage, VLMDBStorage, VMDBXStorage, VRemoteStorage};
+pub use cached_storage::CachedStorage;
+pub use chunked_storage::ChunkedStorage;
+#[cfg(feature = "compression")]
+pub use compress::CompressedStorage;
+#[cfg(feature = "encryption")]
+pub use encrypt::EncryptedStorage;
+pub use change_log_storage::{ChangeKind, ChangeLogStorage, ChangeRecord};
+pub use expiring_storage::{Clock, ExpiringStorage, MockClock, SystemClock};
+pub use key_builder::KeyBuilder;
+pub use merge::merge_into;
+pub use meta_storage::MetaStorage;
+#[cfg(feature = "metrics")]
+pub use metrics::PrometheusObserver;
+pub use migrate::migrate;
+pub use noop_storage::NoopStorage;
+pub use normalizing_storage::NormalizingStorage;
+pub use observe::{CountingObserver, ObservedStorage, StorageObserver};
+pub use prefixed_storage::PrefixedStorage;
+pub use read_only_storage::ReadOnlyStorage;
+pub use vstorage::{VStorage, VStorageGeneric, MapView, VStorageEnum, VMemoryStorage, VLMDBStorage, VMDBXStorage, VRemoteStorage};
 #[cfg(any(feature = "tt_2", feature = "tt_3"))]
-pub use tt_storage::TTStorage;
+pub use tt_storage::{TTClient, TTSchemaCheck, TTStorage, TTStorageConfig};
 #[cfg(any(feature = "tt_2", feature = "tt_3"))]
 pub use vstorage::VTTStorage;
-pub use storage_factory::{StorageBuilder, StorageConfig, StorageError, StorageFactory, StorageProvider, DefaultStorageFactory};
+pub use storage_factory::{StorageBuilder, StorageConfig, StorageError, StorageFactory, StorageProvider, StorageRegistry, DefaultStorageFactory};
 #[cfg(feature = "tokio_0_2")]
 pub use runtime_wrapper::RuntimeWrapper;
 #[cfg(feature = "tokio_1")]
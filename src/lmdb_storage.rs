@@ -1,119 +1,448 @@
 use v_individual_model::onto::individual::Individual;
 use v_individual_model::onto::parser::parse_raw;
-use crate::common::{Storage, StorageId, StorageMode, StorageResult, ZeroCopyStorage};
-use heed::{Env, EnvOpenOptions};
+use crate::common::{add_prefix_count, empty_key_error, DEFAULT_OPEN_ATTEMPTS, OPEN_RETRY_DELAY, read_only_error, txn_done, txn_retry, txn_span, MultiReadOps, Storage, StorageId, StorageMode, StorageResult, StorageStats, ScanEntry, SectionChain, SectionEntry, SectionLoader, SnapshotReader, TxnCounters, TxnStats, WriteTxnRecord, ZeroCopyStorage};
+use heed::{EnvFlags, EnvOpenOptions, WithoutTls};
 use heed::types::*;
+use crate::storage_factory::StorageError;
 use std::borrow::Cow;
 use std::iter::Iterator;
+use std::ops::{Bound, Deref, DerefMut};
 use std::path::Path;
 use std::fs;
-use std::sync::{Arc, OnceLock};
+use std::io;
+use std::sync::{Arc, OnceLock, PoisonError};
 use std::collections::HashMap;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-// Trait for types that can be deserialized from MDB value
-// Similar to heed's BytesDecode but simpler for our use case
-pub trait FromMdbValue: Sized {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self>;
+pub use crate::common::{FromMdbValue, ToMdbValue};
+
+// Global registry of shared environments by path.
+// This is critical for LMDB: multiple instances in the same process must share
+// the same environment for a given database path to avoid conflicts.
+// Each LmdbInstance holds an Arc<LmdbEnv> clone, ensuring thread-safe shared access.
+// The map is only changed by single inserts and removals, so it stays consistent
+// after a panic while the lock was held and a poisoned lock is taken over.
+static GLOBAL_ENVS: OnceLock<Mutex<HashMap<String, Arc<LmdbEnv>>>> = OnceLock::new();
+
+// Environments are opened with MDB_NOTLS: a read transaction is not bound to
+// the reader slot of its thread, so a pinned snapshot transaction does not
+// block other reads on the same thread
+type Env = heed::Env<WithoutTls>;
+
+// Shared environment together with the number of transactions this process
+// has open in it. mdb_env_set_mapsize must not be called while any transaction
+// is open, so every transaction holds a TxnGuard and the map is only resized
+// while the count is zero, see grow_map.
+// The environment is None only after it was closed by reopen_env and could
+// not be opened again; transactions then fail until the next reopen.
+struct LmdbEnv {
+    env: Option<Env>,
+    read_only: bool,
+    open_txns: Mutex<usize>,
+}
+
+impl LmdbEnv {
+    fn new(env: Env, read_only: bool) -> Self {
+        LmdbEnv {
+            env: Some(env),
+            read_only,
+            open_txns: Mutex::new(0),
+        }
+    }
+
+    // A closed environment is reported with an error env_closed recognizes
+    fn env(&self) -> heed::Result<&Env> {
+        self.env.as_ref().ok_or_else(|| heed::Error::Io(io::Error::new(io::ErrorKind::NotConnected, "LMDB environment is closed")))
+    }
+
+    fn enter(self: &Arc<Self>) -> TxnGuard {
+        *self.open_txns.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        TxnGuard {
+            env: self.clone(),
+        }
+    }
+
+    // These shadow Env::read_txn/write_txn, so every transaction goes through the gate
+    fn read_txn(self: &Arc<Self>) -> heed::Result<LmdbReadTxn<'_>> {
+        let guard = self.enter();
+        let txn = self.env()?.read_txn()?;
+        Ok(LmdbReadTxn { txn, _guard: guard })
+    }
+
+    // Read transaction that owns a handle to the environment, see LMDBStorage::snapshot
+    fn static_read_txn(self: &Arc<Self>) -> heed::Result<LmdbReadTxn<'static>> {
+        let guard = self.enter();
+        let txn = self.env()?.clone().static_read_txn()?;
+        Ok(LmdbReadTxn { txn, _guard: guard })
+    }
+
+    // The transaction is counted in counters once it is open, see LmdbWriteTxn::commit
+    fn write_txn<'e>(self: &'e Arc<Self>, counters: &'e TxnCounters) -> heed::Result<LmdbWriteTxn<'e>> {
+        let guard = self.enter();
+        let txn = self.env()?.write_txn()?;
+        Ok(LmdbWriteTxn {
+            txn,
+            _guard: guard,
+            record: counters.begin_write(),
+        })
+    }
+
+    // Resize the map if no transaction is open; None when one is. The lock is
+    // held during the resize, so no transaction can begin meanwhile.
+    fn resize_if_idle(&self, new_size: usize) -> Option<heed::Result<()>> {
+        let open_txns = self.open_txns.lock().unwrap_or_else(PoisonError::into_inner);
+        if *open_txns > 0 {
+            return None;
+        }
+        // Safety: no transaction of this environment is open in this process
+        Some(self.env().and_then(|env| unsafe { env.resize(new_size) }))
+    }
+
+    fn force_sync(&self) -> heed::Result<()> {
+        self.env()?.force_sync()
+    }
+}
+
+// Error returned by LmdbEnv::env for a closed environment
+fn env_closed(e: &heed::Error) -> bool {
+    matches!(e, heed::Error::Io(e) if e.kind() == io::ErrorKind::NotConnected)
 }
 
-// Implement FromMdbValue for common types
-impl FromMdbValue for Vec<u8> {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        Some(bytes.to_vec())
+// Result of a failed operation: NotReady while the environment is closed, since
+// the next reopen may bring it back, Error otherwise
+fn failed<T>(e: &heed::Error, what: &str) -> StorageResult<T> {
+    if env_closed(e) {
+        StorageResult::NotReady
+    } else {
+        StorageResult::Error(format!("{}: {:?}", what, e))
     }
 }
 
-impl FromMdbValue for String {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        String::from_utf8(bytes.to_vec()).ok()
+// Marks a transaction of the environment as open until dropped
+struct TxnGuard {
+    env: Arc<LmdbEnv>,
+}
+
+impl Drop for TxnGuard {
+    fn drop(&mut self) {
+        *self.env.open_txns.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
     }
 }
 
-impl FromMdbValue for i64 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 8] = bytes.try_into().ok()?;
-        Some(i64::from_le_bytes(*arr))
+/// Read transaction of a shared LMDB environment.
+/// Dereferences to heed::RoTxn; the map can not be resized while it is open.
+pub struct LmdbReadTxn<'e> {
+    // Declared before the guard, so the transaction is closed first
+    txn: heed::RoTxn<'e, WithoutTls>,
+    _guard: TxnGuard,
+}
+
+impl<'e> Deref for LmdbReadTxn<'e> {
+    type Target = heed::RoTxn<'e, WithoutTls>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
     }
 }
 
-impl FromMdbValue for u64 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 8] = bytes.try_into().ok()?;
-        Some(u64::from_le_bytes(*arr))
+// Write transaction of a shared LMDB environment, see LmdbReadTxn.
+// Dropped without a successful commit it is counted as aborted.
+struct LmdbWriteTxn<'e> {
+    txn: heed::RwTxn<'e>,
+    _guard: TxnGuard,
+    record: WriteTxnRecord<'e>,
+}
+
+impl LmdbWriteTxn<'_> {
+    fn commit(self) -> heed::Result<()> {
+        let LmdbWriteTxn { txn, _guard, record } = self;
+        txn.commit()?;
+        record.commit();
+        Ok(())
     }
 }
 
-impl FromMdbValue for i32 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 4] = bytes.try_into().ok()?;
-        Some(i32::from_le_bytes(*arr))
+impl<'e> Deref for LmdbWriteTxn<'e> {
+    type Target = heed::RwTxn<'e>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
     }
 }
 
-impl FromMdbValue for u32 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 4] = bytes.try_into().ok()?;
-        Some(u32::from_le_bytes(*arr))
+impl DerefMut for LmdbWriteTxn<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.txn
     }
 }
 
-// Global registry of shared environments by path.
-// This is critical for LMDB: multiple instances in the same process must share
-// the same environment for a given database path to avoid conflicts.
-// Each LmdbInstance holds an Arc<Env> clone, ensuring thread-safe shared access.
-static GLOBAL_ENVS: OnceLock<Mutex<HashMap<String, Arc<Env>>>> = OnceLock::new();
+// Reads between read counter resets when the constructor gets no explicit value
+const DEFAULT_MAX_READ_COUNTER: u64 = 1000;
+
+// Map size of a new environment when the constructor gets no explicit value
+const DEFAULT_MAP_SIZE: usize = 10 * 1024 * 1024 * 1024;
 
+// Named sub-databases per environment, used by the namespaces environment
+const MAX_DBS: u32 = 64;
+
+// Upper bound for growing the map when a write hits MDB_MAP_FULL
+const DEFAULT_MAP_SIZE_CEILING: usize = 16 * DEFAULT_MAP_SIZE;
+
+// Magic number that starts every zstd frame (0xFD2FB528, little-endian)
+#[cfg(feature = "compression")]
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+#[derive(Debug)]
 pub struct LMDBStorage {
     individuals_db: LmdbInstance,
     tickets_db: LmdbInstance,
     az_db: LmdbInstance,
+    namespaces_path: String,
+    namespaces_db: Option<LmdbInstance>,
+    read_only: bool,
+    strict_utf8: bool,
+    sync_on_close: bool,
 }
 
 pub struct LmdbInstance {
     max_read_counter: u64,
     path: String,
-    env: Arc<Env>,
+    env: Arc<LmdbEnv>,
+    read_only: bool,
     read_counter: u64,
+    reopen_count: u64,
+    auto_decompress: bool,
+    slow_op_threshold: Option<Duration>,
+    txn_counters: Arc<TxnCounters>,
+    map_size_ceiling: usize,
+}
+
+// Only the location and counters; the environment and values are not printed
+impl std::fmt::Debug for LmdbInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LmdbInstance")
+            .field("path", &self.path)
+            .field("read_only", &self.read_only)
+            .field("read_counter", &self.read_counter)
+            .field("max_read_counter", &self.max_read_counter)
+            .field("reopen_count", &self.reopen_count)
+            .finish()
+    }
 }
 
 // Get or create a shared LMDB environment for the given path.
 // This function ensures that all LmdbInstance objects for the same path
 // share a single Environment, which is a requirement for correct LMDB operation
 // when multiple readers exist in the same process.
-fn get_or_create_env(path: &str) -> Arc<Env> {
+// The map size is only applied when the environment is created: the first
+// caller for a path wins, a different size requested later is ignored.
+// A read-write environment also serves read-only callers. A read-only one is
+// reopened read-write for a writer once no instance holds it any more; while
+// it is still in use the writer gets an error, since LMDB allows a single
+// environment per path in a process.
+// Opening is tried up to open_attempts times (at least once) with a pause
+// between attempts; after that the last error is returned.
+fn get_or_create_env(path: &str, map_size: Option<usize>, read_only: bool, open_attempts: u32) -> Result<Arc<LmdbEnv>, StorageError> {
     let envs = GLOBAL_ENVS.get_or_init(|| Mutex::new(HashMap::new()));
-    let mut envs_map = envs.lock().unwrap();
+    let mut envs_map = envs.lock().unwrap_or_else(PoisonError::into_inner);
     
     // Return existing environment if already created
-    if let Some(env) = envs_map.get(path) {
-        return env.clone();
+    match envs_map.get(path) {
+        Some(env) if read_only || !env.read_only => {
+            if let Some(requested) = map_size
+                && let Ok(current) = env.env().map(|env| env.info().map_size)
+                && requested != current
+            {
+                warn!("LMDB: environment path=[{}] is already open with map_size={}, requested map_size={} is ignored", path, current, requested);
+            }
+            return Ok(env.clone());
+        },
+        Some(env) if Arc::strong_count(env) > 1 => {
+            error!("LMDB: environment path=[{}] is open read-only and still in use, can not reopen it read-write", path);
+            return Err(StorageError::Backend(format!("LMDB: environment path=[{}] is open read-only and still in use", path)));
+        },
+        Some(_) => {
+            info!("LMDB: reopening read-only environment path=[{}] read-write", path);
+            // Closes the environment, nothing else holds it
+            envs_map.remove(path);
+        },
+        None => {},
     }
+
+    let env = Arc::new(LmdbEnv::new(open_env(path, map_size, read_only, open_attempts)?, read_only));
     
-    // Create directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(path) {
+    // Store environment in global registry
+    envs_map.insert(path.to_string(), env.clone());
+    Ok(env)
+}
+
+// Close the shared environment of path and open it again with the same flags and
+// map size, so nothing of the old one (reader slots, mapping) is kept. Only the
+// last holder of the environment reopens it: while other instances or open
+// transactions use it, env is left as is and false is returned.
+fn reopen_env(path: &str, env: &mut Arc<LmdbEnv>, open_attempts: u32) -> Result<bool, StorageError> {
+    let envs = GLOBAL_ENVS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut envs_map = envs.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let registered = envs_map.get(path).is_some_and(|registered| Arc::ptr_eq(registered, env));
+    if !registered && env.env.is_none() {
+        // A previous reopen failed; take the environment opened since then, if any
+        if let Some(current) = envs_map.get(path) {
+            *env = current.clone();
+            return Ok(true);
+        }
+    }
+    // Held by the registry, by this instance and by nobody else
+    let held = if registered { 2 } else { 1 };
+    if Arc::strong_count(env) > held || Arc::weak_count(env) > 0 {
+        return Ok(false);
+    }
+    if registered {
+        envs_map.remove(path);
+    }
+    let Some(inner) = Arc::get_mut(env) else {
+        return Ok(false);
+    };
+
+    let map_size = inner.env.as_ref().map(|env| env.info().map_size);
+    // Closes the environment, nothing else holds it
+    inner.env = None;
+    inner.env = Some(open_env(path, map_size, inner.read_only, open_attempts)?);
+    envs_map.insert(path.to_string(), env.clone());
+    Ok(true)
+}
+
+// Open the environment of path, see get_or_create_env
+fn open_env(path: &str, map_size: Option<usize>, read_only: bool, open_attempts: u32) -> Result<Env, StorageError> {
+    // Create directory if it doesn't exist; retrying can not help if that fails
+    if !read_only && let Err(e) = fs::create_dir_all(path) {
         error!("LMDB: failed to create directory path=[{}], err={:?}", path, e);
+        return Err(StorageError::IoError(format!("LMDB: failed to create directory path=[{}]: {}", path, e)));
+    }
+    // Read-only open does not create anything, retrying a missing path only delays the failure
+    if read_only && !Path::new(path).exists() {
+        error!("LMDB: can not open environment read-only, path=[{}] does not exist", path);
+        return Err(StorageError::IoError(format!("LMDB: path=[{}] does not exist, can not open it read-only", path)));
     }
     
     // Open new environment with retry logic
-    let env = loop {
-        match unsafe {
-            EnvOpenOptions::new()
-                .map_size(10 * 1024 * 1024 * 1024) // 10GB initial size
-                .max_dbs(1)
-                .open(Path::new(path))
-        } {
-            Ok(env) => break Arc::new(env),
+    let open_attempts = open_attempts.max(1);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut options = EnvOpenOptions::new().read_txn_without_tls();
+        options.map_size(map_size.unwrap_or(DEFAULT_MAP_SIZE)).max_dbs(MAX_DBS);
+        if read_only {
+            unsafe { options.flags(EnvFlags::READ_ONLY) };
+        }
+        match unsafe { options.open(Path::new(path)) } {
+            Ok(env) => return Ok(env),
             Err(e) => {
-                error!("LMDB: failed to open environment, path=[{}], err={:?}", path, e);
-                std::thread::sleep(std::time::Duration::from_secs(1));
+                error!("LMDB: failed to open environment, path=[{}], attempt {}/{}, err={:?}", path, attempt, open_attempts, e);
+                if attempt >= open_attempts {
+                    return Err(StorageError::IoError(format!("LMDB: failed to open environment path=[{}]: {}", path, e)));
+                }
+                std::thread::sleep(OPEN_RETRY_DELAY);
             }
         }
-    };
-    
-    // Store environment in global registry
-    envs_map.insert(path.to_string(), env.clone());
-    env
+    }
+}
+
+/// Write transaction handed to the LmdbInstance::write_batch closure.
+/// Operations are applied to a single RwTxn; after the first failure the
+/// remaining ones are skipped and the whole batch is aborted.
+pub struct LmdbWriteBatch<'a> {
+    txn: LmdbWriteTxn<'a>,
+    db: heed::Database<Bytes, Bytes>,
+    failed: Option<heed::Error>,
+}
+
+impl LmdbWriteBatch<'_> {
+    pub fn put(&mut self, key: &str, val: &[u8]) -> bool {
+        if self.failed.is_some() {
+            return false;
+        }
+        match self.db.put(&mut self.txn, key.as_bytes(), val) {
+            Ok(()) => true,
+            Err(e) => {
+                self.failed = Some(e);
+                false
+            }
+        }
+    }
+
+    /// Returns false if the key does not exist or the batch has already failed
+    pub fn delete(&mut self, key: &str) -> bool {
+        if self.failed.is_some() {
+            return false;
+        }
+        match self.db.delete(&mut self.txn, key.as_bytes()) {
+            Ok(found) => found,
+            Err(e) => {
+                self.failed = Some(e);
+                false
+            }
+        }
+    }
+}
+
+// Value as returned to the caller: with auto_decompress a zstd frame written by an
+// external tool is decoded, anything else is passed through untouched. A frame that
+// can not be decoded is UnprocessableEntity, compressed bytes are never handed out
+#[cfg(feature = "compression")]
+fn decode_value<'a>(auto_decompress: bool, val: Cow<'a, [u8]>, key: &str, path: &str) -> StorageResult<Cow<'a, [u8]>> {
+    if !auto_decompress || !val.starts_with(&ZSTD_MAGIC) {
+        return StorageResult::Ok(val);
+    }
+    match zstd::stream::decode_all(val.as_ref()) {
+        Ok(decoded) => StorageResult::Ok(Cow::Owned(decoded)),
+        Err(e) => {
+            error!("LMDB: failed to decompress zstd value for key=[{}], path=[{}], err={:?}", key, path, e);
+            StorageResult::UnprocessableEntity
+        },
+    }
+}
+
+// Without the compression feature auto_decompress can not be enabled
+#[cfg(not(feature = "compression"))]
+fn decode_value<'a>(_auto_decompress: bool, val: Cow<'a, [u8]>, _key: &str, _path: &str) -> StorageResult<Cow<'a, [u8]>> {
+    StorageResult::Ok(val)
+}
+
+/// Read transaction of one database pinned by LMDBStorage::snapshot.
+/// It holds the transaction guard, so the map is not resized while the snapshot lives.
+struct LmdbSnapshotSection {
+    storage: StorageId,
+    txn: LmdbReadTxn<'static>,
+    db: Option<heed::Database<Bytes, Bytes>>,
+    path: String,
+    auto_decompress: bool,
+}
+
+/// Snapshot of LMDBStorage: one read transaction per database
+struct LmdbSnapshot {
+    sections: Vec<LmdbSnapshotSection>,
+}
+
+impl SnapshotReader for LmdbSnapshot {
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let Some(section) = self.sections.iter().find(|section| section.storage == storage) else {
+            return StorageResult::Error(format!("{:?} is not part of this snapshot", storage));
+        };
+        let Some(db) = section.db else {
+            return StorageResult::NotFound;
+        };
+
+        match db.get(&section.txn, key.as_bytes()) {
+            Ok(Some(val)) => decode_value(section.auto_decompress, Cow::Borrowed(val), key, &section.path).map(Cow::into_owned),
+            Ok(None) => StorageResult::NotFound,
+            Err(e) => {
+                error!("LMDB: snapshot get failed for key=[{}], path=[{}], err={:?}", key, section.path, e);
+                failed(&e, "Failed to read key")
+            },
+        }
+    }
 }
 
 struct LmdbIterator {
@@ -140,35 +469,132 @@ impl LmdbInstance {
     /// The environment is shared globally - multiple instances for the same path
     /// will use the same underlying LMDB environment.
     /// Database handle is NOT stored - it's opened per-transaction for thread safety.
-    pub fn new(path: &str, _mode: StorageMode) -> Self {
-        let env = get_or_create_env(path);
+    /// map_size_bytes only applies if this call creates the environment (10GB when None).
+    /// In ReadOnly mode the environment is opened with MDB_RDONLY and no database is created;
+    /// a path that does not exist fails at once, without retries.
+    /// Panics if the environment can not be opened in DEFAULT_OPEN_ATTEMPTS attempts,
+    /// use try_new to handle a bad path.
+    pub fn new(path: &str, mode: StorageMode, map_size_bytes: Option<usize>) -> Self {
+        LmdbInstance::try_new(path, mode, map_size_bytes, DEFAULT_OPEN_ATTEMPTS).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as new, but gives up after open_attempts failed attempts to open
+    /// the environment (one second apart) and returns the error.
+    pub fn try_new(path: &str, mode: StorageMode, map_size_bytes: Option<usize>, open_attempts: u32) -> Result<Self, StorageError> {
+        let read_only = mode == StorageMode::ReadOnly;
+        let env = get_or_create_env(path, map_size_bytes, read_only, open_attempts)?;
+        let txn_counters = Arc::new(TxnCounters::default());
         
         // Try to initialize database (create_database is idempotent - succeeds if already exists)
-        if let Ok(mut wtxn) = env.write_txn() {
-            if let Ok(_db) = env.create_database::<Bytes, Bytes>(&mut wtxn, None) {
-                let _ = wtxn.commit();
-            }
+        if !read_only
+            && let Ok(mut wtxn) = env.write_txn(&txn_counters)
+            && let Ok(_db) = env.env().and_then(|env| env.create_database::<Bytes, Bytes>(&mut wtxn, None))
+        {
+            let _ = wtxn.commit();
         }
         
-        LmdbInstance {
-            max_read_counter: 1000,
+        Ok(LmdbInstance {
+            max_read_counter: DEFAULT_MAX_READ_COUNTER,
             path: path.to_string(),
             env,
+            read_only,
             read_counter: 0,
+            reopen_count: 0,
+            auto_decompress: false,
+            slow_op_threshold: None,
+            txn_counters,
+            map_size_ceiling: DEFAULT_MAP_SIZE_CEILING,
+        })
+    }
+
+    /// Enable transparent decompression of zstd frames on every value read: get,
+    /// get_individual, get_if, read_value, the batch reads, range, named sub-databases,
+    /// snapshots and read sessions. Intended for databases filled by external tools
+    /// that write zstd-compressed values without any crate-specific header. Values
+    /// without the zstd magic are returned untouched; a frame that fails to decode
+    /// is reported as UnprocessableEntity. get_with_txn, iter and scan still see the
+    /// stored bytes, so copies made with them stay compressed.
+    /// Requires the compression feature.
+    #[cfg(feature = "compression")]
+    pub fn set_auto_decompress(&mut self, enabled: bool) {
+        self.auto_decompress = enabled;
+    }
+
+    /// Log a warning for every transaction (begin -> commit) that takes longer than threshold
+    /// and count it in TxnStats::slow. None disables timing entirely, so the hot path
+    /// does not even read the clock.
+    pub fn set_slow_op_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_op_threshold = threshold;
+    }
+
+    /// Number of operations that exceeded the slow operation threshold
+    pub fn slow_op_count(&self) -> u64 {
+        self.txn_counters.snapshot().slow
+    }
+
+    /// When a write fails because the map is full, the map size is doubled
+    /// and the write retried once, as long as the new size stays within ceiling.
+    /// The map can only grow while no other transaction of the environment is
+    /// open in this process (snapshots, read sessions, concurrent reads);
+    /// otherwise the write fails with MDB_MAP_FULL.
+    pub fn set_map_size_ceiling(&mut self, ceiling: usize) {
+        self.map_size_ceiling = ceiling;
+    }
+
+    /// Number of reads after which the read counter is reset
+    pub fn set_max_read_counter(&mut self, max_read_counter: u64) {
+        self.max_read_counter = max_read_counter;
+    }
+
+    pub fn max_read_counter(&self) -> u64 {
+        self.max_read_counter
+    }
+
+    /// Reads since the last read counter reset
+    pub fn read_counter(&self) -> u64 {
+        self.read_counter
+    }
+
+    /// Number of times the environment was reopened, see open
+    pub fn reopen_count(&self) -> u64 {
+        self.reopen_count
+    }
+
+    /// Counters of transactions begun by this instance
+    pub fn txn_stats(&self) -> TxnStats {
+        self.txn_counters.snapshot()
+    }
+
+    fn start_op(&self) -> Option<Instant> {
+        self.slow_op_threshold.map(|_| Instant::now())
+    }
+
+    fn finish_op(&self, op: &str, key: &str, started: Option<Instant>) {
+        if let Some(started) = started {
+            self.record_op(op, key, started.elapsed());
+        }
+    }
+
+    fn record_op(&self, op: &str, key: &str, elapsed: Duration) {
+        if let Some(threshold) = self.slow_op_threshold
+            && elapsed > threshold
+        {
+            self.txn_counters.record_slow();
+            warn!("LMDB: slow {} for key=[{}], path=[{}], duration={:?}, threshold={:?}", op, key, self.path, elapsed, threshold);
         }
     }
 
     pub fn iter(&mut self) -> Box<dyn Iterator<Item = Vec<u8>>> {
-        match self.env.read_txn() {
+        let started = self.start_op();
+        let res: Box<dyn Iterator<Item = Vec<u8>>> = match self.env.read_txn() {
             Ok(txn) => {
-                match self.env.open_database::<Bytes, Bytes>(&txn, None) {
+                self.txn_counters.record_read();
+                match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(&txn, None)) {
                     Ok(Some(db)) => {
                         let mut keys = Vec::new();
                         if let Ok(iter) = db.iter(&txn) {
-                            for item in iter {
-                                if let Ok((key, _)) = item {
-                                    keys.push(key.to_vec());
-                                }
+                            for (key, _) in iter.flatten() {
+                                keys.push(key.to_vec());
                             }
                         }
                         Box::new(LmdbIterator {
@@ -190,25 +616,91 @@ impl LmdbInstance {
                 error!("LMDB: failed to create read transaction for iterator, path=[{}], err={:?}", self.path, e);
                 Box::new(std::iter::empty())
             },
-        }
+        };
+        self.finish_op("iter", "*", started);
+        res
+    }
+
+    /// Key/value pairs of the database, collected in a single cursor walk
+    pub fn scan(&mut self) -> Box<dyn Iterator<Item = ScanEntry>> {
+        let started = self.start_op();
+        let res: Box<dyn Iterator<Item = ScanEntry>> = match self.env.read_txn() {
+            Ok(txn) => {
+                self.txn_counters.record_read();
+                match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(&txn, None)) {
+                    Ok(Some(db)) => {
+                        let mut pairs = Vec::new();
+                        if let Ok(iter) = db.iter(&txn) {
+                            for (key, val) in iter.flatten() {
+                                pairs.push((key.to_vec(), val.to_vec()));
+                            }
+                        }
+                        Box::new(pairs.into_iter())
+                    },
+                    Ok(None) => {
+                        error!("LMDB: database not found, path=[{}]", self.path);
+                        Box::new(std::iter::empty())
+                    },
+                    Err(e) => {
+                        error!("LMDB: failed to open database for scan, path=[{}], err={:?}", self.path, e);
+                        Box::new(std::iter::empty())
+                    }
+                }
+            },
+            Err(e) => {
+                error!("LMDB: failed to create read transaction for scan, path=[{}], err={:?}", self.path, e);
+                Box::new(std::iter::empty())
+            },
+        };
+        self.finish_op("scan", "*", started);
+        res
     }
 
+    /// Loader that reads all entries of this database in its own read transaction
+    /// when called, so a chained iterator never holds more than one transaction
+    fn section_loader(&self) -> SectionLoader {
+        let env = self.env.clone();
+        let counters = self.txn_counters.clone();
+        let path = self.path.clone();
+        Box::new(move || entries_lmdb(&env, &counters, &path))
+    }
+
+    /// Reopen the environment: release reader slots left by dead threads and
+    /// processes, then close and open the environment again if this instance is
+    /// its last holder, and reset the read counter. A shared environment is kept
+    /// open for the other instances. Called automatically once max_read_counter
+    /// reads have been made.
     pub fn open(&mut self) {
-        // Reset read counter - environment is already open and shared
+        if let Some(env) = &self.env.env {
+            match env.clear_stale_readers() {
+                Ok(0) => {},
+                Ok(cleared) => info!("LMDB: cleared {} stale readers, path=[{}]", cleared, self.path),
+                Err(e) => warn!("LMDB: failed to clear stale readers, path=[{}], err={:?}", self.path, e),
+            }
+        }
         self.read_counter = 0;
-        info!("LMDBStorage: reset read counter for path=[{}]", self.path);
+        match reopen_env(&self.path, &mut self.env, DEFAULT_OPEN_ATTEMPTS) {
+            Ok(true) => {
+                self.reopen_count += 1;
+                info!("LMDBStorage: reopened environment for path=[{}]", self.path);
+            },
+            Ok(false) => info!("LMDBStorage: environment path=[{}] is in use by other instances, not reopened", self.path),
+            Err(e) => error!("LMDB: failed to reopen environment, path=[{}], err={:?}", self.path, e),
+        }
     }
 
     /// Create a read-only transaction for zero-copy operations
     /// Use this with get_with_txn to avoid data copying
-    pub fn begin_ro_txn(&self) -> heed::Result<heed::RoTxn<'_, heed::WithTls>> {
-        self.env.read_txn()
+    pub fn begin_ro_txn(&self) -> heed::Result<LmdbReadTxn<'_>> {
+        let txn = self.env.read_txn()?;
+        self.txn_counters.record_read();
+        Ok(txn)
     }
 
     /// Get data with zero-copy using existing transaction
     /// Returns Cow::Borrowed (reference without copying, valid while transaction lives)
-    pub fn get_with_txn<'tx>(&self, txn: &'tx heed::RoTxn<heed::WithTls>, key: &str) -> Option<Cow<'tx, [u8]>> {
-        match self.env.open_database::<Bytes, Bytes>(txn, None) {
+    pub fn get_with_txn<'tx>(&self, txn: &'tx heed::RoTxn<heed::WithoutTls>, key: &str) -> Option<Cow<'tx, [u8]>> {
+        match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(txn, None)) {
             Ok(Some(db)) => {
                 match db.get(txn, key.as_bytes()) {
                     Ok(Some(val)) => Some(Cow::Borrowed(val)),  // Zero-copy! Returns Cow::Borrowed
@@ -231,79 +723,453 @@ impl LmdbInstance {
     }
 
     pub fn get_individual(&mut self, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
-        if let Some(val) = self.get_raw(uri) {
+        self.try_get::<Vec<u8>>(uri).and_then(|val| {
             iraw.set_raw(&val);
 
-            return if parse_raw(iraw).is_ok() {
+            if parse_raw(iraw).is_ok() {
                 StorageResult::Ok(())
             } else {
                 error!("LMDB: fail parse binobj, path=[{}], len={}, uri=[{}]", self.path, iraw.get_raw_len(), uri);
                 StorageResult::UnprocessableEntity
+            }
+        })
+    }
+
+    /// Return the value only if pred accepts it. The predicate runs on the bytes
+    /// borrowed from the read transaction, so rejected values are never copied.
+    pub fn get_if(&mut self, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        if self.auto_decompress {
+            // Compressed values have to be decoded before the predicate can see them
+            return self.try_get::<Vec<u8>>(key).map(|val| if pred(&val) { Some(val) } else { None });
+        }
+
+        let started = self.start_op();
+        let res = match self.env.read_txn() {
+            Ok(txn) => {
+                self.txn_counters.record_read();
+                match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(&txn, None)) {
+                    Ok(Some(db)) => {
+                        match db.get(&txn, key.as_bytes()) {
+                            Ok(Some(val)) => StorageResult::Ok(if pred(val) { Some(val.to_vec()) } else { None }),
+                            Ok(None) => StorageResult::NotFound,
+                            Err(e) => {
+                                error!("LMDB: get_if failed for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                                failed(&e, "Failed to get value")
+                            },
+                        }
+                    },
+                    Ok(None) => StorageResult::NotFound,
+                    Err(e) => {
+                        error!("LMDB: failed to open database in get_if for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                        failed(&e, "Failed to open database")
+                    }
+                }
+            },
+            Err(e) => {
+                error!("LMDB: failed to create read transaction in get_if for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                failed(&e, "Failed to create read transaction")
+            },
+        };
+        self.finish_op("get_if", key, started);
+        res
+    }
+
+    /// Call f on the value bytes borrowed from a read transaction; the
+    /// transaction is dropped after f returns.
+    pub fn read_value(&mut self, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        if self.auto_decompress {
+            // Compressed values have to be decoded before f can see them
+            return self.try_get::<Vec<u8>>(key).map(|val| f(&val));
+        }
+
+        let started = self.start_op();
+        let res = (|| -> heed::Result<bool> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let Some(db) = self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? else {
+                return Ok(false);
             };
+            match db.get(&txn, key.as_bytes())? {
+                Some(val) => {
+                    f(val);
+                    Ok(true)
+                },
+                None => Ok(false),
+            }
+        })();
+        self.finish_op("read_value", key, started);
+
+        match res {
+            Ok(true) => StorageResult::Ok(()),
+            Ok(false) => StorageResult::NotFound,
+            Err(e) => {
+                error!("LMDB: failed to read value for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                failed(&e, "Failed to read value")
+            },
         }
+    }
 
-        StorageResult::NotFound
+    /// Check presence of a key without copying its value out of the map.
+    pub fn contains(&mut self, key: &str) -> StorageResult<bool> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<bool> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            match self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+                Some(db) => Ok(db.get(&txn, key.as_bytes())?.is_some()),
+                None => Ok(false),
+            }
+        })();
+
+        self.finish_op("contains", key, started);
+        match res {
+            Ok(found) => StorageResult::Ok(found),
+            Err(e) => {
+                error!("LMDB: contains failed for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                failed(&e, "Failed to check key")
+            }
+        }
     }
 
-    pub fn get_v(&mut self, key: &str) -> Option<String> {
-        self.get::<String>(key)
+    /// Check presence of every key in a single read transaction.
+    /// Values are only borrowed from the map, never copied. An empty key
+    /// can not be stored and is reported as absent.
+    pub fn exists_many(&mut self, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Vec<bool>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let mut found = Vec::with_capacity(keys.len());
+            if let Some(db) = self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+                for key in keys {
+                    // LMDB rejects a zero-length key instead of reporting it missing
+                    found.push(!key.is_empty() && db.get(&txn, key.as_bytes())?.is_some());
+                }
+            } else {
+                found.resize(keys.len(), false);
+            }
+            Ok(found)
+        })();
+
+        self.finish_op("exists_many", "*", started);
+        match res {
+            Ok(found) => StorageResult::Ok(found),
+            Err(e) => {
+                error!("LMDB: exists_many failed, path=[{}], keys={}, err={:?}", self.path, keys.len(), e);
+                failed(&e, "Failed to check keys")
+            }
+        }
     }
 
-    pub fn get_raw(&mut self, key: &str) -> Option<Vec<u8>> {
-        self.get::<Vec<u8>>(key)
+    /// Read every key in a single read transaction; results follow the order of keys.
+    /// Values that are not valid UTF-8 are reported as NotFound, like get_v does.
+    pub fn get_values_many(&mut self, keys: &[&str]) -> Vec<StorageResult<String>> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Vec<StorageResult<String>>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let Some(db) = self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? else {
+                return Ok(keys.iter().map(|_| StorageResult::NotFound).collect());
+            };
+            let mut values = Vec::with_capacity(keys.len());
+            for key in keys {
+                if key.is_empty() {
+                    values.push(empty_key_error());
+                    continue;
+                }
+                values.push(match db.get(&txn, key.as_bytes())? {
+                    Some(val) => decode_value(self.auto_decompress, Cow::Borrowed(val), key, &self.path)
+                        .and_then(|val| String::from_mdb_value(&val).into()),
+                    None => StorageResult::NotFound,
+                });
+            }
+            Ok(values)
+        })();
+
+        self.finish_op("get_values_many", "*", started);
+        match res {
+            Ok(values) => values,
+            Err(e) => {
+                error!("LMDB: get_values_many failed, path=[{}], keys={}, err={:?}", self.path, keys.len(), e);
+                keys.iter().map(|_| failed(&e, "Failed to read keys")).collect()
+            }
+        }
     }
 
-    pub fn get<T: FromMdbValue>(&mut self, key: &str) -> Option<T> {
-        for _it in 0..2 {
-            self.read_counter += 1;
-            if self.read_counter > self.max_read_counter {
-                warn!("db {} reset counter for key=[{}] (max counter reached)", self.path, key);
-                self.read_counter = 0;
+    /// Load and parse individuals in a single read transaction; results follow the order of ids.
+    pub fn get_individuals(&mut self, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Vec<StorageResult<Individual>>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let Some(db) = self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? else {
+                return Ok(ids.iter().map(|_| StorageResult::NotFound).collect());
+            };
+            let mut individuals = Vec::with_capacity(ids.len());
+            for id in ids {
+                if id.is_empty() {
+                    individuals.push(empty_key_error());
+                    continue;
+                }
+                individuals.push(match db.get(&txn, id.as_bytes())? {
+                    Some(val) => self.parse_individual(id, val),
+                    None => StorageResult::NotFound,
+                });
             }
+            Ok(individuals)
+        })();
 
-            match self.env.read_txn() {
-                Ok(txn) => {
-                    match self.env.open_database::<Bytes, Bytes>(&txn, None) {
-                        Ok(Some(db)) => {
-                            match db.get(&txn, key.as_bytes()) {
-                                Ok(Some(val)) => {
-                                    return T::from_mdb_value(val);
-                                },
-                                Ok(None) => {
-                                    return None;
-                                },
-                                Err(e) => {
-                                    error!("LMDB: db.get failed for key=[{}], path=[{}], err={:?}", key, self.path, e);
-                                    return None;
-                                },
-                            }
-                        },
-                        Ok(None) => {
-                            error!("LMDB: database not found for key=[{}], path=[{}]", key, self.path);
-                            return None;
-                        },
-                        Err(e) => {
-                            error!("LMDB: failed to open database for key=[{}], path=[{}], err={:?}", key, self.path, e);
-                            std::thread::sleep(std::time::Duration::from_millis(100));
-                        }
-                    }
-                },
-                Err(e) => {
-                    error!("LMDB: failed to create read transaction for key=[{}], path=[{}], err={:?}", key, self.path, e);
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                },
+        self.finish_op("get_individuals", "*", started);
+        match res {
+            Ok(individuals) => ids.iter().map(|id| id.to_string()).zip(individuals).collect(),
+            Err(e) => {
+                error!("LMDB: get_individuals failed, path=[{}], ids={}, err={:?}", self.path, ids.len(), e);
+                ids.iter().map(|id| (id.to_string(), failed(&e, "Failed to read individuals"))).collect()
             }
         }
+    }
 
-        None
+    fn parse_individual(&self, uri: &str, val: &[u8]) -> StorageResult<Individual> {
+        decode_value(self.auto_decompress, Cow::Borrowed(val), uri, &self.path).and_then(|val| {
+            let mut individual = Individual::default();
+            individual.set_raw(&val);
+
+            if parse_raw(&mut individual).is_ok() {
+                StorageResult::Ok(individual)
+            } else {
+                error!("LMDB: fail parse binobj, path=[{}], len={}, uri=[{}]", self.path, individual.get_raw_len(), uri);
+                StorageResult::UnprocessableEntity
+            }
+        })
     }
 
-    pub fn count(&mut self) -> usize {
-        for _it in 0..2 {
-            match self.env.read_txn() {
-                Ok(txn) => {
-                    match self.env.open_database::<Bytes, Bytes>(&txn, None) {
-                        Ok(Some(db)) => {
+    /// Keys starting with prefix, in key order. The cursor is positioned at the
+    /// prefix (MDB_SET_RANGE) and stops at the first key outside of it.
+    /// An empty prefix lists every key.
+    pub fn keys_with_prefix(&mut self, prefix: &str) -> StorageResult<Vec<String>> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Vec<String>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let mut keys = Vec::new();
+            if let Some(db) = self.env.env()?.open_database::<Bytes, DecodeIgnore>(&txn, None)? {
+                // LMDB rejects a zero-length key, an empty prefix means a full walk
+                let entries: Box<dyn Iterator<Item = heed::Result<(&[u8], ())>>> = if prefix.is_empty() {
+                    Box::new(db.iter(&txn)?)
+                } else {
+                    Box::new(db.prefix_iter(&txn, prefix.as_bytes())?)
+                };
+                for entry in entries {
+                    let (key, _) = entry?;
+                    match std::str::from_utf8(key) {
+                        Ok(key) => keys.push(key.to_string()),
+                        Err(_) => warn!("LMDB: keys_with_prefix skips a key that is not valid UTF-8, prefix=[{}], path=[{}]", prefix, self.path),
+                    }
+                }
+            }
+            Ok(keys)
+        })();
+
+        self.finish_op("keys_with_prefix", prefix, started);
+        match res {
+            Ok(keys) => StorageResult::Ok(keys),
+            Err(e) => {
+                error!("LMDB: keys_with_prefix failed for prefix=[{}], path=[{}], err={:?}", prefix, self.path, e);
+                failed(&e, "Failed to scan keys")
+            }
+        }
+    }
+
+    /// Entries with keys in [start, end), in key order. The cursor is positioned
+    /// at start and the walk stops at the first key >= end. Keys that are not
+    /// valid UTF-8 are skipped, as in keys_with_prefix.
+    pub fn range(&mut self, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        if start >= end {
+            return StorageResult::Ok(Vec::new());
+        }
+
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Vec<(String, Vec<u8>)>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let mut entries = Vec::new();
+            if let Some(db) = self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+                // LMDB rejects a zero-length key, an empty start means the first key
+                let from = if start.is_empty() {
+                    Bound::Unbounded
+                } else {
+                    Bound::Included(start.as_bytes())
+                };
+                let bounds = (from, Bound::Excluded(end.as_bytes()));
+                for entry in db.range(&txn, &bounds)? {
+                    let (key, val) = entry?;
+                    match std::str::from_utf8(key) {
+                        Ok(key) => entries.push((key.to_string(), val.to_vec())),
+                        Err(_) => warn!("LMDB: range skips a key that is not valid UTF-8, start=[{}], end=[{}], path=[{}]", start, end, self.path),
+                    }
+                }
+            }
+            Ok(entries)
+        })();
+
+        self.finish_op("range", start, started);
+        match res {
+            Ok(entries) => {
+                let mut decoded = Vec::with_capacity(entries.len());
+                for (key, val) in entries {
+                    match decode_value(self.auto_decompress, Cow::Owned(val), &key, &self.path) {
+                        StorageResult::Ok(val) => decoded.push((key, val.into_owned())),
+                        other => return other.map(|_| Vec::new()),
+                    }
+                }
+                StorageResult::Ok(decoded)
+            },
+            Err(e) => {
+                error!("LMDB: range failed for start=[{}], end=[{}], path=[{}], err={:?}", start, end, self.path, e);
+                failed(&e, "Failed to read range")
+            }
+        }
+    }
+
+    /// Count keys grouped by the part before the first separator.
+    /// Single cursor walk in one read transaction; values are not decoded.
+    pub fn count_by_prefix(&mut self, separator: char) -> StorageResult<HashMap<String, usize>> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<HashMap<String, usize>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            let mut counts = HashMap::new();
+            if let Some(db) = self.env.env()?.open_database::<Bytes, DecodeIgnore>(&txn, None)? {
+                for entry in db.iter(&txn)? {
+                    let (key, _) = entry?;
+                    match std::str::from_utf8(key) {
+                        Ok(key) => add_prefix_count(&mut counts, key, separator),
+                        Err(_) => warn!("LMDB: count_by_prefix skips a key that is not valid UTF-8, path=[{}]", self.path),
+                    }
+                }
+            }
+            Ok(counts)
+        })();
+
+        self.finish_op("count_by_prefix", "*", started);
+        match res {
+            Ok(counts) => StorageResult::Ok(counts),
+            Err(e) => {
+                error!("LMDB: count_by_prefix failed, path=[{}], err={:?}", self.path, e);
+                failed(&e, "Failed to count keys")
+            }
+        }
+    }
+
+    pub fn get_v(&mut self, key: &str) -> Option<String> {
+        self.get::<String>(key)
+    }
+
+    pub fn get_raw(&mut self, key: &str) -> Option<Vec<u8>> {
+        self.get::<Vec<u8>>(key)
+    }
+
+    /// Read transaction that owns its handle to the environment, so it can
+    /// outlive this instance. The database is None if it has not been created yet
+    fn snapshot_section(&self, storage: StorageId) -> heed::Result<LmdbSnapshotSection> {
+        let txn = self.env.static_read_txn()?;
+        self.txn_counters.record_read();
+        let db = self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)?;
+        Ok(LmdbSnapshotSection {
+            storage,
+            txn,
+            db,
+            path: self.path.clone(),
+            auto_decompress: self.auto_decompress,
+        })
+    }
+
+    pub fn get<T: FromMdbValue>(&mut self, key: &str) -> Option<T> {
+        self.try_get::<T>(key).ok()
+    }
+
+    /// Same as get, but keeps the reason of a miss: NotFound for a missing key,
+    /// UnprocessableEntity for a zstd frame that fails to decode
+    pub fn try_get<T: FromMdbValue>(&mut self, key: &str) -> StorageResult<T> {
+        let _span = txn_span("lmdb", "get", &self.path, key.len());
+        let started = self.start_op();
+        let res = self.read::<T>(key);
+        self.finish_op("get", key, started);
+        txn_done(res.is_ok());
+        res
+    }
+
+    fn read<T: FromMdbValue>(&mut self, key: &str) -> StorageResult<T> {
+        for attempt in 0..2 {
+            if attempt > 0 {
+                txn_retry("lmdb", "get", &self.path);
+            }
+            self.read_counter += 1;
+            if self.read_counter > self.max_read_counter {
+                warn!("db {} reopen for key=[{}] (max counter reached)", self.path, key);
+                self.open();
+            }
+
+            match self.env.read_txn() {
+                Ok(txn) => {
+                    self.txn_counters.record_read();
+                    match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(&txn, None)) {
+                        Ok(Some(db)) => {
+                            match db.get(&txn, key.as_bytes()) {
+                                Ok(Some(val)) => {
+                                    return decode_value(self.auto_decompress, Cow::Borrowed(val), key, &self.path)
+                                        .and_then(|val| T::from_mdb_value(&val).into());
+                                },
+                                Ok(None) => {
+                                    return StorageResult::NotFound;
+                                },
+                                Err(e) => {
+                                    error!("LMDB: db.get failed for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                                    return StorageResult::NotFound;
+                                },
+                            }
+                        },
+                        Ok(None) => {
+                            error!("LMDB: database not found for key=[{}], path=[{}]", key, self.path);
+                            return StorageResult::NotFound;
+                        },
+                        Err(e) => {
+                            error!("LMDB: failed to open database for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                            std::thread::sleep(std::time::Duration::from_millis(100));
+                        }
+                    }
+                },
+                Err(e) => {
+                    error!("LMDB: failed to create read transaction for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                    if env_closed(&e) {
+                        return StorageResult::NotReady;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                },
+            }
+        }
+
+        StorageResult::NotFound
+    }
+
+    pub fn count(&mut self) -> usize {
+        let _span = txn_span("lmdb", "count", &self.path, 0);
+        let started = self.start_op();
+        let res = self.count_entries();
+        self.finish_op("count", "*", started);
+        res
+    }
+
+    fn count_entries(&mut self) -> usize {
+        for attempt in 0..2 {
+            if attempt > 0 {
+                txn_retry("lmdb", "count", &self.path);
+            }
+            match self.env.read_txn() {
+                Ok(txn) => {
+                    self.txn_counters.record_read();
+                    match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(&txn, None)) {
+                        Ok(Some(db)) => {
                             match db.len(&txn) {
                                 Ok(count) => {
                                     return count as usize;
@@ -334,25 +1200,266 @@ impl LmdbInstance {
         0
     }
 
+    /// Entry count and page usage of the database
+    pub fn stats(&mut self) -> StorageResult<StorageStats> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Option<heed::DatabaseStat>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            match self.env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+                Some(db) => db.stat(&txn).map(Some),
+                None => Ok(None),
+            }
+        })();
+
+        self.finish_op("stats", "*", started);
+        match res {
+            Ok(Some(stat)) => StorageResult::Ok(StorageStats {
+                entries: stat.entries,
+                size_bytes: (stat.branch_pages + stat.leaf_pages + stat.overflow_pages) as u64 * stat.page_size as u64,
+                depth: stat.depth,
+                overflow_pages: stat.overflow_pages as u64,
+            }),
+            Ok(None) => StorageResult::Ok(StorageStats::default()),
+            Err(e) => {
+                error!("LMDB: failed to get stats, path=[{}], err={:?}", self.path, e);
+                failed(&e, "Failed to get stats")
+            },
+        }
+    }
+
+    /// Value of key in the named sub-database name.
+    /// NotFound if either the sub-database or the key does not exist
+    pub fn get_named(&self, name: &str, key: &str) -> StorageResult<Vec<u8>> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<Option<Vec<u8>>> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            match self.env.env()?.open_database::<Bytes, Bytes>(&txn, Some(name))? {
+                Some(db) => Ok(db.get(&txn, key.as_bytes())?.map(|val| val.to_vec())),
+                None => Ok(None),
+            }
+        })();
+
+        self.finish_op("get_named", key, started);
+        match res {
+            Ok(Some(val)) => decode_value(self.auto_decompress, Cow::Owned(val), key, &self.path).map(Cow::into_owned),
+            Ok(None) => StorageResult::NotFound,
+            Err(e) => {
+                error!("LMDB: get from sub-database failed, name=[{}], key=[{}], path=[{}], err={:?}", name, key, self.path, e);
+                failed(&e, "Failed to read key")
+            },
+        }
+    }
+
+    /// Write key into the named sub-database name, creating it if needed
+    pub fn put_named(&self, name: &str, key: &str, val: &[u8]) -> StorageResult<()> {
+        let started = self.start_op();
+        let res = with_map_growth(&self.env, &self.path, self.map_size_ceiling, || -> heed::Result<()> {
+            let mut txn = self.env.write_txn(&self.txn_counters)?;
+            let db = self.env.env()?.create_database::<Bytes, Bytes>(&mut txn, Some(name))?;
+            db.put(&mut txn, key.as_bytes(), val)?;
+            txn.commit()
+        });
+
+        self.finish_op("put_named", key, started);
+        match res {
+            Ok(()) => StorageResult::Ok(()),
+            Err(e) => {
+                error!("LMDB: put into sub-database failed, name=[{}], key=[{}], path=[{}], err={:?}", name, key, self.path, e);
+                failed(&e, "Failed to write key")
+            },
+        }
+    }
+
+    /// Remove key from the named sub-database name
+    pub fn remove_named(&self, name: &str, key: &str) -> StorageResult<()> {
+        let started = self.start_op();
+        let res = with_map_growth(&self.env, &self.path, self.map_size_ceiling, || -> heed::Result<bool> {
+            let mut txn = self.env.write_txn(&self.txn_counters)?;
+            let found = match self.env.env()?.open_database::<Bytes, Bytes>(&txn, Some(name))? {
+                Some(db) => db.delete(&mut txn, key.as_bytes())?,
+                None => false,
+            };
+            txn.commit()?;
+            Ok(found)
+        });
+
+        self.finish_op("remove_named", key, started);
+        match res {
+            Ok(true) => StorageResult::Ok(()),
+            Ok(false) => StorageResult::NotFound,
+            Err(e) => {
+                error!("LMDB: remove from sub-database failed, name=[{}], key=[{}], path=[{}], err={:?}", name, key, self.path, e);
+                failed(&e, "Failed to remove key")
+            },
+        }
+    }
+
+    /// Number of entries in the named sub-database name, 0 if it does not exist
+    pub fn count_named(&self, name: &str) -> StorageResult<usize> {
+        let started = self.start_op();
+        let res = (|| -> heed::Result<u64> {
+            let txn = self.env.read_txn()?;
+            self.txn_counters.record_read();
+            match self.env.env()?.open_database::<Bytes, Bytes>(&txn, Some(name))? {
+                Some(db) => db.len(&txn),
+                None => Ok(0),
+            }
+        })();
+
+        self.finish_op("count_named", "*", started);
+        match res {
+            Ok(count) => StorageResult::Ok(count as usize),
+            Err(e) => {
+                error!("LMDB: count of sub-database failed, name=[{}], path=[{}], err={:?}", name, self.path, e);
+                failed(&e, "Failed to count keys")
+            },
+        }
+    }
+
+    /// Open and drop a read transaction to confirm the environment is usable
+    pub fn health_check(&self) -> StorageResult<()> {
+        match self.env.read_txn() {
+            Ok(_txn) => StorageResult::Ok(()),
+            Err(e) => {
+                error!("LMDB: health check failed, path=[{}], err={:?}", self.path, e);
+                StorageResult::NotReady
+            },
+        }
+    }
+
+    /// Force a synchronous flush of the environment to disk
+    pub fn sync(&self) -> bool {
+        // Nothing to flush, and MDB_RDONLY environments reject mdb_env_sync
+        if self.read_only {
+            return true;
+        }
+        match self.env.force_sync() {
+            Ok(()) => true,
+            Err(e) => {
+                error!("LMDB: failed to sync, path=[{}], err={:?}", self.path, e);
+                false
+            }
+        }
+    }
+
     pub fn remove(&mut self, key: &str) -> bool {
-        remove_from_lmdb(&self.env, key, &self.path)
+        let _span = txn_span("lmdb", "remove", &self.path, key.len());
+        let started = self.start_op();
+        let res = remove_from_lmdb(&self.env, &self.txn_counters, key, &self.path, self.map_size_ceiling);
+        self.finish_op("remove", key, started);
+        txn_done(res);
+        res
     }
 
     pub fn put(&mut self, key: &str, val: &[u8]) -> bool {
-        put_kv_lmdb(&self.env, key, val, &self.path)
+        let _span = txn_span("lmdb", "put", &self.path, key.len());
+        let started = self.start_op();
+        let res = put_kv_lmdb(&self.env, &self.txn_counters, key, val, &self.path, self.map_size_ceiling);
+        self.finish_op("put", key, started);
+        txn_done(res);
+        res
+    }
+
+    /// Atomically add delta to an i64 counter (missing key counts as 0) and return the new value
+    pub fn increment(&mut self, key: &str, delta: i64) -> StorageResult<i64> {
+        let started = self.start_op();
+        let res = increment_lmdb(&self.env, &self.txn_counters, key, delta, &self.path, self.map_size_ceiling);
+        self.finish_op("increment", key, started);
+        res
+    }
+
+    /// Write new if the current value equals expected (None - key is absent).
+    /// The comparison and the write happen in one write transaction.
+    pub fn compare_and_swap(&mut self, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        let started = self.start_op();
+        let res = compare_and_swap_lmdb(&self.env, &self.txn_counters, key, expected, new, &self.path, self.map_size_ceiling);
+        self.finish_op("compare_and_swap", key, started);
+        res
+    }
+
+    /// Remove all entries whose value matches pred in a single write transaction.
+    /// The predicate must not access this storage: the write transaction is held while it runs.
+    pub fn remove_matching(&mut self, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        let started = self.start_op();
+        let res = remove_matching_lmdb(&self.env, &self.txn_counters, pred, &self.path, self.map_size_ceiling);
+        self.finish_op("remove_matching", "*", started);
+        res
+    }
+
+    /// Remove all keys starting with prefix in a single write transaction
+    pub fn remove_prefix(&mut self, prefix: &str) -> StorageResult<usize> {
+        let started = self.start_op();
+        let res = remove_prefix_lmdb(&self.env, &self.txn_counters, prefix, &self.path, self.map_size_ceiling);
+        self.finish_op("remove_prefix", prefix, started);
+        res
+    }
+
+    /// Clear the database and write all entries in a single write transaction
+    pub fn replace_all(&mut self, entries: &[(&str, &[u8])]) -> bool {
+        let started = self.start_op();
+        let res = replace_all_lmdb(&self.env, &self.txn_counters, entries, &self.path, self.map_size_ceiling);
+        self.finish_op("replace_all", "*", started);
+        res
+    }
+
+    /// Run f against a single write transaction and commit once it returns.
+    /// Nothing is committed if an operation of the batch fails or f panics.
+    /// If the batch fills the map, the first transaction is aborted, the map is
+    /// grown and f is called a second time on a fresh transaction. f is FnMut
+    /// rather than FnOnce for this reason: side effects of f outside the batch
+    /// (counters, logging, sending results elsewhere) then happen twice, so they
+    /// should be idempotent or deferred until write_batch returns true.
+    /// Operations can not be buffered and replayed instead, since after the map
+    /// fills LMDB fails the rest of the transaction and f would see wrong results.
+    pub fn write_batch<F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&mut LmdbWriteBatch),
+    {
+        let started = self.start_op();
+        let res = with_map_growth(&self.env, &self.path, self.map_size_ceiling, || -> heed::Result<()> {
+            let mut txn = self.env.write_txn(&self.txn_counters)?;
+            let db = self.env.env()?.create_database::<Bytes, Bytes>(&mut txn, None)?;
+            let mut batch = LmdbWriteBatch { txn, db, failed: None };
+            f(&mut batch);
+            match batch.failed {
+                Some(e) => Err(e),
+                None => batch.txn.commit(),
+            }
+        });
+
+        self.finish_op("write_batch", "*", started);
+        match res {
+            Ok(()) => true,
+            Err(e) => {
+                error!("LMDB: write batch failed, path=[{}], err={:?}", self.path, e);
+                false
+            }
+        }
+    }
+
+    /// Write all entries in a single write transaction
+    pub fn put_many(&mut self, entries: &[(&str, &[u8])]) -> bool {
+        let started = self.start_op();
+        let res = put_many_lmdb(&self.env, &self.txn_counters, entries, &self.path, self.map_size_ceiling);
+        self.finish_op("put_many", "*", started);
+        res
     }
 }
 
 // Implement ZeroCopyStorage trait for LmdbInstance
 impl ZeroCopyStorage for LmdbInstance {
-    type Transaction<'tx> = heed::RoTxn<'tx, heed::WithTls>;
+    type Transaction<'tx> = LmdbReadTxn<'tx>;
     
     fn begin_ro_txn(&self) -> Result<Self::Transaction<'_>, Box<dyn std::error::Error>> {
-        self.env.read_txn().map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+        let txn = self.env.read_txn().map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
+        self.txn_counters.record_read();
+        Ok(txn)
     }
     
     fn get_with_txn<'tx>(&self, txn: &'tx Self::Transaction<'tx>, key: &str) -> Option<Cow<'tx, [u8]>> {
-        match self.env.open_database::<Bytes, Bytes>(txn, None) {
+        match self.env.env().and_then(|env| env.open_database::<Bytes, Bytes>(txn, None)) {
             Ok(Some(db)) => {
                 match db.get(txn, key.as_bytes()) {
                     Ok(Some(val)) => Some(Cow::Borrowed(val)),
@@ -364,25 +1471,193 @@ impl ZeroCopyStorage for LmdbInstance {
     }
     
     fn put(&mut self, key: &str, val: &[u8]) -> bool {
-        put_kv_lmdb(&self.env, key, val, &self.path)
+        LmdbInstance::put(self, key, val)
+    }
+}
+
+/// Read transactions opened together by LMDBStorage::multi_read_session
+struct LmdbMultiRead<'a> {
+    txns: Vec<(StorageId, &'a LmdbInstance, LmdbReadTxn<'a>)>,
+}
+
+impl MultiReadOps for LmdbMultiRead<'_> {
+    fn get(&mut self, section: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        match self.txns.iter().find(|(s, _, _)| *s == section) {
+            Some((_, db_instance, txn)) => match db_instance.get_with_txn(txn, key) {
+                Some(val) => decode_value(db_instance.auto_decompress, val, key, &db_instance.path).map(Cow::into_owned),
+                None => StorageResult::NotFound,
+            },
+            None => StorageResult::Error(format!("{:?} is not part of this read session", section)),
+        }
     }
 }
 
 impl LMDBStorage {
-    pub fn new(db_path: &str, mode: StorageMode, _max_read_counter_reopen: Option<u64>) -> LMDBStorage {
-        LMDBStorage {
-            individuals_db: LmdbInstance::new(
+    /// max_read_counter_reopen sets the read counter limit of every database,
+    /// DEFAULT_MAX_READ_COUNTER when None.
+    /// map_size_bytes sets the map size of every database environment, 10GB when None.
+    /// Environments are shared per path, so the size given by the first storage
+    /// opened for a path is kept; a conflicting size later is logged and ignored.
+    /// Panics if an environment can not be opened, see try_new.
+    pub fn new(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>, map_size_bytes: Option<usize>) -> LMDBStorage {
+        LMDBStorage::with_map_sizes(db_path, mode, max_read_counter_reopen, map_size_bytes.map(|size| [size; 3]))
+    }
+
+    /// Same as new, but returns an error when an environment can not be opened
+    /// in DEFAULT_OPEN_ATTEMPTS attempts (bad path, permissions, corrupt data).
+    pub fn try_new(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>, map_size_bytes: Option<usize>) -> Result<LMDBStorage, StorageError> {
+        LMDBStorage::try_with_map_sizes(db_path, mode, max_read_counter_reopen, map_size_bytes.map(|size| [size; 3]), DEFAULT_OPEN_ATTEMPTS)
+    }
+
+    /// Same as new, with a separate map size per database in the order
+    /// individuals, tickets, az, e.g. a large map for individuals and small
+    /// ones for tickets and az. None keeps the uniform 10GB default.
+    pub fn with_map_sizes(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>, map_sizes: Option<[usize; 3]>) -> LMDBStorage {
+        LMDBStorage::try_with_map_sizes(db_path, mode, max_read_counter_reopen, map_sizes, DEFAULT_OPEN_ATTEMPTS).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible form of with_map_sizes; every environment is tried open_attempts times
+    pub fn try_with_map_sizes(
+        db_path: &str,
+        mode: StorageMode,
+        max_read_counter_reopen: Option<u64>,
+        map_sizes: Option<[usize; 3]>,
+        open_attempts: u32,
+    ) -> Result<LMDBStorage, StorageError> {
+        if map_sizes.is_some_and(|sizes| sizes.contains(&0)) {
+            return Err(StorageError::InvalidConfiguration("LMDB map size must be greater than zero".to_string()));
+        }
+        let [individuals_size, tickets_size, az_size] = map_sizes.map_or([None; 3], |sizes| sizes.map(Some));
+        let mut storage = LMDBStorage {
+            individuals_db: LmdbInstance::try_new(
                 &(db_path.to_owned() + "/lmdb-individuals/"),
-                mode.clone()
-            ),
-            tickets_db: LmdbInstance::new(
+                mode.clone(),
+                individuals_size,
+                open_attempts
+            )?,
+            tickets_db: LmdbInstance::try_new(
                 &(db_path.to_owned() + "/lmdb-tickets/"),
-                mode.clone()
-            ),
-            az_db: LmdbInstance::new(
+                mode.clone(),
+                tickets_size,
+                open_attempts
+            )?,
+            az_db: LmdbInstance::try_new(
                 &(db_path.to_owned() + "/acl-indexes/"),
-                mode.clone()
-            ),
+                mode.clone(),
+                az_size,
+                open_attempts
+            )?,
+            namespaces_path: db_path.to_owned() + "/lmdb-namespaces/",
+            namespaces_db: None,
+            read_only: mode == StorageMode::ReadOnly,
+            strict_utf8: false,
+            sync_on_close: false,
+        };
+
+        let max_read_counter = max_read_counter_reopen.unwrap_or(DEFAULT_MAX_READ_COUNTER);
+        storage.individuals_db.set_max_read_counter(max_read_counter);
+        storage.tickets_db.set_max_read_counter(max_read_counter);
+        storage.az_db.set_max_read_counter(max_read_counter);
+        Ok(storage)
+    }
+
+    /// Enable transparent zstd decompression for all databases of this storage.
+    /// See LmdbInstance::set_auto_decompress.
+    #[cfg(feature = "compression")]
+    pub fn set_auto_decompress(&mut self, enabled: bool) {
+        self.individuals_db.set_auto_decompress(enabled);
+        self.tickets_db.set_auto_decompress(enabled);
+        self.az_db.set_auto_decompress(enabled);
+    }
+
+    /// When enabled, all databases are force-synced when the storage is dropped.
+    /// Environments stay in the global registry, so this only flushes data;
+    /// the shared handle itself is not released.
+    pub fn set_sync_on_close(&mut self, enabled: bool) {
+        self.sync_on_close = enabled;
+    }
+
+    /// Force a synchronous flush of all databases to disk
+    pub fn sync(&self) -> bool {
+        let individuals = self.individuals_db.sync();
+        let tickets = self.tickets_db.sync();
+        let az = self.az_db.sync();
+        let namespaces = self.namespaces_db.as_ref().is_none_or(|db| db.sync());
+        individuals && tickets && az && namespaces
+    }
+
+    /// Flush all databases to disk and release this storage.
+    /// Environments stay in the global registry, so other instances for
+    /// the same path keep working.
+    pub fn close_and_sync(mut self) -> bool {
+        self.sync_on_close = false;
+        self.sync()
+    }
+
+    /// When enabled, put_raw_value, put_many and replace_section reject values that
+    /// are not valid UTF-8 with UnprocessableEntity, so every stored value stays
+    /// readable via get_value.
+    pub fn set_strict_utf8(&mut self, enabled: bool) {
+        self.strict_utf8 = enabled;
+    }
+
+    /// Set the map size ceiling for all databases of this storage.
+    /// See LmdbInstance::set_map_size_ceiling.
+    pub fn set_map_size_ceiling(&mut self, ceiling: usize) {
+        self.individuals_db.set_map_size_ceiling(ceiling);
+        self.tickets_db.set_map_size_ceiling(ceiling);
+        self.az_db.set_map_size_ceiling(ceiling);
+    }
+
+    /// Set the slow operation threshold for all databases of this storage.
+    /// See LmdbInstance::set_slow_op_threshold.
+    pub fn set_slow_op_threshold(&mut self, threshold: Option<Duration>) {
+        self.individuals_db.set_slow_op_threshold(threshold);
+        self.tickets_db.set_slow_op_threshold(threshold);
+        self.az_db.set_slow_op_threshold(threshold);
+    }
+
+    /// Total number of slow operations across all databases
+    pub fn slow_op_count(&self) -> u64 {
+        self.txn_stats().slow
+    }
+
+    /// Transaction counters summed over all databases
+    pub fn txn_stats(&self) -> TxnStats {
+        self.individuals_db.txn_stats() + self.tickets_db.txn_stats() + self.az_db.txn_stats()
+    }
+
+    /// Open one read transaction per requested section and run f with gets bound to them.
+    /// Reads from a section ignore writes committed after its transaction was opened.
+    /// Each section is a separate environment with its own transaction, so a section
+    /// is consistent only with itself: a write landing between two of them may be
+    /// seen in one section and not in the other, as with snapshot.
+    /// Error without running f if a transaction of any requested section fails to open.
+    pub fn multi_read_session<R>(&mut self, sections: &[StorageId], f: impl FnOnce(&mut dyn MultiReadOps) -> R) -> StorageResult<R> {
+        let mut session = LmdbMultiRead {
+            txns: Vec::with_capacity(sections.len()),
+        };
+        for storage in sections {
+            if session.txns.iter().any(|(s, _, _)| s == storage) {
+                continue;
+            }
+            let db_instance = self.db_instance(storage);
+            match db_instance.begin_ro_txn() {
+                Ok(txn) => session.txns.push((storage.clone(), db_instance, txn)),
+                Err(e) => {
+                    error!("LMDB: failed to open read transaction for session, path=[{}], err={:?}", db_instance.path, e);
+                    return StorageResult::Error(format!("Failed to open read transaction for {:?}: {:?}", storage, e));
+                },
+            }
+        }
+        StorageResult::Ok(f(&mut session))
+    }
+
+    fn db_instance(&self, storage: &StorageId) -> &LmdbInstance {
+        match storage {
+            StorageId::Individuals => &self.individuals_db,
+            StorageId::Tickets => &self.tickets_db,
+            StorageId::Az => &self.az_db,
         }
     }
 
@@ -394,6 +1669,25 @@ impl LMDBStorage {
         }
     }
 
+    /// Environment holding the named namespaces as sub-databases, opened on first use.
+    /// NotFound in ReadOnly mode while no namespace has been written
+    fn namespaces_db(&mut self) -> StorageResult<&mut LmdbInstance> {
+        if self.namespaces_db.is_none() {
+            if self.read_only && !Path::new(&self.namespaces_path).exists() {
+                return StorageResult::NotFound;
+            }
+            let mode = if self.read_only { StorageMode::ReadOnly } else { StorageMode::ReadWrite };
+            match LmdbInstance::try_new(&self.namespaces_path, mode, None, DEFAULT_OPEN_ATTEMPTS) {
+                Ok(db) => self.namespaces_db = Some(db),
+                Err(e) => return StorageResult::Error(e.to_string()),
+            }
+        }
+        match self.namespaces_db.as_mut() {
+            Some(db) => StorageResult::Ok(db),
+            None => StorageResult::NotReady,
+        }
+    }
+
     pub fn open(&mut self, storage: StorageId) {
         let db_instance = self.get_db_instance(&storage);
         db_instance.open();
@@ -404,29 +1698,41 @@ impl LMDBStorage {
 
 impl Storage for LMDBStorage {
     fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        if uri.is_empty() {
+            return empty_key_error();
+        }
+
         let db_instance = self.get_db_instance(&storage);
         db_instance.get_individual(uri, iraw)
     }
 
     fn get_value(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<String> {
-        let db_instance = self.get_db_instance(&storage);
-        match db_instance.get_v(key) {
-            Some(value) => crate::common::StorageResult::Ok(value),
-            None => crate::common::StorageResult::NotFound,
+        if key.is_empty() {
+            return empty_key_error();
         }
+
+        self.get_db_instance(&storage).try_get::<String>(key)
     }
 
     fn get_raw_value(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<Vec<u8>> {
-        let db_instance = self.get_db_instance(&storage);
-        match db_instance.get_raw(key) {
-            Some(value) => crate::common::StorageResult::Ok(value),
-            None => crate::common::StorageResult::NotFound,
+        if key.is_empty() {
+            return empty_key_error();
         }
+
+        self.get_db_instance(&storage).try_get::<Vec<u8>>(key)
     }
 
     fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
         let db_instance = self.get_db_instance(&storage);
-        if put_kv_lmdb(&db_instance.env, key, val.as_bytes(), &db_instance.path) {
+        if db_instance.put(key, val.as_bytes()) {
             crate::common::StorageResult::Ok(())
         } else {
             crate::common::StorageResult::Error("Failed to put value".to_string())
@@ -434,8 +1740,20 @@ impl Storage for LMDBStorage {
     }
 
     fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && std::str::from_utf8(&val).is_err() {
+            return crate::common::StorageResult::UnprocessableEntity;
+        }
+
         let db_instance = self.get_db_instance(&storage);
-        if put_kv_lmdb(&db_instance.env, key, val.as_slice(), &db_instance.path) {
+        if db_instance.put(key, val.as_slice()) {
             crate::common::StorageResult::Ok(())
         } else {
             crate::common::StorageResult::Error("Failed to put raw value".to_string())
@@ -443,8 +1761,16 @@ impl Storage for LMDBStorage {
     }
 
     fn remove_value(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
         let db_instance = self.get_db_instance(&storage);
-        if remove_from_lmdb(&db_instance.env, key, &db_instance.path) {
+        if db_instance.remove(key) {
             crate::common::StorageResult::Ok(())
         } else {
             crate::common::StorageResult::NotFound
@@ -455,84 +1781,1398 @@ impl Storage for LMDBStorage {
         let db_instance = self.get_db_instance(&storage);
         crate::common::StorageResult::Ok(db_instance.count())
     }
-}
 
-fn remove_from_lmdb(env: &Arc<Env>, key: &str, path: &str) -> bool {
-    match env.write_txn() {
-        Ok(mut txn) => {
-            match env.open_database::<Bytes, Bytes>(&txn, None) {
-                Ok(Some(db)) => {
-                    match db.delete(&mut txn, key.as_bytes()) {
-                        Ok(true) => {
-                            match txn.commit() {
-                                Ok(_) => true,
-                                Err(e) => {
-                                    error!("LMDB: failed to commit removal for key=[{}], path=[{}], err={:?}", key, path, e);
-                                    false
-                                }
-                            }
-                        },
-                        Ok(false) => {
-                            // Key not found
-                            false
-                        },
-                        Err(e) => {
-                            error!("LMDB: failed to remove key=[{}] from path=[{}], err={:?}", key, path, e);
-                            false
-                        }
-                    }
-                },
-                Ok(None) => {
-                    error!("LMDB: database not found while removing key=[{}], path=[{}]", key, path);
-                    false
-                },
-                Err(e) => {
-                    error!("LMDB: failed to open database while removing key=[{}], path=[{}], err={:?}", key, path, e);
-                    false
-                }
-            }
-        },
-        Err(e) => {
-            error!("LMDB: failed to create write transaction while removing key=[{}], path=[{}], err={:?}", key, path, e);
-            false
+    fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.get_value(storage, key);
         }
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.namespaces_db().and_then(|db| db.get_named(namespace, key)).and_then(|val| match String::from_utf8(val) {
+            Ok(value) => StorageResult::Ok(value),
+            Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+        })
     }
-}
 
-fn put_kv_lmdb(env: &Arc<Env>, key: &str, val: &[u8], path: &str) -> bool {
-    match env.write_txn() {
-        Ok(mut txn) => {
-            match env.open_database::<Bytes, Bytes>(&txn, None) {
-                Ok(Some(db)) => {
-                    match db.put(&mut txn, key.as_bytes(), val) {
-                        Ok(_) => {
-                            match txn.commit() {
-                                Ok(_) => true,
-                                Err(e) => {
-                                    error!("LMDB: failed to commit put for key=[{}], path=[{}], err={:?}", key, path, e);
-                                    false
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            error!("LMDB: failed to put key=[{}] into path=[{}], err={:?}", key, path, e);
-                            false
-                        }
-                    }
-                },
-                Ok(None) => {
-                    error!("LMDB: database not found while putting key=[{}], path=[{}]", key, path);
-                    false
-                },
-                Err(e) => {
-                    error!("LMDB: failed to open database while putting key=[{}], path=[{}], err={:?}", key, path, e);
-                    false
-                }
-            }
+    fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.put_value(storage, key, val);
+        }
+        if self.read_only {
+            return read_only_error();
+        }
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.namespaces_db().and_then(|db| db.put_named(namespace, key, val.as_bytes()))
+    }
+
+    fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.remove_value(storage, key);
+        }
+        if self.read_only {
+            return read_only_error();
+        }
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.namespaces_db().and_then(|db| db.remove_named(namespace, key))
+    }
+
+    fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.count(storage);
+        }
+
+        match self.namespaces_db() {
+            StorageResult::NotFound => StorageResult::Ok(0),
+            res => res.and_then(|db| db.count_named(namespace)),
+        }
+    }
+
+    fn stats(&mut self, storage: StorageId) -> crate::common::StorageResult<StorageStats> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.stats()
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    /// Each database is a separate environment, so the transactions are opened
+    /// one after another: a snapshot is consistent within every section only.
+    /// While it is alive the maps of these environments can not grow.
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        let mut sections = Vec::with_capacity(3);
+        for storage in StorageId::all() {
+            let db_instance = self.db_instance(&storage);
+            match db_instance.snapshot_section(storage.clone()) {
+                Ok(section) => sections.push(section),
+                Err(e) => {
+                    error!("LMDB: failed to open read transaction for snapshot, path=[{}], err={:?}", db_instance.path, e);
+                    return failed(&e, "Failed to open snapshot");
+                },
+            }
+        }
+        StorageResult::Ok(Box::new(LmdbSnapshot { sections }))
+    }
+
+    /// Always a forced sync: heed exposes only mdb_env_sync with force
+    fn sync(&mut self, _force: bool) -> crate::common::StorageResult<()> {
+        if LMDBStorage::sync(self) {
+            crate::common::StorageResult::Ok(())
+        } else {
+            crate::common::StorageResult::Error("Failed to sync LMDB".to_string())
+        }
+    }
+
+    fn health_check(&mut self) -> crate::common::StorageResult<()> {
+        for db_instance in [&self.individuals_db, &self.tickets_db, &self.az_db] {
+            let res = db_instance.health_check();
+            if !res.is_ok() {
+                return res;
+            }
+        }
+        crate::common::StorageResult::Ok(())
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<bool> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.contains(key)
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> crate::common::StorageResult<Vec<bool>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.exists_many(keys)
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<crate::common::StorageResult<String>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.get_values_many(keys)
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, crate::common::StorageResult<Individual>)> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.get_individuals(ids)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> crate::common::StorageResult<Vec<String>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.keys_with_prefix(prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> crate::common::StorageResult<Vec<(String, Vec<u8>)>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.range(start, end)
+    }
+
+    fn scan(&mut self, storage: StorageId) -> crate::common::StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        let db_instance = self.get_db_instance(&storage);
+        crate::common::StorageResult::Ok(db_instance.scan())
+    }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> crate::common::StorageResult<HashMap<String, usize>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.count_by_prefix(depth_separator)
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> crate::common::StorageResult<usize> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.remove_matching(pred)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> crate::common::StorageResult<usize> {
+        if self.read_only {
+            return read_only_error();
+        }
+        if prefix.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.remove_prefix(prefix)
+    }
+
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> crate::common::StorageResult<Option<Vec<u8>>> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.get_if(key, pred)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> crate::common::StorageResult<()> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.read_value(key, f)
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> crate::common::StorageResult<(i64, bool)> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.increment(key, delta).map(|value| (value, value >= limit))
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> crate::common::StorageResult<bool> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.compare_and_swap(key, expected, new)
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if entries.iter().any(|(key, _)| key.is_empty()) {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && entries.iter().any(|(_, val)| std::str::from_utf8(val).is_err()) {
+            return crate::common::StorageResult::UnprocessableEntity;
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        if db_instance.replace_all(entries) {
+            crate::common::StorageResult::Ok(())
+        } else {
+            crate::common::StorageResult::Error("Failed to replace section".to_string())
+        }
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if entries.iter().any(|(key, _)| key.is_empty()) {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && entries.iter().any(|(_, val)| std::str::from_utf8(val).is_err()) {
+            return crate::common::StorageResult::UnprocessableEntity;
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        if db_instance.put_many(entries) {
+            crate::common::StorageResult::Ok(())
+        } else {
+            crate::common::StorageResult::Error("Failed to put values".to_string())
+        }
+    }
+
+    /// All pairs go through put_many, i.e. one write transaction
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> crate::common::StorageResult<()> {
+        let entries: Vec<(&str, &[u8])> = pairs.iter().map(|(key, val)| (*key, val.as_bytes())).collect();
+        self.put_many(storage, &entries)
+    }
+
+    /// Sections are read lazily, each in its own read transaction.
+    /// Error if a section can not be read when the walk starts; a section that
+    /// fails to load later ends the walk. Keys that are not valid UTF-8 are skipped
+    fn iter_all(&mut self) -> crate::common::StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        for db_instance in [&self.individuals_db, &self.tickets_db, &self.az_db] {
+            if !db_instance.health_check().is_ok() {
+                return crate::common::StorageResult::Error(format!("Failed to open read transaction, path=[{}]", db_instance.path));
+            }
+        }
+        let sections = vec![
+            (StorageId::Individuals, self.individuals_db.section_loader()),
+            (StorageId::Tickets, self.tickets_db.section_loader()),
+            (StorageId::Az, self.az_db.section_loader()),
+        ];
+        crate::common::StorageResult::Ok(Box::new(SectionChain::new(sections)))
+    }
+}
+
+impl Drop for LMDBStorage {
+    fn drop(&mut self) {
+        if self.sync_on_close {
+            LMDBStorage::sync(self);
+        }
+    }
+}
+
+fn remove_from_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, key: &str, path: &str, map_size_ceiling: usize) -> bool {
+    let remove = || -> heed::Result<bool> {
+        let mut txn = env.write_txn(counters)?;
+        let db = match env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+            Some(db) => db,
+            None => {
+                error!("LMDB: database not found while removing key=[{}], path=[{}]", key, path);
+                return Ok(false);
+            },
+        };
+        if !db.delete(&mut txn, key.as_bytes())? {
+            // Key not found
+            return Ok(false);
+        }
+        txn.commit()?;
+        Ok(true)
+    };
+
+    match with_map_growth(env, path, map_size_ceiling, remove) {
+        Ok(removed) => removed,
+        Err(e) => {
+            error!("LMDB: failed to remove key=[{}] from path=[{}], err={:?}", key, path, e);
+            false
+        }
+    }
+}
+
+// Run a write; if it fails with MDB_MAP_FULL, grow the map and retry it once.
+// The failed write transaction is dropped before the map is grown.
+fn with_map_growth<T>(env: &LmdbEnv, path: &str, map_size_ceiling: usize, mut write: impl FnMut() -> heed::Result<T>) -> heed::Result<T> {
+    match write() {
+        Err(heed::Error::Mdb(heed::MdbError::MapFull)) if grow_map(env, path, map_size_ceiling) => write(),
+        res => res,
+    }
+}
+
+// Double the map size of the environment unless that would exceed the ceiling.
+// Doubling keeps the size a multiple of the page size. The map is not grown
+// while another transaction of the environment is open in this process.
+fn grow_map(env: &LmdbEnv, path: &str, map_size_ceiling: usize) -> bool {
+    let current = match env.env() {
+        Ok(inner) => inner.info().map_size,
+        Err(e) => {
+            error!("LMDB: failed to grow map, path=[{}], err={:?}", path, e);
+            return false;
+        },
+    };
+    let new_size = match current.checked_mul(2) {
+        Some(size) if size <= map_size_ceiling => size,
+        _ => {
+            warn!("LMDB: map is full, path=[{}], map_size={} cannot grow past ceiling={}", path, current, map_size_ceiling);
+            return false;
+        },
+    };
+
+    match env.resize_if_idle(new_size) {
+        Some(Ok(())) => {
+            info!("LMDB: map is full, path=[{}], map_size grown from {} to {}", path, current, new_size);
+            true
+        },
+        Some(Err(e)) => {
+            error!("LMDB: failed to grow map, path=[{}], map_size={}, err={:?}", path, current, e);
+            false
+        },
+        None => {
+            warn!("LMDB: map is full, path=[{}], map_size={} cannot grow while other transactions are open", path, current);
+            false
+        },
+    }
+}
+
+fn increment_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, key: &str, delta: i64, path: &str, map_size_ceiling: usize) -> StorageResult<i64> {
+    let res = with_map_growth(env, path, map_size_ceiling, || -> heed::Result<Option<i64>> {
+        let mut txn = env.write_txn(counters)?;
+        let db = env.env()?.create_database::<Bytes, Bytes>(&mut txn, None)?;
+        let current = match db.get(&txn, key.as_bytes())? {
+            Some(val) => match i64::from_mdb_value(val) {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+            None => 0,
+        };
+        let new_value = current.saturating_add(delta);
+        db.put(&mut txn, key.as_bytes(), &new_value.to_le_bytes())?;
+        txn.commit()?;
+        Ok(Some(new_value))
+    });
+
+    match res {
+        Ok(Some(value)) => StorageResult::Ok(value),
+        Ok(None) => {
+            error!("LMDB: value is not an i64 counter, key=[{}], path=[{}]", key, path);
+            StorageResult::UnprocessableEntity
         },
         Err(e) => {
-            error!("LMDB: failed to create write transaction while putting key=[{}], path=[{}], err={:?}", key, path, e);
+            error!("LMDB: failed to increment key=[{}], path=[{}], err={:?}", key, path, e);
+            failed(&e, "Failed to increment")
+        }
+    }
+}
+
+fn compare_and_swap_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, key: &str, expected: Option<&[u8]>, new: &[u8], path: &str, map_size_ceiling: usize) -> StorageResult<bool> {
+    let swap = || -> heed::Result<bool> {
+        let mut txn = env.write_txn(counters)?;
+        let db = env.env()?.create_database::<Bytes, Bytes>(&mut txn, None)?;
+        if db.get(&txn, key.as_bytes())? != expected {
+            return Ok(false);
+        }
+        db.put(&mut txn, key.as_bytes(), new)?;
+        txn.commit()?;
+        Ok(true)
+    };
+
+    match with_map_growth(env, path, map_size_ceiling, swap) {
+        Ok(swapped) => StorageResult::Ok(swapped),
+        Err(e) => {
+            error!("LMDB: failed to compare and swap key=[{}], path=[{}], err={:?}", key, path, e);
+            failed(&e, "Failed to compare and swap")
+        }
+    }
+}
+
+fn remove_matching_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, pred: &mut dyn FnMut(&[u8]) -> bool, path: &str, map_size_ceiling: usize) -> StorageResult<usize> {
+    let res = with_map_growth(env, path, map_size_ceiling, || -> heed::Result<usize> {
+        let mut txn = env.write_txn(counters)?;
+        let db = env.env()?.create_database::<Bytes, Bytes>(&mut txn, None)?;
+        let mut removed = 0;
+        let mut iter = db.iter_mut(&mut txn)?;
+        while let Some(entry) = iter.next() {
+            let (_, val) = entry?;
+            if pred(val) {
+                // SAFETY: the value borrowed from this entry is not used after deletion
+                unsafe { iter.del_current()? };
+                removed += 1;
+            }
+        }
+        drop(iter);
+        txn.commit()?;
+        Ok(removed)
+    });
+
+    match res {
+        Ok(removed) => StorageResult::Ok(removed),
+        Err(e) => {
+            error!("LMDB: failed to remove matching entries, path=[{}], err={:?}", path, e);
+            failed(&e, "Failed to remove matching entries")
+        }
+    }
+}
+
+fn remove_prefix_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, prefix: &str, path: &str, map_size_ceiling: usize) -> StorageResult<usize> {
+    let res = with_map_growth(env, path, map_size_ceiling, || -> heed::Result<usize> {
+        let mut txn = env.write_txn(counters)?;
+        let db = env.env()?.create_database::<Bytes, DecodeIgnore>(&mut txn, None)?;
+        let mut removed = 0;
+        let mut iter = db.prefix_iter_mut(&mut txn, prefix.as_bytes())?;
+        while let Some(entry) = iter.next() {
+            entry?;
+            // SAFETY: nothing borrowed from this entry is used after deletion
+            unsafe { iter.del_current()? };
+            removed += 1;
+        }
+        drop(iter);
+        txn.commit()?;
+        Ok(removed)
+    });
+
+    match res {
+        Ok(removed) => StorageResult::Ok(removed),
+        Err(e) => {
+            error!("LMDB: failed to remove keys by prefix=[{}], path=[{}], err={:?}", prefix, path, e);
+            failed(&e, "Failed to remove keys by prefix")
+        }
+    }
+}
+
+fn replace_all_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, entries: &[(&str, &[u8])], path: &str, map_size_ceiling: usize) -> bool {
+    let res = with_map_growth(env, path, map_size_ceiling, || -> heed::Result<()> {
+        let mut txn = env.write_txn(counters)?;
+        let db = env.env()?.create_database::<Bytes, Bytes>(&mut txn, None)?;
+        db.clear(&mut txn)?;
+        for (key, val) in entries {
+            db.put(&mut txn, key.as_bytes(), val)?;
+        }
+        txn.commit()
+    });
+
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            error!("LMDB: failed to replace database contents, path=[{}], entries={}, err={:?}", path, entries.len(), e);
+            false
+        }
+    }
+}
+
+fn entries_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, path: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+    let res = (|| -> heed::Result<Vec<(String, Vec<u8>)>> {
+        let txn = env.read_txn()?;
+        counters.record_read();
+        let mut entries = Vec::new();
+        if let Some(db) = env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+            for entry in db.iter(&txn)? {
+                let (key, val) = entry?;
+                match std::str::from_utf8(key) {
+                    Ok(key) => entries.push((key.to_string(), val.to_vec())),
+                    Err(_) => warn!("LMDB: iter_all skips a key that is not valid UTF-8, path=[{}]", path),
+                }
+            }
+        }
+        Ok(entries)
+    })();
+
+    match res {
+        Ok(entries) => StorageResult::Ok(entries),
+        Err(e) => {
+            error!("LMDB: failed to read entries, path=[{}], err={:?}", path, e);
+            StorageResult::Error(format!("Failed to read entries: {:?}", e))
+        }
+    }
+}
+
+fn put_many_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, entries: &[(&str, &[u8])], path: &str, map_size_ceiling: usize) -> bool {
+    let res = with_map_growth(env, path, map_size_ceiling, || -> heed::Result<()> {
+        let mut txn = env.write_txn(counters)?;
+        let db = env.env()?.create_database::<Bytes, Bytes>(&mut txn, None)?;
+        for (key, val) in entries {
+            db.put(&mut txn, key.as_bytes(), val)?;
+        }
+        txn.commit()
+    });
+
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            error!("LMDB: failed to put entries, path=[{}], entries={}, err={:?}", path, entries.len(), e);
             false
         }
     }
 }
+
+fn put_kv_lmdb(env: &Arc<LmdbEnv>, counters: &TxnCounters, key: &str, val: &[u8], path: &str, map_size_ceiling: usize) -> bool {
+    let put = || -> heed::Result<bool> {
+        let mut txn = env.write_txn(counters)?;
+        let db = match env.env()?.open_database::<Bytes, Bytes>(&txn, None)? {
+            Some(db) => db,
+            None => {
+                error!("LMDB: database not found while putting key=[{}], path=[{}]", key, path);
+                return Ok(false);
+            },
+        };
+        db.put(&mut txn, key.as_bytes(), val)?;
+        txn.commit()?;
+        Ok(true)
+    };
+
+    match with_map_growth(env, path, map_size_ceiling, put) {
+        Ok(stored) => stored,
+        Err(e) => {
+            error!("LMDB: failed to put key=[{}] into path=[{}], err={:?}", key, path, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_op_threshold() {
+        let temp_dir = format!("/tmp/test-lmdb-slow-op-{}", std::process::id());
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+
+        // Disabled by default
+        instance.record_op("put", "key", Duration::from_secs(10));
+        assert_eq!(instance.slow_op_count(), 0);
+
+        instance.set_slow_op_threshold(Some(Duration::from_millis(100)));
+        instance.record_op("put", "key", Duration::from_millis(50));
+        instance.record_op("put", "key", Duration::from_millis(100));
+        assert_eq!(instance.slow_op_count(), 0);
+
+        instance.record_op("put", "key", Duration::from_millis(150));
+        instance.record_op("get", "key", Duration::from_secs(2));
+        assert_eq!(instance.slow_op_count(), 2);
+
+        // Real operations are measured too
+        instance.set_slow_op_threshold(Some(Duration::ZERO));
+        assert!(instance.put("key", b"value"));
+        assert_eq!(instance.slow_op_count(), 3);
+
+        // Batch, named and multi-key paths are timed as well, and surface in the stats
+        assert!(instance.write_batch(|batch| {
+            batch.put("batch", b"1");
+        }));
+        assert!(instance.put_named("names", "key", b"value").is_ok());
+        assert!(instance.exists_many(&["key", "batch"]).is_ok());
+        assert_eq!(instance.count_named("names"), StorageResult::Ok(1));
+        assert_eq!(instance.txn_stats().slow, 7);
+        assert_eq!(instance.slow_op_count(), 7);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_get_if() {
+        let temp_dir = format!("/tmp/test-lmdb-get-if-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Tickets, "session", "inactive").is_ok());
+
+        let mut seen = 0;
+        let res = storage.get_if(StorageId::Tickets, "session", &mut |val| {
+            seen = val.len();
+            val == b"active"
+        });
+        assert_eq!(res, StorageResult::Ok(None));
+        assert_eq!(seen, "inactive".len());
+
+        let res = storage.get_if(StorageId::Tickets, "session", &mut |val| val.starts_with(b"in"));
+        assert_eq!(res, StorageResult::Ok(Some(b"inactive".to_vec())));
+
+        let res = storage.get_if(StorageId::Tickets, "missing", &mut |_| true);
+        assert_eq!(res, StorageResult::NotFound);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_lmdb_strict_utf8_replace_section() {
+        let temp_dir = format!("/tmp/test-lmdb-strict-replace-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Az, "old", "v").is_ok());
+
+        // Both batch paths reject a value that is not UTF-8 and leave the section as is
+        storage.set_strict_utf8(true);
+        let entries: [(&str, &[u8]); 2] = [("new", b"n"), ("bin", &[0xff])];
+        assert_eq!(storage.put_many(StorageId::Az, &entries), StorageResult::UnprocessableEntity);
+        assert_eq!(storage.replace_section(StorageId::Az, &entries), StorageResult::UnprocessableEntity);
+        assert_eq!(storage.get_value(StorageId::Az, "old"), StorageResult::Ok("v".to_string()));
+        assert_eq!(storage.get_value(StorageId::Az, "new"), StorageResult::NotFound);
+
+        let entries: [(&str, &[u8]); 1] = [("new", b"n")];
+        assert!(storage.replace_section(StorageId::Az, &entries).is_ok());
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(1));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_lmdb_read_only_mode() {
+        let temp_dir = format!("/tmp/test-lmdb-read-only-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert!(storage.close_and_sync());
+
+        let mut read_only = LMDBStorage::new(&temp_dir, StorageMode::ReadOnly, None, None);
+        assert_eq!(read_only.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(read_only.count(StorageId::Individuals), StorageResult::Ok(1));
+
+        let rejected = StorageResult::Error(crate::common::READ_ONLY_ERROR.to_string());
+        assert_eq!(read_only.put_value(StorageId::Individuals, "key", "changed"), rejected);
+        assert_eq!(read_only.put_raw_value(StorageId::Tickets, "key", b"raw".to_vec()), rejected);
+        assert_eq!(read_only.remove_value(StorageId::Individuals, "key"), rejected);
+        assert_eq!(read_only.put_many(StorageId::Individuals, &[("other", b"1")]), rejected);
+        assert!(read_only.increment(StorageId::Az, "counter", 1).is_error());
+        assert_eq!(read_only.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert!(read_only.sync());
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_read_write_open_after_read_only() {
+        let temp_dir = format!("/tmp/test-lmdb-ro-then-rw-{}", std::process::id());
+
+        // Create the database without registering its environment in this process
+        {
+            fs::create_dir_all(&temp_dir).unwrap();
+            let env = unsafe { EnvOpenOptions::new().read_txn_without_tls().map_size(1 << 20).open(Path::new(&temp_dir)) }.unwrap();
+            let mut txn = env.write_txn().unwrap();
+            let db = env.create_database::<Bytes, Bytes>(&mut txn, None).unwrap();
+            db.put(&mut txn, b"key", b"value").unwrap();
+            txn.commit().unwrap();
+        }
+
+        let mut reader = LmdbInstance::new(&temp_dir, StorageMode::ReadOnly, None);
+        assert_eq!(reader.get_raw("key"), Some(b"value".to_vec()));
+
+        // The read-only environment can not be reopened while the reader holds it
+        assert!(LmdbInstance::try_new(&temp_dir, StorageMode::ReadWrite, None, 1).is_err());
+        drop(reader);
+
+        let mut writer = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(writer.put("key", b"changed"));
+
+        // Later readers share the read-write environment
+        let mut reader = LmdbInstance::new(&temp_dir, StorageMode::ReadOnly, None);
+        assert_eq!(reader.get_raw("key"), Some(b"changed".to_vec()));
+        assert!(writer.put("other", b"1"));
+        assert_eq!(reader.get_raw("other"), Some(b"1".to_vec()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_lmdb_close_and_sync() {
+        let temp_dir = format!("/tmp/test-lmdb-close-sync-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        storage.set_sync_on_close(true);
+        assert!(storage.put_value(StorageId::Individuals, "persisted", "value").is_ok());
+        assert!(storage.close_and_sync());
+
+        let mut reopened = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert_eq!(reopened.get_value(StorageId::Individuals, "persisted"), StorageResult::Ok("value".to_string()));
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_put_many_single_transaction() {
+        let temp_dir = format!("/tmp/test-lmdb-put-many-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        let before = storage.txn_stats();
+
+        let keys: Vec<String> = (0..10).map(|i| format!("key{}", i)).collect();
+        let entries: Vec<(&str, &[u8])> = keys.iter().map(|k| (k.as_str(), k.as_bytes())).collect();
+        assert!(storage.put_many(StorageId::Individuals, &entries).is_ok());
+
+        let after = storage.txn_stats();
+        assert_eq!(after.writes - before.writes, 1);
+        assert_eq!(after.committed - before.committed, 1);
+        assert_eq!(after.aborted, before.aborted);
+
+        assert_eq!(storage.get_value(StorageId::Individuals, "key7"), StorageResult::Ok("key7".to_string()));
+        assert_eq!(storage.txn_stats().reads - after.reads, 1);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_put_values_many_rolls_back_on_failure() {
+        let temp_dir = format!("/tmp/test-lmdb-put-values-many-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        let before = storage.txn_stats();
+
+        // The oversized key fails inside the write transaction, after "a" was already put
+        let long_key = "k".repeat(4096);
+        let res = storage.put_values_many(StorageId::Individuals, &[("a", "1"), (long_key.as_str(), "2"), ("b", "3")]);
+        assert!(res.is_error());
+
+        let after = storage.txn_stats();
+        assert_eq!(after.committed, before.committed);
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::NotFound);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+        assert!(storage.put_values_many(StorageId::Individuals, &[("a", "1"), ("b", "3")]).is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "b"), StorageResult::Ok("3".to_string()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_txn_stats_count_uncommitted_writes_as_aborted() {
+        let temp_dir = format!("/tmp/test-lmdb-txn-aborted-{}", std::process::id());
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+        let before = instance.txn_stats();
+
+        // Both write transactions are dropped without a commit
+        assert!(!instance.remove("missing"));
+        assert_eq!(instance.compare_and_swap("key", Some(b"old"), b"new"), StorageResult::Ok(false));
+        let after = instance.txn_stats();
+        assert_eq!(after.writes - before.writes, 2);
+        assert_eq!(after.aborted - before.aborted, 2);
+        assert_eq!(after.committed, before.committed);
+        assert_eq!(after.begun - before.begun, 2);
+
+        assert!(instance.put("key", b"old"));
+        assert_eq!(instance.txn_stats().committed - after.committed, 1);
+        assert_eq!(instance.txn_stats().aborted, after.aborted);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_iter_all_sections() {
+        let temp_dir = format!("/tmp/test-lmdb-iter-all-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "i1", "a").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "i2", "b").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t1", "c").is_ok());
+        assert!(storage.put_value(StorageId::Az, "z1", "d").is_ok());
+
+        let reads_before = storage.txn_stats().reads;
+        let mut iter = match storage.iter_all() {
+            StorageResult::Ok(iter) => iter,
+            other => panic!("iter_all failed: {:?}", other.map(|_| ())),
+        };
+        // Nothing is read until the iterator advances
+        assert_eq!(storage.txn_stats().reads, reads_before);
+
+        let first = iter.next();
+        assert_eq!(storage.txn_stats().reads, reads_before + 1);
+
+        let mut entries: Vec<(StorageId, String, Vec<u8>)> = first.into_iter().chain(iter).collect();
+        assert_eq!(storage.txn_stats().reads, reads_before + 3);
+
+        // Section-by-section order
+        assert!(entries[..2].iter().all(|(s, _, _)| *s == StorageId::Individuals));
+        entries.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            entries,
+            vec![
+                (StorageId::Individuals, "i1".to_string(), b"a".to_vec()),
+                (StorageId::Individuals, "i2".to_string(), b"b".to_vec()),
+                (StorageId::Tickets, "t1".to_string(), b"c".to_vec()),
+                (StorageId::Az, "z1".to_string(), b"d".to_vec()),
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_multi_read_session_snapshot() {
+        let temp_dir = format!("/tmp/test-lmdb-multi-read-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "doc", "v1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "acl", "v1").is_ok());
+
+        let (doc, acl) = storage.multi_read_session(&[StorageId::Individuals, StorageId::Az], |session| {
+            // A writer commits to both sections after the session has been opened
+            let writer_dir = temp_dir.clone();
+            std::thread::spawn(move || {
+                let mut writer = LMDBStorage::new(&writer_dir, StorageMode::ReadWrite, None, None);
+                assert!(writer.put_value(StorageId::Individuals, "doc", "v2").is_ok());
+                assert!(writer.put_value(StorageId::Az, "acl", "v2").is_ok());
+            })
+            .join()
+            .unwrap();
+
+            assert!(session.get(StorageId::Tickets, "doc").is_error());
+            (session.get(StorageId::Individuals, "doc"), session.get(StorageId::Az, "acl"))
+        }).unwrap();
+        assert_eq!(doc, StorageResult::Ok(b"v1".to_vec()));
+        assert_eq!(acl, StorageResult::Ok(b"v1".to_vec()));
+
+        // New reads see the write
+        assert_eq!(storage.get_value(StorageId::Individuals, "doc"), StorageResult::Ok("v2".to_string()));
+        assert_eq!(storage.get_value(StorageId::Az, "acl"), StorageResult::Ok("v2".to_string()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_exists_many_single_transaction() {
+        let temp_dir = format!("/tmp/test-lmdb-exists-many-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "c", "3").is_ok());
+
+        let before = storage.txn_stats();
+        let res = storage.exists_many(StorageId::Individuals, &["a", "b", "c", "d"]);
+        assert_eq!(res, StorageResult::Ok(vec![true, false, true, false]));
+
+        let after = storage.txn_stats();
+        assert_eq!(after.begun - before.begun, 1);
+        assert_eq!(after.reads - before.reads, 1);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_get_values_many_single_transaction() {
+        let temp_dir = format!("/tmp/test-lmdb-get-values-many-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "c", "3").is_ok());
+
+        let before = storage.txn_stats();
+        let res = storage.get_values_many(StorageId::Individuals, &["c", "b", "a", "c"]);
+        assert_eq!(
+            res,
+            vec![
+                StorageResult::Ok("3".to_string()),
+                StorageResult::NotFound,
+                StorageResult::Ok("1".to_string()),
+                StorageResult::Ok("3".to_string()),
+            ]
+        );
+
+        let after = storage.txn_stats();
+        assert_eq!(after.begun - before.begun, 1);
+        assert_eq!(after.reads - before.reads, 1);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_explicit_map_size_first_caller_wins() {
+        let temp_dir = format!("/tmp/test-lmdb-map-size-{}", std::process::id());
+        let map_size = 16 * 1024 * 1024;
+
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, Some(map_size));
+        assert_eq!(storage.individuals_db.env.env().unwrap().info().map_size, map_size);
+        assert_eq!(storage.az_db.env.env().unwrap().info().map_size, map_size);
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+
+        // The environment is shared per path, a later conflicting size is ignored
+        let mut other = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, Some(2 * map_size));
+        assert_eq!(other.individuals_db.env.env().unwrap().info().map_size, map_size);
+        assert_eq!(other.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_asymmetric_map_sizes() {
+        let temp_dir = format!("/tmp/test-lmdb-map-sizes-{}", std::process::id());
+        let sizes = [64 * 1024 * 1024, 1024 * 1024, 2 * 1024 * 1024];
+
+        let mut storage = LMDBStorage::with_map_sizes(&temp_dir, StorageMode::ReadWrite, None, Some(sizes));
+        assert_eq!(storage.individuals_db.env.env().unwrap().info().map_size, sizes[0]);
+        assert_eq!(storage.tickets_db.env.env().unwrap().info().map_size, sizes[1]);
+        assert_eq!(storage.az_db.env.env().unwrap().info().map_size, sizes[2]);
+
+        for id in StorageId::all() {
+            assert!(storage.put_value(id.clone(), "key", id.as_str()).is_ok());
+            assert_eq!(storage.get_value(id.clone(), "key"), StorageResult::Ok(id.as_str().to_string()));
+            assert_eq!(storage.count(id), StorageResult::Ok(1));
+        }
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_old_values() {
+        let temp_dir = format!("/tmp/test-lmdb-snapshot-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "z", "old").is_ok());
+
+        let mut snapshot = storage.snapshot().ok().unwrap();
+        assert!(storage.put_value(StorageId::Individuals, "a", "2").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "b", "new").is_ok());
+        assert!(storage.remove_value(StorageId::Az, "z").is_ok());
+
+        assert_eq!(snapshot.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(snapshot.get_value(StorageId::Individuals, "b"), StorageResult::NotFound);
+        assert_eq!(snapshot.get_value(StorageId::Az, "z"), StorageResult::Ok("old".to_string()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("2".to_string()));
+
+        drop(snapshot);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_snapshot_blocks_map_growth() {
+        let temp_dir = format!("/tmp/test-lmdb-snapshot-grow-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let initial = 64 * 1024;
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, Some(initial));
+        storage.set_map_size_ceiling(4 * 1024 * 1024);
+
+        let value = "x".repeat(4096);
+        let snapshot = storage.snapshot().ok().unwrap();
+        let stored = (0..100).take_while(|i| storage.put_value(StorageId::Tickets, &format!("key{}", i), &value).is_ok()).count();
+        assert!(stored < 100);
+        assert_eq!(storage.tickets_db.env.env().unwrap().info().map_size, initial);
+
+        drop(snapshot);
+        assert!(storage.put_value(StorageId::Tickets, "after", &value).is_ok());
+        assert!(storage.tickets_db.env.env().unwrap().info().map_size > initial);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_namespaces_are_isolated() {
+        let temp_dir = format!("/tmp/test-lmdb-namespaces-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+
+        // Nothing written yet: a read-only storage sees empty namespaces
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        let mut reader = LMDBStorage::new(&temp_dir, StorageMode::ReadOnly, None, None);
+        assert_eq!(reader.count_ns("sessions"), StorageResult::Ok(0));
+        assert_eq!(reader.get_value_ns("sessions", "key"), StorageResult::NotFound);
+
+        assert!(storage.put_value_ns("sessions", "key", "s").is_ok());
+        assert!(storage.put_value_ns("settings", "key", "v").is_ok());
+        assert!(storage.put_value_ns("settings", "other", "w").is_ok());
+
+        assert_eq!(storage.get_value_ns("sessions", "key"), StorageResult::Ok("s".to_string()));
+        assert_eq!(storage.get_value_ns("settings", "key"), StorageResult::Ok("v".to_string()));
+        assert_eq!(storage.count_ns("sessions"), StorageResult::Ok(1));
+        assert_eq!(storage.count_ns("settings"), StorageResult::Ok(2));
+        assert_eq!(storage.count_ns("unknown"), StorageResult::Ok(0));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+        assert!(storage.remove_value_ns("sessions", "key").is_ok());
+        assert_eq!(storage.remove_value_ns("sessions", "key"), StorageResult::NotFound);
+        assert_eq!(storage.get_value_ns("settings", "key"), StorageResult::Ok("v".to_string()));
+
+        // Reserved names map to the StorageId sections
+        assert!(storage.put_value_ns("az", "a", "1").is_ok());
+        assert_eq!(storage.get_value(StorageId::Az, "a"), StorageResult::Ok("1".to_string()));
+
+        let mut reader = LMDBStorage::new(&temp_dir, StorageMode::ReadOnly, None, None);
+        assert_eq!(reader.get_value_ns("settings", "other"), StorageResult::Ok("w".to_string()));
+        assert!(reader.put_value_ns("settings", "other", "x").is_error());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_uncreatable_path_returns_error() {
+        // A regular file can not hold the database directory
+        let file = format!("/tmp/test-lmdb-bad-path-{}", std::process::id());
+        std::fs::write(&file, b"not a directory").unwrap();
+
+        let res = LMDBStorage::try_with_map_sizes(&format!("{}/db", file), StorageMode::ReadWrite, None, None, 2);
+        assert!(matches!(res, Err(StorageError::IoError(_))), "{:?}", res);
+        assert!(LmdbInstance::try_new(&format!("{}/instance", file), StorageMode::ReadWrite, None, 1).is_err());
+
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_map_full_grows_map() {
+        let temp_dir = format!("/tmp/test-lmdb-map-grow-{}", std::process::id());
+        let initial = 64 * 1024;
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, Some(initial));
+        instance.set_map_size_ceiling(4 * 1024 * 1024);
+
+        // Far more data than the initial map can hold
+        let value = vec![7u8; 4096];
+        for i in 0..100 {
+            assert!(instance.put(&format!("key{}", i), &value), "put {} failed", i);
+        }
+        assert!(instance.env.env().unwrap().info().map_size > initial);
+        assert_eq!(instance.count(), 100);
+        assert!(instance.remove("key0"));
+
+        // No growth past the ceiling
+        let grown = instance.env.env().unwrap().info().map_size;
+        instance.set_map_size_ceiling(grown);
+        let stored = (100..1000).take_while(|i| instance.put(&format!("key{}", i), &value)).count();
+        assert!(stored < 900);
+        assert_eq!(instance.env.env().unwrap().info().map_size, grown);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_map_not_grown_while_txn_open() {
+        let temp_dir = format!("/tmp/test-lmdb-map-grow-txn-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let initial = 64 * 1024;
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, Some(initial));
+        instance.set_map_size_ceiling(4 * 1024 * 1024);
+
+        // An open read transaction blocks the resize, the write fails instead
+        let value = vec![7u8; 4096];
+        let writer = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+        let txn = writer.begin_ro_txn().unwrap();
+        let stored = (0..100).take_while(|i| instance.put(&format!("key{}", i), &value)).count();
+        assert!(stored < 100);
+        assert_eq!(instance.env.env().unwrap().info().map_size, initial);
+
+        // Once it is closed the map grows
+        drop(txn);
+        assert!(instance.put("key100", &value));
+        assert!(instance.env.env().unwrap().info().map_size > initial);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_map_full_grows_map_for_batches() {
+        let temp_dir = format!("/tmp/test-lmdb-map-grow-batch-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let initial = 64 * 1024;
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, Some(initial));
+        instance.set_map_size_ceiling(4 * 1024 * 1024);
+
+        // About 80KB in one transaction, more than the initial map
+        let entries: Vec<(String, Vec<u8>)> = (0..60).map(|i| (format!("batch{}", i), vec![1u8; 1000])).collect();
+        let entries: Vec<(&str, &[u8])> = entries.iter().map(|(key, val)| (key.as_str(), val.as_slice())).collect();
+        assert!(instance.put_many(&entries));
+        assert!(instance.env.env().unwrap().info().map_size > initial);
+        assert_eq!(instance.count(), 60);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_write_batch() {
+        let temp_dir = format!("/tmp/test-lmdb-write-batch-{}", std::process::id());
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(instance.put("stale", b"x"));
+        let before = instance.txn_counters.snapshot();
+
+        assert!(instance.write_batch(|batch| {
+            for i in 0..1000 {
+                assert!(batch.put(&format!("key{}", i), format!("val{}", i).as_bytes()));
+            }
+            assert!(batch.delete("stale"));
+            assert!(!batch.delete("missing"));
+        }));
+
+        let after = instance.txn_counters.snapshot();
+        assert_eq!(after.writes - before.writes, 1);
+        assert_eq!(after.committed - before.committed, 1);
+        assert_eq!(instance.count(), 1000);
+        assert_eq!(instance.get_v("key999"), Some("val999".to_string()));
+        assert_eq!(instance.get_raw("stale"), None);
+
+        // A panic inside the closure aborts the transaction
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            instance.write_batch(|batch| {
+                batch.put("partial", b"1");
+                panic!("batch interrupted");
+            })
+        }));
+        assert!(res.is_err());
+        assert_eq!(instance.get_raw("partial"), None);
+
+        // So does a failed operation: the empty key is rejected by LMDB
+        assert!(!instance.write_batch(|batch| {
+            batch.put("first", b"1");
+            assert!(!batch.put("", b"2"));
+        }));
+        assert_eq!(instance.get_raw("first"), None);
+        assert_eq!(instance.count(), 1000);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_max_read_counter_from_constructor() {
+        let temp_dir = format!("/tmp/test-lmdb-read-counter-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, Some(5), None);
+        assert_eq!(storage.individuals_db.max_read_counter(), 5);
+        assert_eq!(storage.az_db.max_read_counter(), 5);
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+
+        for expected in 1..=5 {
+            assert!(storage.get_value(StorageId::Individuals, "key").is_ok());
+            assert_eq!(storage.individuals_db.read_counter(), expected);
+        }
+        assert_eq!(storage.individuals_db.reopen_count(), 0);
+        let closing = storage.individuals_db.env.env.as_ref().unwrap().clone().prepare_for_closing();
+        assert!(!closing.wait_timeout(Duration::ZERO));
+
+        // The sixth read exceeds the limit, reopens the environment and resets the counter
+        assert!(storage.get_value(StorageId::Individuals, "key").is_ok());
+        assert_eq!(storage.individuals_db.read_counter(), 0);
+        assert_eq!(storage.individuals_db.reopen_count(), 1);
+        // The old environment was closed
+        assert!(closing.wait_timeout(Duration::ZERO));
+
+        // Reads keep working through the reopened environment
+        for _ in 0..12 {
+            assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        }
+        assert_eq!(storage.individuals_db.reopen_count(), 3);
+        assert_eq!(storage.tickets_db.reopen_count(), 0);
+
+        let default_storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert_eq!(default_storage.tickets_db.max_read_counter(), DEFAULT_MAX_READ_COUNTER);
+
+        // A shared environment stays open for the other storage
+        for _ in 0..6 {
+            assert!(storage.get_value(StorageId::Individuals, "key").is_ok());
+        }
+        assert_eq!(storage.individuals_db.read_counter(), 0);
+        assert_eq!(storage.individuals_db.reopen_count(), 3);
+        assert!(Arc::ptr_eq(&storage.individuals_db.env, &default_storage.individuals_db.env));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_compare_and_swap_concurrent() {
+        let temp_dir = format!("/tmp/test-lmdb-cas-{}", std::process::id());
+        let threads = 4;
+        let increments = 50;
+
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "counter", None, b"0"), StorageResult::Ok(true));
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "counter", None, b"0"), StorageResult::Ok(false));
+
+        // Every thread increments the counter through a read + compare_and_swap retry loop;
+        // lost updates would leave the counter short
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let temp_dir = temp_dir.clone();
+                std::thread::spawn(move || {
+                    let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+                    for _ in 0..increments {
+                        loop {
+                            let current = storage.get_value(StorageId::Az, "counter").unwrap_or_default();
+                            let next = (current.parse::<u64>().unwrap() + 1).to_string();
+                            if storage.compare_and_swap(StorageId::Az, "counter", Some(current.as_bytes()), next.as_bytes()) == StorageResult::Ok(true) {
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(storage.get_value(StorageId::Az, "counter"), StorageResult::Ok((threads * increments).to_string()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_typed_values_roundtrip() {
+        let temp_dir = format!("/tmp/test-lmdb-typed-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+
+        assert!(storage.put_raw_value(StorageId::Az, "flag", true.to_mdb_value()).is_ok());
+        assert!(storage.put_raw_value(StorageId::Az, "off", false.to_mdb_value()).is_ok());
+        assert!(storage.put_raw_value(StorageId::Az, "score", 0.25f64.to_mdb_value()).is_ok());
+        assert!(storage.put_raw_value(StorageId::Az, "ratio", (-1.5f32).to_mdb_value()).is_ok());
+        assert!(storage.put_raw_value(StorageId::Az, "count", 7u64.to_mdb_value()).is_ok());
+        assert!(storage.put_raw_value(StorageId::Az, "name", "value".to_mdb_value()).is_ok());
+
+        assert_eq!(storage.get_typed::<bool>(StorageId::Az, "flag"), StorageResult::Ok(true));
+        assert_eq!(storage.get_typed::<bool>(StorageId::Az, "off"), StorageResult::Ok(false));
+        assert_eq!(storage.get_typed::<f64>(StorageId::Az, "score"), StorageResult::Ok(0.25));
+        assert_eq!(storage.get_typed::<f32>(StorageId::Az, "ratio"), StorageResult::Ok(-1.5));
+        assert_eq!(storage.get_typed::<u64>(StorageId::Az, "count"), StorageResult::Ok(7));
+        assert_eq!(storage.get_typed::<String>(StorageId::Az, "name"), StorageResult::Ok("value".to_string()));
+
+        // Any nonzero byte reads as true, wrong lengths are rejected
+        assert_eq!(bool::from_mdb_value(&[2]), Some(true));
+        assert_eq!(bool::from_mdb_value(&[]), None);
+        assert_eq!(bool::from_mdb_value(&[1, 0]), None);
+        assert_eq!(f64::from_mdb_value(&[0; 4]), None);
+        assert_eq!(f32::from_mdb_value(&[0; 8]), None);
+        assert_eq!(storage.get_typed::<f64>(StorageId::Az, "ratio"), StorageResult::UnprocessableEntity);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    #[test]
+    fn test_get_produces_span() {
+        let temp_dir = format!("/tmp/test-lmdb-tracing-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+
+        assert!(logs_contain(r#"storage_txn{kind="lmdb" op="get""#));
+        assert!(logs_contain("key_len=3"));
+        assert!(logs_contain("lmdb-individuals"));
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_lmdb_remove_prefix() {
+        let temp_dir = format!("/tmp/test-lmdb-remove-prefix-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+
+        assert!(storage.put_value(StorageId::Az, "a:1", "1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "a:2", "2").is_ok());
+        assert!(storage.put_value(StorageId::Az, "b:1", "3").is_ok());
+
+        assert_eq!(storage.remove_prefix(StorageId::Az, "a:"), StorageResult::Ok(2));
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(1));
+        assert_eq!(storage.get_value(StorageId::Az, "b:1"), StorageResult::Ok("3".to_string()));
+        assert_eq!(storage.remove_prefix(StorageId::Az, "a:"), StorageResult::Ok(0));
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_prefix_scans_skip_non_utf8_keys() {
+        let temp_dir = format!("/tmp/test-lmdb-prefix-utf8-{}", std::process::id());
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(instance.put("ns:a", b"1"));
+
+        // A key written by another tool, not valid UTF-8
+        let mut txn = instance.env.write_txn(&instance.txn_counters).unwrap();
+        let db = instance.env.env().unwrap().create_database::<Bytes, Bytes>(&mut txn, None).unwrap();
+        db.put(&mut txn, b"ns:\xff", b"2").unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(instance.keys_with_prefix("ns:"), StorageResult::Ok(vec!["ns:a".to_string()]));
+        assert_eq!(instance.count_by_prefix(':'), StorageResult::Ok(HashMap::from([("ns".to_string(), 1)])));
+        assert_eq!(instance.range("ns:", "ns;"), StorageResult::Ok(vec![("ns:a".to_string(), b"1".to_vec())]));
+
+        drop(instance);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_closed_environment_is_not_ready() {
+        let temp_dir = format!("/tmp/test-lmdb-closed-env-{}", std::process::id());
+        let mut instance = LmdbInstance::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(instance.put("key", b"1"));
+
+        // State left behind when reopen_env closed the environment and could not open it again
+        let registered = std::mem::replace(&mut instance.env, Arc::new(LmdbEnv {
+            env: None,
+            read_only: false,
+            open_txns: Mutex::new(0),
+        }));
+
+        assert_eq!(instance.try_get::<Vec<u8>>("key"), StorageResult::NotReady);
+        assert_eq!(instance.contains("key"), StorageResult::NotReady);
+        assert_eq!(instance.exists_many(&["key"]), StorageResult::NotReady);
+        assert_eq!(instance.keys_with_prefix("k"), StorageResult::NotReady);
+        assert_eq!(instance.health_check(), StorageResult::NotReady);
+
+        // The next reopen picks up the environment registered for the path
+        instance.open();
+        assert_eq!(instance.contains("key"), StorageResult::Ok(true));
+
+        drop(registered);
+        drop(instance);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_iter_all_skips_non_utf8_keys_and_fails_on_closed_section() {
+        let temp_dir = format!("/tmp/test-lmdb-iter-all-errors-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Tickets, "t", "1").is_ok());
+
+        // A key written by another tool, not valid UTF-8
+        let instance = &storage.tickets_db;
+        let mut txn = instance.env.write_txn(&instance.txn_counters).unwrap();
+        let db = instance.env.env().unwrap().create_database::<Bytes, Bytes>(&mut txn, None).unwrap();
+        db.put(&mut txn, b"t\xff", b"2").unwrap();
+        txn.commit().unwrap();
+
+        let entries: Vec<SectionEntry> = storage.iter_all().ok().unwrap().collect();
+        assert_eq!(entries, vec![(StorageId::Tickets, "t".to_string(), b"1".to_vec())]);
+
+        // A section that can not be read is an error, not an empty section
+        let registered = std::mem::replace(&mut storage.az_db.env, Arc::new(LmdbEnv {
+            env: None,
+            read_only: false,
+            open_txns: Mutex::new(0),
+        }));
+        assert!(storage.iter_all().is_error());
+
+        drop(registered);
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_multi_read_session_fails_on_closed_section() {
+        let temp_dir = format!("/tmp/test-lmdb-multi-read-closed-{}", std::process::id());
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "doc", "v1").is_ok());
+
+        let registered = std::mem::replace(&mut storage.az_db.env, Arc::new(LmdbEnv {
+            env: None,
+            read_only: false,
+            open_txns: Mutex::new(0),
+        }));
+
+        // The section that can not be read is reported instead of being left out
+        let mut ran = false;
+        let res = storage.multi_read_session(&[StorageId::Individuals, StorageId::Az], |_| ran = true);
+        assert!(res.is_error());
+        assert!(!ran);
+
+        let res = storage.multi_read_session(&[StorageId::Individuals], |session| session.get(StorageId::Individuals, "doc"));
+        assert_eq!(res, StorageResult::Ok(StorageResult::Ok(b"v1".to_vec())));
+
+        drop(registered);
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_poisoned_registry_is_recovered() {
+        let temp_dir = format!("/tmp/test-lmdb-poisoned-registry-{}", std::process::id());
+
+        // A thread that panics while holding the registry lock poisons it
+        let _ = std::thread::spawn(|| {
+            let _map = GLOBAL_ENVS.get_or_init(|| Mutex::new(HashMap::new())).lock();
+            panic!("poison the registry");
+        })
+        .join();
+
+        let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+}
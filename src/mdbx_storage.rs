@@ -1,125 +1,211 @@
 use v_individual_model::onto::individual::Individual;
 use v_individual_model::onto::parser::parse_raw;
-use crate::common::{Storage, StorageId, StorageMode, StorageResult, ZeroCopyStorage};
+use crate::common::{add_prefix_count, empty_key_error, DEFAULT_OPEN_ATTEMPTS, OPEN_RETRY_DELAY, read_only_error, txn_done, txn_retry, txn_span, MultiReadOps, Storage, StorageId, StorageMode, StorageResult, StorageStats, ScanEntry, SectionChain, SectionEntry, SectionLoader, SnapshotReader, TxnCounters, TxnStats, ZeroCopyStorage};
 use libmdbx::{Database, DatabaseOptions, Mode, ReadWriteOptions, SyncMode, WriteFlags, WriteMap, Transaction, RO, RW};
+use crate::storage_factory::StorageError;
 use std::borrow::Cow;
 use std::iter::Iterator;
 use std::path::Path;
 use std::fs;
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, OnceLock, PoisonError};
 use std::collections::HashMap;
 use std::sync::Mutex;
 
-// Trait for types that can be deserialized from MDB value
-// Similar to heed's BytesDecode but simpler for our use case
-pub trait FromMdbValue: Sized {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self>;
-}
-
-// Implement FromMdbValue for common types
-impl FromMdbValue for Vec<u8> {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        Some(bytes.to_vec())
-    }
-}
+pub use crate::common::{FromMdbValue, ToMdbValue};
 
-impl FromMdbValue for String {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        String::from_utf8(bytes.to_vec()).ok()
-    }
+// Global registry of shared databases by path.
+// This is critical for MDBX: multiple instances in the same process must share
+// the same database for a given database path to avoid conflicts.
+// Each MdbxInstance holds an Arc<MdbxDb> clone, ensuring thread-safe shared access.
+// The map is only changed by single inserts and removals, so it stays consistent
+// after a panic while the lock was held and a poisoned lock is taken over.
+static GLOBAL_DBS: OnceLock<Mutex<HashMap<String, Arc<MdbxDb>>>> = OnceLock::new();
+
+// Error code reported for transactions of a closed database (EBADF)
+const DATABASE_CLOSED: i32 = 9;
+
+// Shared database and whether it was opened read-only.
+// The database is None only after it was closed by reopen_db and could not
+// be opened again; transactions then fail until the next reopen.
+struct MdbxDb {
+    db: Option<Database<WriteMap>>,
+    read_only: bool,
 }
 
-impl FromMdbValue for i64 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 8] = bytes.try_into().ok()?;
-        Some(i64::from_le_bytes(*arr))
+impl MdbxDb {
+    fn db(&self) -> libmdbx::Result<&Database<WriteMap>> {
+        self.db.as_ref().ok_or(libmdbx::Error::Other(DATABASE_CLOSED))
     }
-}
 
-impl FromMdbValue for u64 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 8] = bytes.try_into().ok()?;
-        Some(u64::from_le_bytes(*arr))
+    // These shadow Database::begin_ro_txn/begin_rw_txn/sync
+    fn begin_ro_txn(&self) -> libmdbx::Result<Transaction<'_, RO, WriteMap>> {
+        self.db()?.begin_ro_txn()
     }
-}
 
-impl FromMdbValue for i32 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 4] = bytes.try_into().ok()?;
-        Some(i32::from_le_bytes(*arr))
+    fn begin_rw_txn(&self) -> libmdbx::Result<Transaction<'_, RW, WriteMap>> {
+        self.db()?.begin_rw_txn()
     }
-}
 
-impl FromMdbValue for u32 {
-    fn from_mdb_value(bytes: &[u8]) -> Option<Self> {
-        let arr: &[u8; 4] = bytes.try_into().ok()?;
-        Some(u32::from_le_bytes(*arr))
+    fn sync(&self, force: bool) -> libmdbx::Result<bool> {
+        self.db()?.sync(force)
     }
 }
 
-// Global registry of shared databases by path.
-// This is critical for MDBX: multiple instances in the same process must share
-// the same database for a given database path to avoid conflicts.
-// Each MdbxInstance holds an Arc<Database> clone, ensuring thread-safe shared access.
-static GLOBAL_DBS: OnceLock<Mutex<HashMap<String, Arc<Database<WriteMap>>>>> = OnceLock::new();
+// Reads between read counter resets when the constructor gets no explicit value
+const DEFAULT_MAX_READ_COUNTER: u64 = 1000;
 
+#[derive(Debug)]
 pub struct MDBXStorage {
     individuals_db: MdbxInstance,
     tickets_db: MdbxInstance,
     az_db: MdbxInstance,
+    read_only: bool,
+    strict_utf8: bool,
+    sync_on_close: bool,
 }
 
 pub struct MdbxInstance {
     max_read_counter: u64,
     path: String,
-    db: Arc<Database<WriteMap>>,
+    db: Arc<MdbxDb>,
+    read_only: bool,
     read_counter: u64,
+    reopen_count: u64,
+    txn_counters: Arc<TxnCounters>,
+}
+
+// Only the location and counters; the database and values are not printed
+impl std::fmt::Debug for MdbxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MdbxInstance")
+            .field("path", &self.path)
+            .field("read_only", &self.read_only)
+            .field("read_counter", &self.read_counter)
+            .field("max_read_counter", &self.max_read_counter)
+            .field("reopen_count", &self.reopen_count)
+            .finish()
+    }
 }
 
 // Get or create a shared MDBX database for the given path.
 // This function ensures that all MdbxInstance objects for the same path
 // share a single Database, which is a requirement for correct MDBX operation
 // when multiple readers exist in the same process.
-fn get_or_create_db(path: &str) -> Arc<Database<WriteMap>> {
+// A read-write database also serves read-only callers. A read-only one is
+// reopened read-write for a writer once no instance holds it any more; while
+// it is still in use the writer gets an error.
+// Opening is tried up to open_attempts times (at least once) with a pause
+// between attempts; after that the last error is returned.
+fn get_or_create_db(path: &str, read_only: bool, open_attempts: u32) -> Result<Arc<MdbxDb>, StorageError> {
     let dbs = GLOBAL_DBS.get_or_init(|| Mutex::new(HashMap::new()));
-    let mut dbs_map = dbs.lock().unwrap();
+    let mut dbs_map = dbs.lock().unwrap_or_else(PoisonError::into_inner);
     
     // Return existing database if already created
-    if let Some(db) = dbs_map.get(path) {
-        return db.clone();
+    match dbs_map.get(path) {
+        Some(db) if read_only || !db.read_only => return Ok(db.clone()),
+        Some(db) if Arc::strong_count(db) > 1 => {
+            error!("MDBX: database path=[{}] is open read-only and still in use, can not reopen it read-write", path);
+            return Err(StorageError::Backend(format!("MDBX: database path=[{}] is open read-only and still in use", path)));
+        },
+        Some(_) => {
+            info!("MDBX: reopening read-only database path=[{}] read-write", path);
+            // Closes the database, nothing else holds it
+            dbs_map.remove(path);
+        },
+        None => {},
     }
+
+    let db = Arc::new(MdbxDb {
+        db: Some(open_db(path, read_only, open_attempts)?),
+        read_only,
+    });
     
-    // Create directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(path) {
+    // Store database in global registry
+    dbs_map.insert(path.to_string(), db.clone());
+    Ok(db)
+}
+
+// Close the shared database of path and open it again, so nothing of the old
+// one (reader slots, mapping) is kept. Only the last holder of the database
+// reopens it: while other instances or snapshots use it, db is left as is and
+// false is returned.
+fn reopen_db(path: &str, db: &mut Arc<MdbxDb>, open_attempts: u32) -> Result<bool, StorageError> {
+    let dbs = GLOBAL_DBS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut dbs_map = dbs.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let registered = dbs_map.get(path).is_some_and(|registered| Arc::ptr_eq(registered, db));
+    if !registered && db.db.is_none() {
+        // A previous reopen failed; take the database opened since then, if any
+        if let Some(current) = dbs_map.get(path) {
+            *db = current.clone();
+            return Ok(true);
+        }
+    }
+    // Held by the registry, by this instance and by nobody else
+    let held = if registered { 2 } else { 1 };
+    if Arc::strong_count(db) > held || Arc::weak_count(db) > 0 {
+        return Ok(false);
+    }
+    if registered {
+        dbs_map.remove(path);
+    }
+    let Some(inner) = Arc::get_mut(db) else {
+        return Ok(false);
+    };
+
+    // Closes the database, nothing else holds it
+    inner.db = None;
+    inner.db = Some(open_db(path, inner.read_only, open_attempts)?);
+    dbs_map.insert(path.to_string(), db.clone());
+    Ok(true)
+}
+
+// Open the database of path, see get_or_create_db
+fn open_db(path: &str, read_only: bool, open_attempts: u32) -> Result<Database<WriteMap>, StorageError> {
+    // Create directory if it doesn't exist; retrying can not help if that fails
+    if !read_only && let Err(e) = fs::create_dir_all(path) {
         error!("MDBX: failed to create directory path=[{}], err={:?}", path, e);
+        return Err(StorageError::IoError(format!("MDBX: failed to create directory path=[{}]: {}", path, e)));
+    }
+    // Read-only open does not create anything, retrying a missing path only delays the failure
+    if read_only && !Path::new(path).exists() {
+        error!("MDBX: can not open database read-only, path=[{}] does not exist", path);
+        return Err(StorageError::IoError(format!("MDBX: path=[{}] does not exist, can not open it read-only", path)));
     }
     
     // Open new database with retry logic
-    let db = loop {
-        let options = DatabaseOptions {
-            mode: Mode::ReadWrite(ReadWriteOptions {
+    let open_attempts = open_attempts.max(1);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mode = if read_only {
+            Mode::ReadOnly
+        } else {
+            Mode::ReadWrite(ReadWriteOptions {
                 sync_mode: SyncMode::SafeNoSync,
                 min_size: Some(0),
                 max_size: Some(10 * 1024 * 1024 * 1024), // 10GB
                 growth_step: Some(1024 * 1024 * 1024),   // 1GB growth step
                 shrink_threshold: None,
-            }),
+            })
+        };
+        let options = DatabaseOptions {
+            mode,
             max_tables: Some(1),
             ..Default::default()
         };
         
         match Database::<WriteMap>::open_with_options(Path::new(path), options) {
-            Ok(db) => break Arc::new(db),
+            Ok(db) => return Ok(db),
             Err(e) => {
-                error!("MDBX: failed to open database, path=[{}], err={:?}", path, e);
-                std::thread::sleep(std::time::Duration::from_secs(1));
+                error!("MDBX: failed to open database, path=[{}], attempt {}/{}, err={:?}", path, attempt, open_attempts, e);
+                if attempt >= open_attempts {
+                    return Err(StorageError::IoError(format!("MDBX: failed to open database path=[{}]: {}", path, e)));
+                }
+                std::thread::sleep(OPEN_RETRY_DELAY);
             }
         }
-    };
-    
-    // Store database in global registry
-    dbs_map.insert(path.to_string(), db.clone());
-    db
+    }
 }
 
 struct MdbxIterator {
@@ -145,20 +231,59 @@ impl MdbxInstance {
     /// Create a new MdbxInstance.
     /// The database is shared globally - multiple instances for the same path
     /// will use the same underlying MDBX database.
-    pub fn new(path: &str, _mode: StorageMode) -> Self {
-        let db = get_or_create_db(path);
+    /// In ReadOnly mode the database is opened with the MDBX read-only mode;
+    /// a path that does not exist fails at once, without retries.
+    /// Panics if the database can not be opened in DEFAULT_OPEN_ATTEMPTS attempts,
+    /// use try_new to handle a bad path.
+    pub fn new(path: &str, mode: StorageMode) -> Self {
+        MdbxInstance::try_new(path, mode, DEFAULT_OPEN_ATTEMPTS).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as new, but gives up after open_attempts failed attempts to open
+    /// the database (one second apart) and returns the error.
+    pub fn try_new(path: &str, mode: StorageMode, open_attempts: u32) -> Result<Self, StorageError> {
+        let read_only = mode == StorageMode::ReadOnly;
+        let db = get_or_create_db(path, read_only, open_attempts)?;
         
-        MdbxInstance {
-            max_read_counter: 1000,
+        Ok(MdbxInstance {
+            max_read_counter: DEFAULT_MAX_READ_COUNTER,
             path: path.to_string(),
             db,
+            read_only,
             read_counter: 0,
-        }
+            reopen_count: 0,
+            txn_counters: Arc::new(TxnCounters::default()),
+        })
+    }
+
+    /// Number of reads after which the read counter is reset
+    pub fn set_max_read_counter(&mut self, max_read_counter: u64) {
+        self.max_read_counter = max_read_counter;
+    }
+
+    pub fn max_read_counter(&self) -> u64 {
+        self.max_read_counter
+    }
+
+    /// Reads since the last read counter reset
+    pub fn read_counter(&self) -> u64 {
+        self.read_counter
+    }
+
+    /// Number of times the database was reopened, see open
+    pub fn reopen_count(&self) -> u64 {
+        self.reopen_count
+    }
+
+    /// Counters of transactions begun by this instance
+    pub fn txn_stats(&self) -> TxnStats {
+        self.txn_counters.snapshot()
     }
 
     pub fn iter(&mut self) -> Box<dyn Iterator<Item = Vec<u8>>> {
         match self.db.begin_ro_txn() {
             Ok(txn) => {
+                self.txn_counters.record_read();
                 match txn.open_table(None) {
                     Ok(table) => {
                         let mut keys = Vec::new();
@@ -185,16 +310,65 @@ impl MdbxInstance {
         }
     }
 
+    /// Key/value pairs of the table, collected in a single cursor walk
+    pub fn scan(&mut self) -> Box<dyn Iterator<Item = ScanEntry>> {
+        match self.db.begin_ro_txn() {
+            Ok(txn) => {
+                self.txn_counters.record_read();
+                match txn.open_table(None) {
+                    Ok(table) => {
+                        let mut pairs = Vec::new();
+                        if let Ok(mut cursor) = txn.cursor(&table) {
+                            while let Ok(Some((key, val))) = cursor.next::<Vec<u8>, Vec<u8>>() {
+                                pairs.push((key, val));
+                            }
+                        }
+                        Box::new(pairs.into_iter())
+                    },
+                    Err(e) => {
+                        error!("MDBX: failed to open table for scan, path=[{}], err={:?}", self.path, e);
+                        Box::new(std::iter::empty())
+                    }
+                }
+            },
+            Err(e) => {
+                error!("MDBX: failed to create read transaction for scan, path=[{}], err={:?}", self.path, e);
+                Box::new(std::iter::empty())
+            },
+        }
+    }
+
+    /// Loader that reads all entries of this database in its own read transaction
+    /// when called, so a chained iterator never holds more than one transaction
+    fn section_loader(&self) -> SectionLoader {
+        let db = self.db.clone();
+        let counters = self.txn_counters.clone();
+        let path = self.path.clone();
+        Box::new(move || entries_mdbx(&db, &counters, &path))
+    }
+
+    /// Reopen the database: close and open it again if this instance is its last
+    /// holder, and reset the read counter. A shared database is kept open for the
+    /// other instances. Stale readers are reclaimed by MDBX itself when transactions
+    /// begin. Called automatically once max_read_counter reads have been made.
     pub fn open(&mut self) {
-        // Reset read counter - database is already open and shared
         self.read_counter = 0;
-        info!("MDBXStorage: reset read counter for path=[{}]", self.path);
+        match reopen_db(&self.path, &mut self.db, DEFAULT_OPEN_ATTEMPTS) {
+            Ok(true) => {
+                self.reopen_count += 1;
+                info!("MDBXStorage: reopened database for path=[{}]", self.path);
+            },
+            Ok(false) => info!("MDBXStorage: database path=[{}] is in use by other instances, not reopened", self.path),
+            Err(e) => error!("MDBX: failed to reopen database, path=[{}], err={:?}", self.path, e),
+        }
     }
 
     /// Create a read-only transaction for zero-copy operations
     /// Use this with get_with_txn to avoid data copying
     pub fn begin_ro_txn(&self) -> Result<Transaction<'_, RO, WriteMap>, libmdbx::Error> {
-        self.db.begin_ro_txn()
+        let txn = self.db.begin_ro_txn()?;
+        self.txn_counters.record_read();
+        Ok(txn)
     }
 
     /// Create a read-write transaction
@@ -225,6 +399,62 @@ impl MdbxInstance {
         }
     }
 
+    /// Return the value only if pred accepts it. The predicate runs on the bytes
+    /// borrowed from the read transaction, so rejected values are never copied.
+    pub fn get_if(&mut self, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        match self.db.begin_ro_txn() {
+            Ok(txn) => {
+                self.txn_counters.record_read();
+                match txn.open_table(None) {
+                    Ok(table) => {
+                        match txn.get::<Cow<[u8]>>(&table, key.as_bytes()) {
+                            Ok(Some(val)) => StorageResult::Ok(if pred(&val) { Some(val.into_owned()) } else { None }),
+                            Ok(None) => StorageResult::NotFound,
+                            Err(e) => {
+                                error!("MDBX: get_if failed for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                                StorageResult::Error(format!("Failed to get value: {:?}", e))
+                            },
+                        }
+                    },
+                    Err(e) => {
+                        error!("MDBX: failed to open table in get_if for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                        StorageResult::Error(format!("Failed to open table: {:?}", e))
+                    }
+                }
+            },
+            Err(e) => {
+                error!("MDBX: failed to create read transaction in get_if for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                StorageResult::Error(format!("Failed to create read transaction: {:?}", e))
+            },
+        }
+    }
+
+    /// Call f on the value bytes borrowed from a read transaction; the
+    /// transaction is dropped after f returns.
+    pub fn read_value(&mut self, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        let res = (|| -> libmdbx::Result<bool> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            match txn.get::<Cow<[u8]>>(&table, key.as_bytes())? {
+                Some(val) => {
+                    f(&val);
+                    Ok(true)
+                },
+                None => Ok(false),
+            }
+        })();
+
+        match res {
+            Ok(true) => StorageResult::Ok(()),
+            Ok(false) => StorageResult::NotFound,
+            Err(e) => {
+                error!("MDBX: failed to read value for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                StorageResult::Error(format!("Failed to read value: {:?}", e))
+            },
+        }
+    }
+
     pub fn get_individual(&mut self, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
         if let Some(val) = self.get_raw(uri) {
             iraw.set_raw(&val);
@@ -240,6 +470,213 @@ impl MdbxInstance {
         StorageResult::NotFound
     }
 
+    /// Check presence of a key; the value is decoded as (), so nothing is copied.
+    pub fn contains(&mut self, key: &str) -> StorageResult<bool> {
+        let res = (|| -> libmdbx::Result<bool> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            Ok(txn.get::<()>(&table, key.as_bytes())?.is_some())
+        })();
+
+        match res {
+            Ok(found) => StorageResult::Ok(found),
+            Err(e) => {
+                error!("MDBX: contains failed for key=[{}], path=[{}], err={:?}", key, self.path, e);
+                StorageResult::Error(format!("Failed to check key: {:?}", e))
+            }
+        }
+    }
+
+    /// Check presence of every key in a single read transaction.
+    /// Values are decoded as (), so nothing is copied out of the map. An empty
+    /// key can not be stored and is reported as absent.
+    pub fn exists_many(&mut self, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        let res = (|| -> libmdbx::Result<Vec<bool>> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            let mut found = Vec::with_capacity(keys.len());
+            for key in keys {
+                found.push(!key.is_empty() && txn.get::<()>(&table, key.as_bytes())?.is_some());
+            }
+            Ok(found)
+        })();
+
+        match res {
+            Ok(found) => StorageResult::Ok(found),
+            Err(e) => {
+                error!("MDBX: exists_many failed, path=[{}], keys={}, err={:?}", self.path, keys.len(), e);
+                StorageResult::Error(format!("Failed to check keys: {:?}", e))
+            }
+        }
+    }
+
+    /// Read every key in a single read transaction; results follow the order of keys.
+    /// Values that are not valid UTF-8 are reported as NotFound, like get_v does.
+    pub fn get_values_many(&mut self, keys: &[&str]) -> Vec<StorageResult<String>> {
+        let res = (|| -> libmdbx::Result<Vec<StorageResult<String>>> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            let mut values = Vec::with_capacity(keys.len());
+            for key in keys {
+                if key.is_empty() {
+                    values.push(empty_key_error());
+                    continue;
+                }
+                let value = txn.get::<Cow<[u8]>>(&table, key.as_bytes())?;
+                values.push(match value.and_then(|val| String::from_mdb_value(&val)) {
+                    Some(value) => StorageResult::Ok(value),
+                    None => StorageResult::NotFound,
+                });
+            }
+            Ok(values)
+        })();
+
+        match res {
+            Ok(values) => values,
+            Err(e) => {
+                error!("MDBX: get_values_many failed, path=[{}], keys={}, err={:?}", self.path, keys.len(), e);
+                keys.iter().map(|_| StorageResult::Error(format!("Failed to read keys: {:?}", e))).collect()
+            }
+        }
+    }
+
+    /// Load and parse individuals in a single read transaction; results follow the order of ids.
+    pub fn get_individuals(&mut self, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        let res = (|| -> libmdbx::Result<Vec<StorageResult<Individual>>> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            let mut individuals = Vec::with_capacity(ids.len());
+            for id in ids {
+                if id.is_empty() {
+                    individuals.push(empty_key_error());
+                    continue;
+                }
+                individuals.push(match txn.get::<Cow<[u8]>>(&table, id.as_bytes())? {
+                    Some(val) => {
+                        let mut individual = Individual::default();
+                        individual.set_raw(&val);
+                        if parse_raw(&mut individual).is_ok() {
+                            StorageResult::Ok(individual)
+                        } else {
+                            error!("MDBX: fail parse binobj, path=[{}], len={}, uri=[{}]", self.path, individual.get_raw_len(), id);
+                            StorageResult::UnprocessableEntity
+                        }
+                    },
+                    None => StorageResult::NotFound,
+                });
+            }
+            Ok(individuals)
+        })();
+
+        match res {
+            Ok(individuals) => ids.iter().map(|id| id.to_string()).zip(individuals).collect(),
+            Err(e) => {
+                error!("MDBX: get_individuals failed, path=[{}], ids={}, err={:?}", self.path, ids.len(), e);
+                ids.iter().map(|id| (id.to_string(), StorageResult::Error(format!("Failed to read individuals: {:?}", e)))).collect()
+            }
+        }
+    }
+
+    /// Keys starting with prefix, in key order. The cursor is positioned at the
+    /// prefix (MDBX_SET_RANGE) and stops at the first key outside of it.
+    pub fn keys_with_prefix(&mut self, prefix: &str) -> StorageResult<Vec<String>> {
+        let res = (|| -> libmdbx::Result<Vec<String>> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            let mut cursor = txn.cursor(&table)?;
+            let mut keys = Vec::new();
+            let mut entry = cursor.set_range::<Cow<[u8]>, ()>(prefix.as_bytes())?;
+            while let Some((key, ())) = entry {
+                if !key.starts_with(prefix.as_bytes()) {
+                    break;
+                }
+                match std::str::from_utf8(&key) {
+                    Ok(key) => keys.push(key.to_string()),
+                    Err(_) => warn!("MDBX: keys_with_prefix skips a key that is not valid UTF-8, prefix=[{}], path=[{}]", prefix, self.path),
+                }
+                entry = cursor.next::<Cow<[u8]>, ()>()?;
+            }
+            Ok(keys)
+        })();
+
+        match res {
+            Ok(keys) => StorageResult::Ok(keys),
+            Err(e) => {
+                error!("MDBX: keys_with_prefix failed for prefix=[{}], path=[{}], err={:?}", prefix, self.path, e);
+                StorageResult::Error(format!("Failed to scan keys: {:?}", e))
+            }
+        }
+    }
+
+    /// Entries with keys in [start, end), in key order. The cursor is positioned
+    /// at start and the walk stops at the first key >= end. Keys that are not
+    /// valid UTF-8 are skipped, as in keys_with_prefix.
+    pub fn range(&mut self, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        if start >= end {
+            return StorageResult::Ok(Vec::new());
+        }
+
+        let res = (|| -> libmdbx::Result<Vec<(String, Vec<u8>)>> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            let mut cursor = txn.cursor(&table)?;
+            let mut entries = Vec::new();
+            let mut entry = cursor.set_range::<Cow<[u8]>, Vec<u8>>(start.as_bytes())?;
+            while let Some((key, val)) = entry {
+                if key.as_ref() >= end.as_bytes() {
+                    break;
+                }
+                match std::str::from_utf8(&key) {
+                    Ok(key) => entries.push((key.to_string(), val)),
+                    Err(_) => warn!("MDBX: range skips a key that is not valid UTF-8, start=[{}], end=[{}], path=[{}]", start, end, self.path),
+                }
+                entry = cursor.next::<Cow<[u8]>, Vec<u8>>()?;
+            }
+            Ok(entries)
+        })();
+
+        match res {
+            Ok(entries) => StorageResult::Ok(entries),
+            Err(e) => {
+                error!("MDBX: range failed for start=[{}], end=[{}], path=[{}], err={:?}", start, end, self.path, e);
+                StorageResult::Error(format!("Failed to read range: {:?}", e))
+            }
+        }
+    }
+
+    /// Count keys grouped by the part before the first separator.
+    /// Single cursor walk in one read transaction; values are decoded as ().
+    pub fn count_by_prefix(&mut self, separator: char) -> StorageResult<HashMap<String, usize>> {
+        let res = (|| -> libmdbx::Result<HashMap<String, usize>> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            let mut cursor = txn.cursor(&table)?;
+            let mut counts = HashMap::new();
+            while let Some((key, ())) = cursor.next::<Cow<[u8]>, ()>()? {
+                match std::str::from_utf8(&key) {
+                    Ok(key) => add_prefix_count(&mut counts, key, separator),
+                    Err(_) => warn!("MDBX: count_by_prefix skips a key that is not valid UTF-8, path=[{}]", self.path),
+                }
+            }
+            Ok(counts)
+        })();
+
+        match res {
+            Ok(counts) => StorageResult::Ok(counts),
+            Err(e) => {
+                error!("MDBX: count_by_prefix failed, path=[{}], err={:?}", self.path, e);
+                StorageResult::Error(format!("Failed to count keys: {:?}", e))
+            }
+        }
+    }
+
     pub fn get_v(&mut self, key: &str) -> Option<String> {
         self.get::<String>(key)
     }
@@ -249,15 +686,26 @@ impl MdbxInstance {
     }
 
     pub fn get<T: FromMdbValue>(&mut self, key: &str) -> Option<T> {
-        for _it in 0..2 {
+        let _span = txn_span("mdbx", "get", &self.path, key.len());
+        let res = self.read::<T>(key);
+        txn_done(res.is_some());
+        res
+    }
+
+    fn read<T: FromMdbValue>(&mut self, key: &str) -> Option<T> {
+        for attempt in 0..2 {
+            if attempt > 0 {
+                txn_retry("mdbx", "get", &self.path);
+            }
             self.read_counter += 1;
             if self.read_counter > self.max_read_counter {
-                warn!("db {} reset counter for key=[{}] (max counter reached)", self.path, key);
-                self.read_counter = 0;
+                warn!("db {} reopen for key=[{}] (max counter reached)", self.path, key);
+                self.open();
             }
 
             match self.db.begin_ro_txn() {
                 Ok(txn) => {
+                    self.txn_counters.record_read();
                     match txn.open_table(None) {
                         Ok(table) => {
                             match txn.get::<Vec<u8>>(&table, key.as_bytes()) {
@@ -290,9 +738,14 @@ impl MdbxInstance {
     }
 
     pub fn count(&mut self) -> usize {
-        for _it in 0..2 {
+        let _span = txn_span("mdbx", "count", &self.path, 0);
+        for attempt in 0..2 {
+            if attempt > 0 {
+                txn_retry("mdbx", "count", &self.path);
+            }
             match self.db.begin_ro_txn() {
                 Ok(txn) => {
+                    self.txn_counters.record_read();
                     match txn.open_table(None) {
                         Ok(table) => {
                             match txn.table_stat(&table) {
@@ -321,12 +774,105 @@ impl MdbxInstance {
         0
     }
 
+    /// Entry count and page usage of the table
+    pub fn stats(&mut self) -> StorageResult<StorageStats> {
+        let res = (|| -> libmdbx::Result<libmdbx::Stat> {
+            let txn = self.db.begin_ro_txn()?;
+            self.txn_counters.record_read();
+            let table = txn.open_table(None)?;
+            txn.table_stat(&table)
+        })();
+
+        match res {
+            Ok(stat) => StorageResult::Ok(StorageStats {
+                entries: stat.entries(),
+                size_bytes: stat.total_size(),
+                depth: stat.depth(),
+                overflow_pages: stat.overflow_pages() as u64,
+            }),
+            Err(e) => {
+                error!("MDBX: failed to get stats, path=[{}], err={:?}", self.path, e);
+                StorageResult::Error(format!("Failed to get stats: {:?}", e))
+            },
+        }
+    }
+
+    /// Open and drop a read transaction to confirm the database is usable
+    pub fn health_check(&self) -> StorageResult<()> {
+        match self.db.begin_ro_txn() {
+            Ok(_txn) => StorageResult::Ok(()),
+            Err(e) => {
+                error!("MDBX: health check failed, path=[{}], err={:?}", self.path, e);
+                StorageResult::NotReady
+            },
+        }
+    }
+
+    /// Force a synchronous flush of the database to disk
+    pub fn sync(&self) -> bool {
+        self.flush(true)
+    }
+
+    /// Flush committed transactions to disk. Without force the flush follows
+    /// the configured sync mode and may be skipped when nothing is pending.
+    pub fn flush(&self, force: bool) -> bool {
+        // Nothing to flush in a read-only database
+        if self.read_only {
+            return true;
+        }
+        match self.db.sync(force).map(|_| ()) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("MDBX: failed to sync, path=[{}], err={:?}", self.path, e);
+                false
+            }
+        }
+    }
+
     pub fn remove(&mut self, key: &str) -> bool {
-        remove_from_mdbx(&self.db, key, &self.path)
+        let _span = txn_span("mdbx", "remove", &self.path, key.len());
+        let res = remove_from_mdbx(&self.db, &self.txn_counters, key, &self.path);
+        txn_done(res);
+        res
     }
 
     pub fn put(&mut self, key: &str, val: &[u8]) -> bool {
-        put_kv_mdbx(&self.db, key, val, &self.path)
+        let _span = txn_span("mdbx", "put", &self.path, key.len());
+        let res = put_kv_mdbx(&self.db, &self.txn_counters, key, val, &self.path);
+        txn_done(res);
+        res
+    }
+
+    /// Atomically add delta to an i64 counter (missing key counts as 0) and return the new value
+    pub fn increment(&mut self, key: &str, delta: i64) -> StorageResult<i64> {
+        increment_mdbx(&self.db, &self.txn_counters, key, delta, &self.path)
+    }
+
+    /// Write new if the current value equals expected (None - key is absent).
+    /// The comparison and the write happen in one write transaction.
+    pub fn compare_and_swap(&mut self, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        compare_and_swap_mdbx(&self.db, &self.txn_counters, key, expected, new, &self.path)
+    }
+
+    /// Remove all entries whose value matches pred in a single write transaction.
+    /// The predicate must not access this storage: the write transaction is held while it runs.
+    pub fn remove_matching(&mut self, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        remove_matching_mdbx(&self.db, &self.txn_counters, pred, &self.path)
+    }
+
+    /// Remove all keys starting with prefix in a single write transaction
+    pub fn remove_prefix(&mut self, prefix: &str) -> StorageResult<usize> {
+        remove_prefix_mdbx(&self.db, &self.txn_counters, prefix, &self.path)
+    }
+
+    /// Clear the table and write all entries in a single write transaction
+    pub fn replace_all(&mut self, entries: &[(&str, &[u8])]) -> bool {
+        replace_all_mdbx(&self.db, &self.txn_counters, entries, &self.path)
+    }
+
+    /// Write all entries in a single write transaction
+    pub fn put_many(&mut self, entries: &[(&str, &[u8])]) -> bool {
+        put_many_mdbx(&self.db, &self.txn_counters, entries, &self.path)
     }
 }
 
@@ -335,7 +881,9 @@ impl ZeroCopyStorage for MdbxInstance {
     type Transaction<'tx> = Transaction<'tx, RO, WriteMap>;
     
     fn begin_ro_txn(&self) -> Result<Self::Transaction<'_>, Box<dyn std::error::Error>> {
-        self.db.begin_ro_txn().map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+        let txn = self.db.begin_ro_txn().map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
+        self.txn_counters.record_read();
+        Ok(txn)
     }
     
     fn get_with_txn<'tx>(&self, txn: &'tx Self::Transaction<'tx>, key: &str) -> Option<Cow<'tx, [u8]>> {
@@ -351,25 +899,165 @@ impl ZeroCopyStorage for MdbxInstance {
     }
     
     fn put(&mut self, key: &str, val: &[u8]) -> bool {
-        put_kv_mdbx(&self.db, key, val, &self.path)
+        MdbxInstance::put(self, key, val)
+    }
+}
+
+/// Snapshot of MDBXStorage: one read transaction per database.
+/// The transactions borrow databases kept alive by _dbs; txns is declared first,
+/// so it is dropped before the databases
+struct MdbxSnapshot {
+    txns: Vec<(StorageId, Transaction<'static, RO, WriteMap>)>,
+    _dbs: Vec<Arc<MdbxDb>>,
+}
+
+impl SnapshotReader for MdbxSnapshot {
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let Some((_, txn)) = self.txns.iter().find(|(s, _)| *s == storage) else {
+            return StorageResult::Error(format!("{:?} is not part of this snapshot", storage));
+        };
+        // The table is missing until the first write to the database
+        let Ok(table) = txn.open_table(None) else {
+            return StorageResult::NotFound;
+        };
+
+        match txn.get::<Vec<u8>>(&table, key.as_bytes()) {
+            Ok(Some(val)) => StorageResult::Ok(val),
+            Ok(None) => StorageResult::NotFound,
+            Err(e) => {
+                error!("MDBX: snapshot get failed for key=[{}], err={:?}", key, e);
+                StorageResult::Error(format!("Failed to read key: {:?}", e))
+            },
+        }
+    }
+}
+
+/// Read transactions opened together by MDBXStorage::multi_read_session
+struct MdbxMultiRead<'a> {
+    txns: Vec<(StorageId, &'a MdbxInstance, Transaction<'a, RO, WriteMap>)>,
+}
+
+impl MultiReadOps for MdbxMultiRead<'_> {
+    fn get(&mut self, section: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        match self.txns.iter().find(|(s, _, _)| *s == section) {
+            Some((_, db_instance, txn)) => match db_instance.get_with_txn(txn, key) {
+                Some(val) => StorageResult::Ok(val.into_owned()),
+                None => StorageResult::NotFound,
+            },
+            None => StorageResult::Error(format!("{:?} is not part of this read session", section)),
+        }
     }
 }
 
 impl MDBXStorage {
-    pub fn new(db_path: &str, mode: StorageMode, _max_read_counter_reopen: Option<u64>) -> MDBXStorage {
-        MDBXStorage {
-            individuals_db: MdbxInstance::new(
+    /// max_read_counter_reopen sets the read counter limit of every database,
+    /// DEFAULT_MAX_READ_COUNTER when None.
+    /// Panics if a database can not be opened, see try_new.
+    pub fn new(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>) -> MDBXStorage {
+        MDBXStorage::try_new(db_path, mode, max_read_counter_reopen, DEFAULT_OPEN_ATTEMPTS).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as new, but returns an error when a database can not be opened
+    /// in open_attempts attempts (bad path, permissions, corrupt data).
+    pub fn try_new(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>, open_attempts: u32) -> Result<MDBXStorage, StorageError> {
+        let mut storage = MDBXStorage {
+            individuals_db: MdbxInstance::try_new(
                 &(db_path.to_owned() + "/mdbx-individuals/"),
-                mode.clone()
-            ),
-            tickets_db: MdbxInstance::new(
+                mode.clone(),
+                open_attempts
+            )?,
+            tickets_db: MdbxInstance::try_new(
                 &(db_path.to_owned() + "/mdbx-tickets/"),
-                mode.clone()
-            ),
-            az_db: MdbxInstance::new(
+                mode.clone(),
+                open_attempts
+            )?,
+            az_db: MdbxInstance::try_new(
                 &(db_path.to_owned() + "/acl-indexes/"),
-                mode.clone()
-            ),
+                mode.clone(),
+                open_attempts
+            )?,
+            read_only: mode == StorageMode::ReadOnly,
+            strict_utf8: false,
+            sync_on_close: false,
+        };
+
+        let max_read_counter = max_read_counter_reopen.unwrap_or(DEFAULT_MAX_READ_COUNTER);
+        storage.individuals_db.set_max_read_counter(max_read_counter);
+        storage.tickets_db.set_max_read_counter(max_read_counter);
+        storage.az_db.set_max_read_counter(max_read_counter);
+        Ok(storage)
+    }
+
+    /// When enabled, all databases are force-synced when the storage is dropped.
+    /// Environments stay in the global registry, so this only flushes data;
+    /// the shared handle itself is not released.
+    pub fn set_sync_on_close(&mut self, enabled: bool) {
+        self.sync_on_close = enabled;
+    }
+
+    /// Force a synchronous flush of all databases to disk
+    pub fn sync(&self) -> bool {
+        self.flush(true)
+    }
+
+    fn flush(&self, force: bool) -> bool {
+        let individuals = self.individuals_db.flush(force);
+        let tickets = self.tickets_db.flush(force);
+        let az = self.az_db.flush(force);
+        individuals && tickets && az
+    }
+
+    /// Flush all databases to disk and release this storage.
+    /// Environments stay in the global registry, so other instances for
+    /// the same path keep working.
+    pub fn close_and_sync(mut self) -> bool {
+        self.sync_on_close = false;
+        self.sync()
+    }
+
+    /// When enabled, put_raw_value, put_many and replace_section reject values that
+    /// are not valid UTF-8 with UnprocessableEntity, so every stored value stays
+    /// readable via get_value.
+    pub fn set_strict_utf8(&mut self, enabled: bool) {
+        self.strict_utf8 = enabled;
+    }
+
+    /// Transaction counters summed over all databases
+    pub fn txn_stats(&self) -> TxnStats {
+        self.individuals_db.txn_stats() + self.tickets_db.txn_stats() + self.az_db.txn_stats()
+    }
+
+    /// Open one read transaction per requested section and run f with gets bound to them.
+    /// Reads from a section ignore writes committed after its transaction was opened.
+    /// Each section is a separate database with its own transaction, so a section
+    /// is consistent only with itself: a write landing between two of them may be
+    /// seen in one section and not in the other, as with snapshot.
+    /// Error without running f if a transaction of any requested section fails to open.
+    pub fn multi_read_session<R>(&mut self, sections: &[StorageId], f: impl FnOnce(&mut dyn MultiReadOps) -> R) -> StorageResult<R> {
+        let mut session = MdbxMultiRead {
+            txns: Vec::with_capacity(sections.len()),
+        };
+        for storage in sections {
+            if session.txns.iter().any(|(s, _, _)| s == storage) {
+                continue;
+            }
+            let db_instance = self.db_instance(storage);
+            match db_instance.begin_ro_txn() {
+                Ok(txn) => session.txns.push((storage.clone(), db_instance, txn)),
+                Err(e) => {
+                    error!("MDBX: failed to open read transaction for session, path=[{}], err={:?}", db_instance.path, e);
+                    return StorageResult::Error(format!("Failed to open read transaction for {:?}: {:?}", storage, e));
+                },
+            }
+        }
+        StorageResult::Ok(f(&mut session))
+    }
+
+    fn db_instance(&self, storage: &StorageId) -> &MdbxInstance {
+        match storage {
+            StorageId::Individuals => &self.individuals_db,
+            StorageId::Tickets => &self.tickets_db,
+            StorageId::Az => &self.az_db,
         }
     }
 
@@ -391,29 +1079,41 @@ impl MDBXStorage {
 
 impl Storage for MDBXStorage {
     fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        if uri.is_empty() {
+            return empty_key_error();
+        }
+
         let db_instance = self.get_db_instance(&storage);
         db_instance.get_individual(uri, iraw)
     }
 
     fn get_value(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<String> {
-        let db_instance = self.get_db_instance(&storage);
-        match db_instance.get_v(key) {
-            Some(value) => crate::common::StorageResult::Ok(value),
-            None => crate::common::StorageResult::NotFound,
+        if key.is_empty() {
+            return empty_key_error();
         }
+
+        self.get_db_instance(&storage).get_v(key).into()
     }
 
     fn get_raw_value(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<Vec<u8>> {
-        let db_instance = self.get_db_instance(&storage);
-        match db_instance.get_raw(key) {
-            Some(value) => crate::common::StorageResult::Ok(value),
-            None => crate::common::StorageResult::NotFound,
+        if key.is_empty() {
+            return empty_key_error();
         }
+
+        self.get_db_instance(&storage).get_raw(key).into()
     }
 
     fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
         let db_instance = self.get_db_instance(&storage);
-        if put_kv_mdbx(&db_instance.db, key, val.as_bytes(), &db_instance.path) {
+        if db_instance.put(key, val.as_bytes()) {
             crate::common::StorageResult::Ok(())
         } else {
             crate::common::StorageResult::Error("Failed to put value".to_string())
@@ -421,17 +1121,37 @@ impl Storage for MDBXStorage {
     }
 
     fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> crate::common::StorageResult<()> {
-        let db_instance = self.get_db_instance(&storage);
-        if put_kv_mdbx(&db_instance.db, key, val.as_slice(), &db_instance.path) {
-            crate::common::StorageResult::Ok(())
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && std::str::from_utf8(&val).is_err() {
+            return crate::common::StorageResult::UnprocessableEntity;
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        if db_instance.put(key, val.as_slice()) {
+            crate::common::StorageResult::Ok(())
         } else {
             crate::common::StorageResult::Error("Failed to put raw value".to_string())
         }
     }
 
     fn remove_value(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
         let db_instance = self.get_db_instance(&storage);
-        if remove_from_mdbx(&db_instance.db, key, &db_instance.path) {
+        if db_instance.remove(key) {
             crate::common::StorageResult::Ok(())
         } else {
             crate::common::StorageResult::NotFound
@@ -442,17 +1162,258 @@ impl Storage for MDBXStorage {
         let db_instance = self.get_db_instance(&storage);
         crate::common::StorageResult::Ok(db_instance.count())
     }
+
+    fn stats(&mut self, storage: StorageId) -> crate::common::StorageResult<StorageStats> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.stats()
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    /// Each database is opened separately, so the transactions are started
+    /// one after another: a snapshot is consistent within every section only
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        let mut snapshot = MdbxSnapshot {
+            txns: Vec::with_capacity(3),
+            _dbs: Vec::with_capacity(3),
+        };
+        for storage in StorageId::all() {
+            let db_instance = self.db_instance(&storage);
+            let db = db_instance.db.clone();
+            match db.begin_ro_txn() {
+                Ok(txn) => {
+                    db_instance.txn_counters.record_read();
+                    // SAFETY: the transaction borrows the database behind the Arc, which the
+                    // snapshot holds in _dbs and drops only after all transactions
+                    let txn: Transaction<'static, RO, WriteMap> = unsafe { std::mem::transmute(txn) };
+                    snapshot.txns.push((storage, txn));
+                },
+                Err(e) => {
+                    error!("MDBX: failed to open read transaction for snapshot, path=[{}], err={:?}", db_instance.path, e);
+                    return StorageResult::Error(format!("Failed to open snapshot: {:?}", e));
+                },
+            }
+            snapshot._dbs.push(db);
+        }
+        StorageResult::Ok(Box::new(snapshot))
+    }
+
+    fn sync(&mut self, force: bool) -> crate::common::StorageResult<()> {
+        if self.flush(force) {
+            crate::common::StorageResult::Ok(())
+        } else {
+            crate::common::StorageResult::Error("Failed to sync MDBX".to_string())
+        }
+    }
+
+    fn health_check(&mut self) -> crate::common::StorageResult<()> {
+        for db_instance in [&self.individuals_db, &self.tickets_db, &self.az_db] {
+            let res = db_instance.health_check();
+            if !res.is_ok() {
+                return res;
+            }
+        }
+        crate::common::StorageResult::Ok(())
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> crate::common::StorageResult<bool> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.contains(key)
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> crate::common::StorageResult<Vec<bool>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.exists_many(keys)
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<crate::common::StorageResult<String>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.get_values_many(keys)
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, crate::common::StorageResult<Individual>)> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.get_individuals(ids)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> crate::common::StorageResult<Vec<String>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.keys_with_prefix(prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> crate::common::StorageResult<Vec<(String, Vec<u8>)>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.range(start, end)
+    }
+
+    fn scan(&mut self, storage: StorageId) -> crate::common::StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        let db_instance = self.get_db_instance(&storage);
+        crate::common::StorageResult::Ok(db_instance.scan())
+    }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> crate::common::StorageResult<HashMap<String, usize>> {
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.count_by_prefix(depth_separator)
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> crate::common::StorageResult<usize> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.remove_matching(pred)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> crate::common::StorageResult<usize> {
+        if self.read_only {
+            return read_only_error();
+        }
+        if prefix.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.remove_prefix(prefix)
+    }
+
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> crate::common::StorageResult<Option<Vec<u8>>> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.get_if(key, pred)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> crate::common::StorageResult<()> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.read_value(key, f)
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> crate::common::StorageResult<(i64, bool)> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.increment(key, delta).map(|value| (value, value >= limit))
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> crate::common::StorageResult<bool> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        db_instance.compare_and_swap(key, expected, new)
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if entries.iter().any(|(key, _)| key.is_empty()) {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && entries.iter().any(|(_, val)| std::str::from_utf8(val).is_err()) {
+            return crate::common::StorageResult::UnprocessableEntity;
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        if db_instance.replace_all(entries) {
+            crate::common::StorageResult::Ok(())
+        } else {
+            crate::common::StorageResult::Error("Failed to replace section".to_string())
+        }
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> crate::common::StorageResult<()> {
+        if self.read_only {
+            return read_only_error();
+        }
+
+        if entries.iter().any(|(key, _)| key.is_empty()) {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && entries.iter().any(|(_, val)| std::str::from_utf8(val).is_err()) {
+            return crate::common::StorageResult::UnprocessableEntity;
+        }
+
+        let db_instance = self.get_db_instance(&storage);
+        if db_instance.put_many(entries) {
+            crate::common::StorageResult::Ok(())
+        } else {
+            crate::common::StorageResult::Error("Failed to put values".to_string())
+        }
+    }
+
+    /// All pairs go through put_many, i.e. one write transaction
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> crate::common::StorageResult<()> {
+        let entries: Vec<(&str, &[u8])> = pairs.iter().map(|(key, val)| (*key, val.as_bytes())).collect();
+        self.put_many(storage, &entries)
+    }
+
+    /// Sections are read lazily, each in its own read transaction.
+    /// Error if a section can not be read when the walk starts; a section that
+    /// fails to load later ends the walk. Keys that are not valid UTF-8 are skipped
+    fn iter_all(&mut self) -> crate::common::StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        for db_instance in [&self.individuals_db, &self.tickets_db, &self.az_db] {
+            if !db_instance.health_check().is_ok() {
+                return crate::common::StorageResult::Error(format!("Failed to open read transaction, path=[{}]", db_instance.path));
+            }
+        }
+        let sections = vec![
+            (StorageId::Individuals, self.individuals_db.section_loader()),
+            (StorageId::Tickets, self.tickets_db.section_loader()),
+            (StorageId::Az, self.az_db.section_loader()),
+        ];
+        crate::common::StorageResult::Ok(Box::new(SectionChain::new(sections)))
+    }
 }
 
-fn remove_from_mdbx(db: &Arc<Database<WriteMap>>, key: &str, path: &str) -> bool {
+impl Drop for MDBXStorage {
+    fn drop(&mut self) {
+        if self.sync_on_close {
+            MDBXStorage::sync(self);
+        }
+    }
+}
+
+fn remove_from_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, key: &str, path: &str) -> bool {
     match db.begin_rw_txn() {
         Ok(txn) => {
+            let record = counters.begin_write();
             match txn.open_table(None) {
                 Ok(table) => {
                     match txn.del(&table, key.as_bytes(), None) {
                         Ok(true) => {
                             match txn.commit() {
-                                Ok(_) => true,
+                                Ok(_) => {
+                                    record.commit();
+                                    true
+                                },
                                 Err(e) => {
                                     error!("MDBX: failed to commit removal for key=[{}], path=[{}], err={:?}", key, path, e);
                                     false
@@ -482,15 +1443,209 @@ fn remove_from_mdbx(db: &Arc<Database<WriteMap>>, key: &str, path: &str) -> bool
     }
 }
 
-fn put_kv_mdbx(db: &Arc<Database<WriteMap>>, key: &str, val: &[u8], path: &str) -> bool {
+fn increment_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, key: &str, delta: i64, path: &str) -> StorageResult<i64> {
+    let res = (|| -> libmdbx::Result<Option<i64>> {
+        let txn = db.begin_rw_txn()?;
+        let record = counters.begin_write();
+        let table = txn.open_table(None)?;
+        let current = match txn.get::<Vec<u8>>(&table, key.as_bytes())? {
+            Some(val) => match i64::from_mdb_value(&val) {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+            None => 0,
+        };
+        let new_value = current.saturating_add(delta);
+        txn.put(&table, key.as_bytes(), new_value.to_le_bytes(), WriteFlags::empty())?;
+        txn.commit()?;
+        record.commit();
+        Ok(Some(new_value))
+    })();
+
+    match res {
+        Ok(Some(value)) => StorageResult::Ok(value),
+        Ok(None) => {
+            error!("MDBX: value is not an i64 counter, key=[{}], path=[{}]", key, path);
+            StorageResult::UnprocessableEntity
+        },
+        Err(e) => {
+            error!("MDBX: failed to increment key=[{}], path=[{}], err={:?}", key, path, e);
+            StorageResult::Error(format!("Failed to increment: {:?}", e))
+        }
+    }
+}
+
+fn compare_and_swap_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, key: &str, expected: Option<&[u8]>, new: &[u8], path: &str) -> StorageResult<bool> {
+    let res = (|| -> libmdbx::Result<bool> {
+        let txn = db.begin_rw_txn()?;
+        let record = counters.begin_write();
+        let table = txn.open_table(None)?;
+        if txn.get::<Vec<u8>>(&table, key.as_bytes())?.as_deref() != expected {
+            return Ok(false);
+        }
+        txn.put(&table, key.as_bytes(), new, WriteFlags::empty())?;
+        txn.commit()?;
+        record.commit();
+        Ok(true)
+    })();
+
+    match res {
+        Ok(swapped) => StorageResult::Ok(swapped),
+        Err(e) => {
+            error!("MDBX: failed to compare and swap key=[{}], path=[{}], err={:?}", key, path, e);
+            StorageResult::Error(format!("Failed to compare and swap: {:?}", e))
+        }
+    }
+}
+
+fn remove_matching_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, pred: &mut dyn FnMut(&[u8]) -> bool, path: &str) -> StorageResult<usize> {
+    let res = (|| -> libmdbx::Result<usize> {
+        let txn = db.begin_rw_txn()?;
+        let record = counters.begin_write();
+        let table = txn.open_table(None)?;
+        let mut removed = 0;
+        {
+            let mut cursor = txn.cursor(&table)?;
+            let mut entry = cursor.first::<(), Cow<[u8]>>()?;
+            while let Some(((), val)) = entry {
+                if pred(&val) {
+                    // After deletion the cursor is positioned so that next() yields the following entry
+                    cursor.del(WriteFlags::empty())?;
+                    removed += 1;
+                }
+                entry = cursor.next::<(), Cow<[u8]>>()?;
+            }
+        }
+        txn.commit()?;
+        record.commit();
+        Ok(removed)
+    })();
+
+    match res {
+        Ok(removed) => StorageResult::Ok(removed),
+        Err(e) => {
+            error!("MDBX: failed to remove matching entries, path=[{}], err={:?}", path, e);
+            StorageResult::Error(format!("Failed to remove matching entries: {:?}", e))
+        }
+    }
+}
+
+fn remove_prefix_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, prefix: &str, path: &str) -> StorageResult<usize> {
+    let res = (|| -> libmdbx::Result<usize> {
+        let txn = db.begin_rw_txn()?;
+        let record = counters.begin_write();
+        let table = txn.open_table(None)?;
+        let mut removed = 0;
+        {
+            let mut cursor = txn.cursor(&table)?;
+            let mut entry = cursor.set_range::<Cow<[u8]>, ()>(prefix.as_bytes())?;
+            while let Some((key, ())) = entry {
+                if !key.starts_with(prefix.as_bytes()) {
+                    break;
+                }
+                // After deletion the cursor is positioned so that next() yields the following entry
+                cursor.del(WriteFlags::empty())?;
+                removed += 1;
+                entry = cursor.next::<Cow<[u8]>, ()>()?;
+            }
+        }
+        txn.commit()?;
+        record.commit();
+        Ok(removed)
+    })();
+
+    match res {
+        Ok(removed) => StorageResult::Ok(removed),
+        Err(e) => {
+            error!("MDBX: failed to remove keys by prefix=[{}], path=[{}], err={:?}", prefix, path, e);
+            StorageResult::Error(format!("Failed to remove keys by prefix: {:?}", e))
+        }
+    }
+}
+
+fn replace_all_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, entries: &[(&str, &[u8])], path: &str) -> bool {
+    let res = (|| -> libmdbx::Result<()> {
+        let txn = db.begin_rw_txn()?;
+        let record = counters.begin_write();
+        let table = txn.open_table(None)?;
+        txn.clear_table(&table)?;
+        for (key, val) in entries {
+            txn.put(&table, key.as_bytes(), val, WriteFlags::empty())?;
+        }
+        txn.commit()?;
+        record.commit();
+        Ok(())
+    })();
+
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            error!("MDBX: failed to replace table contents, path=[{}], entries={}, err={:?}", path, entries.len(), e);
+            false
+        }
+    }
+}
+
+fn entries_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, path: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+    let res = (|| -> libmdbx::Result<Vec<(String, Vec<u8>)>> {
+        let txn = db.begin_ro_txn()?;
+        counters.record_read();
+        let table = txn.open_table(None)?;
+        let mut cursor = txn.cursor(&table)?;
+        let mut entries = Vec::new();
+        while let Some((key, val)) = cursor.next::<Vec<u8>, Vec<u8>>()? {
+            match String::from_utf8(key) {
+                Ok(key) => entries.push((key, val)),
+                Err(_) => warn!("MDBX: iter_all skips a key that is not valid UTF-8, path=[{}]", path),
+            }
+        }
+        Ok(entries)
+    })();
+
+    match res {
+        Ok(entries) => StorageResult::Ok(entries),
+        Err(e) => {
+            error!("MDBX: failed to read entries, path=[{}], err={:?}", path, e);
+            StorageResult::Error(format!("Failed to read entries: {:?}", e))
+        }
+    }
+}
+
+fn put_many_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, entries: &[(&str, &[u8])], path: &str) -> bool {
+    let res = (|| -> libmdbx::Result<()> {
+        let txn = db.begin_rw_txn()?;
+        let record = counters.begin_write();
+        let table = txn.open_table(None)?;
+        for (key, val) in entries {
+            txn.put(&table, key.as_bytes(), val, WriteFlags::empty())?;
+        }
+        txn.commit()?;
+        record.commit();
+        Ok(())
+    })();
+
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            error!("MDBX: failed to put entries, path=[{}], entries={}, err={:?}", path, entries.len(), e);
+            false
+        }
+    }
+}
+
+fn put_kv_mdbx(db: &Arc<MdbxDb>, counters: &TxnCounters, key: &str, val: &[u8], path: &str) -> bool {
     match db.begin_rw_txn() {
         Ok(txn) => {
+            let record = counters.begin_write();
             match txn.open_table(None) {
                 Ok(table) => {
                     match txn.put(&table, key.as_bytes(), val, WriteFlags::empty()) {
                         Ok(_) => {
                             match txn.commit() {
-                                Ok(_) => true,
+                                Ok(_) => {
+                                    record.commit();
+                                    true
+                                },
                                 Err(e) => {
                                     error!("MDBX: failed to commit put for key=[{}], path=[{}], err={:?}", key, path, e);
                                     false
@@ -661,4 +1816,297 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_mdbx_replace_section() {
+        let temp_dir = format!("/tmp/test-mdbx-replace-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+
+        assert!(storage.put_value(StorageId::Az, "old1", "v1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "old2", "v2").is_ok());
+
+        let entries: [(&str, &[u8]); 2] = [("new1", b"n1"), ("new2", b"n2")];
+        assert!(storage.replace_section(StorageId::Az, &entries).is_ok());
+
+        assert_eq!(storage.get_value(StorageId::Az, "old1"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Az, "old2"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Az, "new1"), StorageResult::Ok("n1".to_string()));
+        assert_eq!(storage.get_value(StorageId::Az, "new2"), StorageResult::Ok("n2".to_string()));
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(2));
+
+        // In strict mode a value that is not UTF-8 rejects the whole replacement
+        storage.set_strict_utf8(true);
+        let entries: [(&str, &[u8]); 2] = [("new3", b"n3"), ("bin", &[0xff])];
+        assert_eq!(storage.replace_section(StorageId::Az, &entries), StorageResult::UnprocessableEntity);
+        assert_eq!(storage.get_value(StorageId::Az, "new1"), StorageResult::Ok("n1".to_string()));
+        assert_eq!(storage.get_value(StorageId::Az, "new3"), StorageResult::NotFound);
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_snapshot_keeps_old_values() {
+        let temp_dir = format!("/tmp/test-mdbx-snapshot-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+
+        let mut snapshot = storage.snapshot().ok().unwrap();
+        assert!(storage.put_value(StorageId::Individuals, "a", "2").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t", "new").is_ok());
+
+        assert_eq!(snapshot.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(snapshot.get_value(StorageId::Tickets, "t"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("2".to_string()));
+
+        // Cleanup
+        drop(snapshot);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_increment_checked() {
+        let temp_dir = format!("/tmp/test-mdbx-increment-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 3, 5), StorageResult::Ok((3, false)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 1, 5), StorageResult::Ok((4, false)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 1, 5), StorageResult::Ok((5, true)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 1, 5), StorageResult::Ok((6, true)));
+
+        assert!(storage.put_value(StorageId::Tickets, "text", "abc").is_ok());
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "text", 1, 5), StorageResult::UnprocessableEntity);
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_read_only_mode() {
+        let temp_dir = format!("/tmp/test-mdbx-read-only-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert!(storage.close_and_sync());
+
+        let mut read_only = MDBXStorage::new(&temp_dir, StorageMode::ReadOnly, None);
+        assert_eq!(read_only.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(read_only.count(StorageId::Individuals), StorageResult::Ok(1));
+
+        let rejected = StorageResult::Error(crate::common::READ_ONLY_ERROR.to_string());
+        assert_eq!(read_only.put_value(StorageId::Individuals, "key", "changed"), rejected);
+        assert_eq!(read_only.put_raw_value(StorageId::Tickets, "key", b"raw".to_vec()), rejected);
+        assert_eq!(read_only.remove_value(StorageId::Individuals, "key"), rejected);
+        assert_eq!(read_only.put_many(StorageId::Individuals, &[("other", b"1")]), rejected);
+        assert!(read_only.increment(StorageId::Az, "counter", 1).is_error());
+        assert_eq!(read_only.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert!(read_only.sync());
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_close_and_sync() {
+        let temp_dir = format!("/tmp/test-mdbx-close-sync-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        storage.set_sync_on_close(true);
+        assert!(storage.put_value(StorageId::Individuals, "persisted", "value").is_ok());
+        assert!(storage.close_and_sync());
+
+        let mut reopened = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert_eq!(reopened.get_value(StorageId::Individuals, "persisted"), StorageResult::Ok("value".to_string()));
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_remove_matching() {
+        let temp_dir = format!("/tmp/test-mdbx-remove-matching-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+
+        // Adjacent matches check that the cursor does not skip entries after deletion
+        assert!(storage.put_value(StorageId::Tickets, "t1", "done:1").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t2", "done:2").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t3", "active:3").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t4", "done:4").is_ok());
+
+        let removed = storage.remove_matching(StorageId::Tickets, &mut |val| val.starts_with(b"done"));
+        assert_eq!(removed, StorageResult::Ok(3));
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(1));
+        assert_eq!(storage.get_value(StorageId::Tickets, "t3"), StorageResult::Ok("active:3".to_string()));
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_remove_prefix() {
+        let temp_dir = format!("/tmp/test-mdbx-remove-prefix-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+
+        assert!(storage.put_value(StorageId::Az, "a:1", "1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "a:2", "2").is_ok());
+        assert!(storage.put_value(StorageId::Az, "b:1", "3").is_ok());
+
+        assert_eq!(storage.remove_prefix(StorageId::Az, "a:"), StorageResult::Ok(2));
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(1));
+        assert_eq!(storage.get_value(StorageId::Az, "b:1"), StorageResult::Ok("3".to_string()));
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_mdbx_put_many_single_transaction() {
+        let temp_dir = format!("/tmp/test-mdbx-put-many-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        let before = storage.txn_stats();
+
+        let keys: Vec<String> = (0..10).map(|i| format!("key{}", i)).collect();
+        let entries: Vec<(&str, &[u8])> = keys.iter().map(|k| (k.as_str(), k.as_bytes())).collect();
+        assert!(storage.put_many(StorageId::Az, &entries).is_ok());
+
+        let after = storage.txn_stats();
+        assert_eq!(after.writes - before.writes, 1);
+        assert_eq!(after.committed - before.committed, 1);
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(10));
+
+        // Cleanup
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_put_values_many_rolls_back_on_failure() {
+        let temp_dir = format!("/tmp/test-mdbx-put-values-many-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        let before = storage.txn_stats();
+
+        // The oversized key fails inside the write transaction, after "a" was already put
+        let long_key = "k".repeat(4096);
+        let res = storage.put_values_many(StorageId::Individuals, &[("a", "1"), (long_key.as_str(), "2"), ("b", "3")]);
+        assert!(res.is_error());
+
+        let after = storage.txn_stats();
+        assert_eq!(after.committed, before.committed);
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::NotFound);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+        assert!(storage.put_values_many(StorageId::Individuals, &[("a", "1"), ("b", "3")]).is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "b"), StorageResult::Ok("3".to_string()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_max_read_counter_reopens_database() {
+        let temp_dir = format!("/tmp/test-mdbx-read-counter-{}", std::process::id());
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, Some(3));
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+
+        for _ in 0..3 {
+            assert!(storage.get_value(StorageId::Individuals, "key").is_ok());
+        }
+        assert_eq!(storage.individuals_db.reopen_count(), 0);
+
+        // The fourth read exceeds the limit and reopens the database
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(storage.individuals_db.read_counter(), 0);
+        assert_eq!(storage.individuals_db.reopen_count(), 1);
+        assert_eq!(storage.az_db.reopen_count(), 0);
+
+        // While a snapshot holds the database it is not closed
+        let mut snapshot = storage.snapshot().unwrap();
+        for _ in 0..4 {
+            assert!(storage.get_value(StorageId::Individuals, "key").is_ok());
+        }
+        assert_eq!(storage.individuals_db.read_counter(), 0);
+        assert_eq!(storage.individuals_db.reopen_count(), 1);
+        assert_eq!(snapshot.get_raw_value(StorageId::Individuals, "key"), StorageResult::Ok(b"value".to_vec()));
+        drop(snapshot);
+
+        for _ in 0..4 {
+            assert!(storage.get_value(StorageId::Individuals, "key").is_ok());
+        }
+        assert_eq!(storage.individuals_db.reopen_count(), 2);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_compare_and_swap_concurrent() {
+        let temp_dir = format!("/tmp/test-mdbx-cas-{}", std::process::id());
+        let threads = 4;
+        let increments = 50;
+
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "counter", None, b"0"), StorageResult::Ok(true));
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "counter", None, b"0"), StorageResult::Ok(false));
+
+        // Every thread increments the counter through a read + compare_and_swap retry loop;
+        // lost updates would leave the counter short
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let temp_dir = temp_dir.clone();
+                std::thread::spawn(move || {
+                    let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+                    for _ in 0..increments {
+                        loop {
+                            let current = storage.get_value(StorageId::Az, "counter").unwrap_or_default();
+                            let next = (current.parse::<u64>().unwrap() + 1).to_string();
+                            if storage.compare_and_swap(StorageId::Az, "counter", Some(current.as_bytes()), next.as_bytes()) == StorageResult::Ok(true) {
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(storage.get_value(StorageId::Az, "counter"), StorageResult::Ok((threads * increments).to_string()));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_prefix_scans_skip_non_utf8_keys() {
+        let temp_dir = format!("/tmp/test-mdbx-prefix-utf8-{}", std::process::id());
+        let mut instance = MdbxInstance::new(&temp_dir, StorageMode::ReadWrite);
+        assert!(instance.put("ns:a", b"1"));
+
+        // A key written by another tool, not valid UTF-8
+        let txn = instance.db.begin_rw_txn().unwrap();
+        let table = txn.open_table(None).unwrap();
+        txn.put(&table, b"ns:\xff", b"2", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(instance.keys_with_prefix("ns:"), StorageResult::Ok(vec!["ns:a".to_string()]));
+        assert_eq!(instance.count_by_prefix(':'), StorageResult::Ok(HashMap::from([("ns".to_string(), 1)])));
+        assert_eq!(instance.range("ns:", "ns;"), StorageResult::Ok(vec![("ns:a".to_string(), b"1".to_vec())]));
+
+        drop(instance);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_poisoned_registry_is_recovered() {
+        let temp_dir = format!("/tmp/test-mdbx-poisoned-registry-{}", std::process::id());
+
+        // A thread that panics while holding the registry lock poisons it
+        let _ = std::thread::spawn(|| {
+            let _map = GLOBAL_DBS.get_or_init(|| Mutex::new(HashMap::new())).lock();
+            panic!("poison the registry");
+        })
+        .join();
+
+        let mut storage = MDBXStorage::new(&temp_dir, StorageMode::ReadWrite, None);
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -2,14 +2,141 @@
 
 use v_individual_model::onto::individual::Individual;
 use v_individual_model::onto::parser::parse_raw;
-use crate::common::{Storage, StorageId, StorageResult};
-use std::collections::HashMap;
-use std::sync::RwLock;
+use crate::common::{add_prefix_count, empty_key_error, lock_poisoned_error, ScanEntry, SectionEntry, SnapshotReader, Storage, StorageId, StorageResult, StorageStats};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Заголовок файла сохранения MemoryStorage
+const PERSIST_MAGIC: &[u8; 4] = b"VMS1";
+
+/// Оценка накладных расходов на запись: заголовки String и Vec в ячейке
+/// HashMap и управляющий байт таблицы
+const ENTRY_OVERHEAD: usize = std::mem::size_of::<String>() + std::mem::size_of::<Vec<u8>>() + 1;
+
+/// Флаг в байте секции: после значения записан срок истечения ключа
+const PERSIST_TTL_FLAG: u8 = 0x80;
+
+/// Запись файла сохранения: секция, ключ, значение, срок истечения и длина записи в байтах
+type PersistedRecord = (StorageId, String, Vec<u8>, Option<SystemTime>, u64);
+
+/// Результат загрузки MemoryStorage из файла
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Число загруженных записей; истекшие к моменту загрузки не учитываются
+    pub recovered: usize,
+    /// Смещение (в байтах от начала файла) первой поврежденной записи
+    pub corrupt_at: Option<u64>,
+}
+
+/// Порядок обращений к ключам секции: вытесняется ключ с самым старым обращением
+#[derive(Default, Clone)]
+struct AccessOrder {
+    tick: u64,
+    ticks: HashMap<String, u64>,
+    keys: BTreeMap<u64, String>,
+}
+
+impl AccessOrder {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        if let Some(old) = self.ticks.insert(key.to_string(), self.tick) {
+            self.keys.remove(&old);
+        }
+        self.keys.insert(self.tick, key.to_string());
+    }
+
+    fn forget(&mut self, key: &str) {
+        if let Some(tick) = self.ticks.remove(key) {
+            self.keys.remove(&tick);
+        }
+    }
+
+    fn pop_oldest(&mut self) -> Option<String> {
+        let (_, key) = self.keys.pop_first()?;
+        self.ticks.remove(&key);
+        Some(key)
+    }
+}
+
+/// Лимит записей в секции и порядок обращений к ключам каждой секции
+struct LruState {
+    capacity: usize,
+    access: [Mutex<AccessOrder>; 3],
+}
 
 pub struct MemoryStorage {
     individuals: RwLock<HashMap<String, Vec<u8>>>,
     tickets: RwLock<HashMap<String, Vec<u8>>>,
     az: RwLock<HashMap<String, Vec<u8>>>,
+    strict_utf8: bool,
+    /// None - без ограничения числа записей
+    lru: Option<Box<LruState>>,
+    /// Время истечения ключей, записанных через put_value_with_ttl, по секциям
+    expiry: [Mutex<HashMap<String, Instant>>; 3],
+    now: fn() -> Instant,
+    /// Именованные коллекции (см. Storage::get_value_ns), создаются при первой записи.
+    /// Не подпадают под лимит записей и TTL и не сохраняются в файл
+    namespaces: RwLock<HashMap<String, HashMap<String, Vec<u8>>>>,
+}
+
+/// Снимок MemoryStorage: копии секций на момент создания
+struct MemorySnapshot {
+    sections: [HashMap<String, Vec<u8>>; 3],
+}
+
+impl SnapshotReader for MemorySnapshot {
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        match self.sections[MemoryStorage::section_tag(&storage) as usize].get(key) {
+            Some(val) => StorageResult::Ok(val.clone()),
+            None => StorageResult::NotFound,
+        }
+    }
+}
+
+impl Clone for LruState {
+    fn clone(&self) -> Self {
+        LruState {
+            capacity: self.capacity,
+            access: std::array::from_fn(|i| Mutex::new(self.access[i].lock().unwrap_or_else(|e| e.into_inner()).clone())),
+        }
+    }
+}
+
+/// Глубокая копия: содержимое секций копируется под блокировкой чтения
+/// в новые RwLock, дальнейшие изменения копий независимы
+impl Clone for MemoryStorage {
+    fn clone(&self) -> Self {
+        let copy = |map: &RwLock<HashMap<String, Vec<u8>>>| RwLock::new(map.read().unwrap_or_else(|e| e.into_inner()).clone());
+        MemoryStorage {
+            individuals: copy(&self.individuals),
+            tickets: copy(&self.tickets),
+            az: copy(&self.az),
+            strict_utf8: self.strict_utf8,
+            lru: self.lru.clone(),
+            expiry: std::array::from_fn(|i| Mutex::new(self.expiry[i].lock().unwrap_or_else(|e| e.into_inner()).clone())),
+            now: self.now,
+            namespaces: RwLock::new(self.namespaces.read().unwrap_or_else(|e| e.into_inner()).clone()),
+        }
+    }
+}
+
+/// Печатает только число записей в секциях (под блокировкой чтения), без значений
+impl std::fmt::Debug for MemoryStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = |map: &RwLock<HashMap<String, Vec<u8>>>| map.read().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("MemoryStorage")
+            .field("individuals", &len(&self.individuals))
+            .field("tickets", &len(&self.tickets))
+            .field("az", &len(&self.az))
+            .field("strict_utf8", &self.strict_utf8)
+            .field("capacity", &self.lru.as_ref().map(|lru| lru.capacity))
+            .field("namespaces", &self.namespaces.read().unwrap_or_else(|e| e.into_inner()).len())
+            .finish()
+    }
 }
 
 impl Default for MemoryStorage {
@@ -24,6 +151,73 @@ impl MemoryStorage {
             individuals: RwLock::new(HashMap::new()),
             tickets: RwLock::new(HashMap::new()),
             az: RwLock::new(HashMap::new()),
+            strict_utf8: false,
+            lru: None,
+            expiry: Default::default(),
+            now: Instant::now,
+            namespaces: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Хранилище с лимитом записей в каждой секции: при превышении лимита
+    /// вытесняется ключ, к которому дольше всего не обращались (чтение или запись)
+    pub fn with_capacity(max_entries_per_storage: usize) -> Self {
+        MemoryStorage {
+            lru: Some(Box::new(LruState {
+                capacity: max_entries_per_storage,
+                access: Default::default(),
+            })),
+            ..Self::new()
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.lru.as_ref().map(|lru| lru.capacity)
+    }
+
+    /// Примерный объем памяти под данные всех секций и именованных коллекций в байтах:
+    /// длины ключей и значений плюс ENTRY_OVERHEAD на запись. Свободная емкость
+    /// таблиц и буферов, а также служебные структуры LRU и TTL не учитываются
+    pub fn memory_usage(&self) -> usize {
+        let usage = |map: &HashMap<String, Vec<u8>>| map.iter().map(|(key, val)| key.len() + val.len() + ENTRY_OVERHEAD).sum::<usize>();
+        let sections = [&self.individuals, &self.tickets, &self.az]
+            .iter()
+            .map(|section| usage(&section.read().unwrap_or_else(|e| e.into_inner())))
+            .sum::<usize>();
+        let namespaces = self.namespaces.read().unwrap_or_else(|e| e.into_inner()).values().map(usage).sum::<usize>();
+        sections + namespaces
+    }
+
+    /// Строгий режим: put_raw_value и replace_section отклоняют значения не в UTF-8 (UnprocessableEntity)
+    pub fn set_strict_utf8(&mut self, enabled: bool) {
+        self.strict_utf8 = enabled;
+    }
+
+    /// Источник текущего времени для TTL (по умолчанию Instant::now)
+    pub fn set_clock(&mut self, now: fn() -> Instant) {
+        self.now = now;
+    }
+
+    /// Записывает значение, которое перестает быть видимым через ttl.
+    /// Истекшие ключи удаляются при обращении к ним; повторная запись
+    /// через put_value снимает ограничение. ttl, который не помещается
+    /// в Instant (например Duration::MAX), означает "не истекает никогда"
+    pub fn put_value_with_ttl(&mut self, storage: StorageId, key: &str, val: &str, ttl: Duration) -> StorageResult<()> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            map.insert(key.to_string(), val.as_bytes().to_vec());
+            match (self.now)().checked_add(ttl) {
+                Some(at) => expiry.insert(key.to_string(), at),
+                None => expiry.remove(key),
+            };
+            self.track_insert(&storage, &mut map, &mut expiry, key);
+            StorageResult::Ok(())
+        } else {
+            lock_poisoned_error()
         }
     }
 
@@ -35,6 +229,233 @@ impl MemoryStorage {
         }
     }
 
+    fn access_order(&self, storage: &StorageId) -> Option<MutexGuard<'_, AccessOrder>> {
+        self.lru.as_ref()?.access[Self::section_tag(storage) as usize].lock().ok()
+    }
+
+    /// Отмечает обращение к ключу, если включен лимит
+    fn touch(&self, storage: &StorageId, key: &str) {
+        if let Some(mut order) = self.access_order(storage) {
+            order.touch(key);
+        }
+    }
+
+    /// Отмечает запись ключа и вытесняет давно не использованные ключи сверх лимита
+    /// вместе с их TTL
+    fn track_insert(&self, storage: &StorageId, map: &mut HashMap<String, Vec<u8>>, expiry: &mut HashMap<String, Instant>, key: &str) {
+        if let (Some(capacity), Some(mut order)) = (self.capacity(), self.access_order(storage)) {
+            order.touch(key);
+            while map.len() > capacity {
+                match order.pop_oldest() {
+                    Some(oldest) => {
+                        map.remove(&oldest);
+                        expiry.remove(&oldest);
+                    },
+                    None => break,
+                }
+            }
+        }
+    }
+
+    fn forget(&self, storage: &StorageId, key: &str) {
+        if let Some(mut order) = self.access_order(storage) {
+            order.forget(key);
+        }
+    }
+
+    // Блокировка сроков всегда берется раньше блокировки секции
+    fn expiry(&self, storage: &StorageId) -> MutexGuard<'_, HashMap<String, Instant>> {
+        self.expiry[Self::section_tag(storage) as usize].lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Снимает TTL с ключа (ключ перезаписан или удален)
+    fn clear_ttl(&self, storage: &StorageId, key: &str) {
+        let mut expiry = self.expiry(storage);
+        if !expiry.is_empty() {
+            expiry.remove(key);
+        }
+    }
+
+    /// Удаляет ключ, если его TTL истек
+    fn expire_key(&self, storage: &StorageId, key: &str) {
+        let mut expiry = self.expiry(storage);
+        if expiry.get(key).is_some_and(|at| *at <= (self.now)()) {
+            expiry.remove(key);
+            if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+                map.remove(key);
+            }
+            self.forget(storage, key);
+        }
+    }
+
+    /// Удаляет все ключи секции с истекшим TTL
+    fn expire_section(&self, storage: &StorageId) {
+        let mut expiry = self.expiry(storage);
+        if expiry.is_empty() {
+            return;
+        }
+
+        let now = (self.now)();
+        let expired: Vec<String> = expiry.iter().filter(|(_, at)| **at <= now).map(|(key, _)| key.clone()).collect();
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            for key in expired {
+                expiry.remove(&key);
+                map.remove(&key);
+                self.forget(storage, &key);
+            }
+        }
+    }
+
+    fn section_tag(storage: &StorageId) -> u8 {
+        match storage {
+            StorageId::Individuals => 0,
+            StorageId::Tickets => 1,
+            StorageId::Az => 2,
+        }
+    }
+
+    fn section_by_tag(tag: u8) -> Option<StorageId> {
+        match tag {
+            0 => Some(StorageId::Individuals),
+            1 => Some(StorageId::Tickets),
+            2 => Some(StorageId::Az),
+            _ => None,
+        }
+    }
+
+    /// Сохраняет все секции в файл.
+    /// Формат: заголовок VMS1, затем записи [секция u8][длина ключа u32 LE][ключ][длина значения u32 LE][значение].
+    /// У ключей с TTL в байте секции выставлен PERSIST_TTL_FLAG, а после значения записан
+    /// срок истечения [мс от Unix epoch u64 LE]: после загрузки такой ключ истекает в то же время.
+    /// Запись идет во временный файл, который затем переименовывается.
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp_path = path.with_extension("tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            writer.write_all(PERSIST_MAGIC)?;
+            for storage in StorageId::all() {
+                self.expire_section(&storage);
+                let tag = Self::section_tag(&storage);
+                let expiry = self.expiry(&storage);
+                let map = self.get_storage(storage).read().map_err(|_| io::Error::other("storage lock poisoned"))?;
+                let (now, wall_now) = ((self.now)(), SystemTime::now());
+                for (key, val) in map.iter() {
+                    let expires = expiry.get(key).map(|at| wall_now + at.saturating_duration_since(now));
+                    writer.write_all(&[if expires.is_some() { tag | PERSIST_TTL_FLAG } else { tag }])?;
+                    writer.write_all(&(key.len() as u32).to_le_bytes())?;
+                    writer.write_all(key.as_bytes())?;
+                    writer.write_all(&(val.len() as u32).to_le_bytes())?;
+                    writer.write_all(val)?;
+                    if let Some(expires) = expires {
+                        let ms = expires.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64);
+                        writer.write_all(&ms.to_le_bytes())?;
+                    }
+                }
+            }
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)
+    }
+
+    /// Загружает хранилище из файла, восстанавливая все корректные записи
+    /// до первой поврежденной. Место повреждения возвращается в LoadReport.
+    /// Ключи с TTL получают сохраненный срок; истекшие к моменту загрузки пропускаются.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> io::Result<(MemoryStorage, LoadReport)> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PERSIST_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a MemoryStorage file"));
+        }
+
+        let storage = MemoryStorage::new();
+        let mut offset = PERSIST_MAGIC.len() as u64;
+        let mut recovered = 0;
+
+        loop {
+            match Self::read_record(&mut reader) {
+                Ok(None) => break,
+                Ok(Some((section, key, val, expires, len))) => {
+                    let ttl = expires.map(|at| at.duration_since(SystemTime::now()).unwrap_or_default());
+                    if ttl != Some(Duration::ZERO) {
+                        let mut expiry = storage.expiry(&section);
+                        if let Ok(mut map) = storage.get_storage(section.clone()).write() {
+                            if let Some(at) = ttl.and_then(|ttl| (storage.now)().checked_add(ttl)) {
+                                expiry.insert(key.clone(), at);
+                            }
+                            map.insert(key, val);
+                            recovered += 1;
+                        }
+                    }
+                    offset += len;
+                },
+                Err(e) => {
+                    warn!("MemoryStorage: corrupt record at offset {}, recovered {} records, err={:?}", offset, recovered, e);
+                    return Ok((storage, LoadReport { recovered, corrupt_at: Some(offset) }));
+                },
+            }
+        }
+
+        Ok((storage, LoadReport { recovered, corrupt_at: None }))
+    }
+
+    /// Загружает хранилище из файла, возвращая ошибку при любом повреждении
+    pub fn load_from_strict<P: AsRef<Path>>(path: P) -> io::Result<MemoryStorage> {
+        let (storage, report) = Self::load_from(path)?;
+        match report.corrupt_at {
+            None => Ok(storage),
+            Some(offset) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("corrupt record at offset {}", offset))),
+        }
+    }
+
+    /// Снимок всех секций в файл, см. save_to
+    pub fn save_snapshot(&self, path: &Path) -> io::Result<()> {
+        self.save_to(path)
+    }
+
+    /// Хранилище из снимка, сделанного save_snapshot; поврежденный файл - ошибка, см. load_from_strict
+    pub fn load_snapshot(path: &Path) -> io::Result<MemoryStorage> {
+        Self::load_from_strict(path)
+    }
+
+    // Возвращает None при корректном конце файла
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Option<PersistedRecord>> {
+        let mut tag = [0u8; 1];
+        if reader.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        let section = Self::section_by_tag(tag[0] & !PERSIST_TTL_FLAG).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unknown section"))?;
+
+        let key = Self::read_chunk(reader)?;
+        let key = String::from_utf8(key).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "key is not UTF-8"))?;
+        let val = Self::read_chunk(reader)?;
+
+        let mut len = 1 + 4 + key.len() as u64 + 4 + val.len() as u64;
+        let mut expires = None;
+        if tag[0] & PERSIST_TTL_FLAG != 0 {
+            let mut ms = [0u8; 8];
+            reader.read_exact(&mut ms)?;
+            expires = Some(UNIX_EPOCH + Duration::from_millis(u64::from_le_bytes(ms)));
+            len += 8;
+        }
+        Ok(Some((section, key, val, expires, len)))
+    }
+
+    fn read_chunk<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        let mut buf = Vec::new();
+        reader.take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated record"));
+        }
+        Ok(buf)
+    }
+
     #[cfg(test)]
     pub fn insert_test_data(&self, storage: StorageId, key: &str, val: Vec<u8>) {
         if let Ok(mut map) = self.get_storage(storage).write() {
@@ -54,8 +475,17 @@ impl MemoryStorage {
 
 impl Storage for MemoryStorage {
     fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
-        let storage_map = self.get_storage(storage);
-        if let Some(data) = storage_map.read().unwrap().get(uri) {
+        if uri.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, uri);
+        let map = match self.get_storage(storage.clone()).read() {
+            Ok(map) => map,
+            Err(_) => return lock_poisoned_error(),
+        };
+        if let Some(data) = map.get(uri) {
+            self.touch(&storage, uri);
             iraw.set_raw(data);
             if parse_raw(iraw).is_ok() {
                 return StorageResult::Ok(());
@@ -67,66 +497,506 @@ impl Storage for MemoryStorage {
     }
 
     fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
-        if let Ok(map) = self.get_storage(storage).read() {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, key);
+        if let Ok(map) = self.get_storage(storage.clone()).read() {
             match map.get(key) {
-                Some(val) => match String::from_utf8(val.clone()) {
-                    Ok(string_val) => StorageResult::Ok(string_val),
-                    Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+                Some(val) => {
+                    self.touch(&storage, key);
+                    match String::from_utf8(val.clone()) {
+                        Ok(string_val) => StorageResult::Ok(string_val),
+                        Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+                    }
                 },
                 None => StorageResult::NotFound,
             }
         } else {
-            StorageResult::NotReady
+            lock_poisoned_error()
         }
     }
 
     fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
-        if let Ok(map) = self.get_storage(storage).read() {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, key);
+        if let Ok(map) = self.get_storage(storage.clone()).read() {
             match map.get(key) {
-                Some(val) => StorageResult::Ok(val.clone()),
+                Some(val) => {
+                    self.touch(&storage, key);
+                    StorageResult::Ok(val.clone())
+                },
                 None => StorageResult::NotFound,
             }
         } else {
-            StorageResult::NotReady
+            lock_poisoned_error()
         }
     }
 
     fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
-        if let Ok(mut map) = self.get_storage(storage).write() {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        let mut expiry = self.expiry(&storage);
+        expiry.remove(key);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
             map.insert(key.to_string(), val.as_bytes().to_vec());
+            self.track_insert(&storage, &mut map, &mut expiry, key);
             StorageResult::Ok(())
         } else {
-            StorageResult::NotReady
+            lock_poisoned_error()
         }
     }
 
     fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
-        if let Ok(mut map) = self.get_storage(storage).write() {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && std::str::from_utf8(&val).is_err() {
+            return StorageResult::UnprocessableEntity;
+        }
+
+        let mut expiry = self.expiry(&storage);
+        expiry.remove(key);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
             map.insert(key.to_string(), val);
+            self.track_insert(&storage, &mut map, &mut expiry, key);
             StorageResult::Ok(())
         } else {
-            StorageResult::NotReady
+            lock_poisoned_error()
+        }
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        if pairs.iter().any(|(key, _)| key.is_empty()) {
+            return empty_key_error();
+        }
+
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            for (key, val) in pairs {
+                expiry.remove(*key);
+                map.insert(key.to_string(), val.as_bytes().to_vec());
+                self.track_insert(&storage, &mut map, &mut expiry, key);
+            }
+            StorageResult::Ok(())
+        } else {
+            lock_poisoned_error()
         }
     }
 
     fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
-        if let Ok(mut map) = self.get_storage(storage).write() {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, key);
+        self.clear_ttl(&storage, key);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            self.forget(&storage, key);
             match map.remove(key) {
                 Some(_) => StorageResult::Ok(()),
                 None => StorageResult::NotFound,
             }
         } else {
-            StorageResult::NotReady
+            lock_poisoned_error()
+        }
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        self.expire_section(&storage);
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            let before = map.len();
+            let mut order = self.access_order(&storage);
+            map.retain(|key, val| {
+                let keep = !pred(val);
+                if !keep {
+                    expiry.remove(key);
+                    if let Some(order) = order.as_mut() {
+                        order.forget(key);
+                    }
+                }
+                keep
+            });
+            StorageResult::Ok(before - map.len())
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        if prefix.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_section(&storage);
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            let before = map.len();
+            let mut order = self.access_order(&storage);
+            map.retain(|key, _| {
+                let keep = !key.starts_with(prefix);
+                if !keep {
+                    expiry.remove(key);
+                    if let Some(order) = order.as_mut() {
+                        order.forget(key);
+                    }
+                }
+                keep
+            });
+            StorageResult::Ok(before - map.len())
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        // Счетчик сохраняет свой TTL, истекший счетчик начинается заново
+        self.expire_key(&storage, key);
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            if !map.contains_key(key) {
+                expiry.remove(key);
+            }
+            let current = match map.get(key) {
+                Some(val) => match <[u8; 8]>::try_from(val.as_slice()) {
+                    Ok(arr) => i64::from_le_bytes(arr),
+                    Err(_) => return StorageResult::UnprocessableEntity,
+                },
+                None => 0,
+            };
+            let new_value = current.saturating_add(delta);
+            map.insert(key.to_string(), new_value.to_le_bytes().to_vec());
+            self.track_insert(&storage, &mut map, &mut expiry, key);
+            StorageResult::Ok((new_value, new_value >= limit))
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, key);
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            if map.get(key).map(Vec::as_slice) != expected {
+                return StorageResult::Ok(false);
+            }
+            expiry.remove(key);
+            map.insert(key.to_string(), new.to_vec());
+            self.track_insert(&storage, &mut map, &mut expiry, key);
+            StorageResult::Ok(true)
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        if entries.iter().any(|(key, _)| key.is_empty()) {
+            return empty_key_error();
+        }
+
+        if self.strict_utf8 && entries.iter().any(|(_, val)| std::str::from_utf8(val).is_err()) {
+            return StorageResult::UnprocessableEntity;
+        }
+
+        let new_map: HashMap<String, Vec<u8>> = entries.iter().map(|(k, v)| (k.to_string(), v.to_vec())).collect();
+        let mut expiry = self.expiry(&storage);
+        if let Ok(mut map) = self.get_storage(storage.clone()).write() {
+            *map = new_map;
+            expiry.clear();
+            if let Some(mut order) = self.access_order(&storage) {
+                *order = AccessOrder::default();
+            }
+            for (key, _) in entries {
+                self.track_insert(&storage, &mut map, &mut expiry, key);
+            }
+            StorageResult::Ok(())
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    /// size_bytes - сумма длин ключей и значений, без накладных расходов HashMap
+    fn stats(&mut self, storage: StorageId) -> StorageResult<StorageStats> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage).read() {
+            StorageResult::Ok(StorageStats {
+                entries: map.len(),
+                size_bytes: map.iter().map(|(key, val)| (key.len() + val.len()) as u64).sum(),
+                ..StorageStats::default()
+            })
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    /// f вызывается под блокировкой чтения секции, значение не копируется
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, key);
+        if let Ok(map) = self.get_storage(storage.clone()).read() {
+            match map.get(key) {
+                Some(val) => {
+                    self.touch(&storage, key);
+                    f(val);
+                    StorageResult::Ok(())
+                },
+                None => StorageResult::NotFound,
+            }
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    /// Данные не сохраняются на диск, синхронизировать нечего
+    fn sync(&mut self, _force: bool) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.get_value(storage, key);
+        }
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        if let Ok(namespaces) = self.namespaces.read() {
+            match namespaces.get(namespace).and_then(|map| map.get(key)) {
+                Some(val) => match String::from_utf8(val.clone()) {
+                    Ok(string_val) => StorageResult::Ok(string_val),
+                    Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+                },
+                None => StorageResult::NotFound,
+            }
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.put_value(storage, key, val);
+        }
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        if let Ok(mut namespaces) = self.namespaces.write() {
+            namespaces.entry(namespace.to_string()).or_default().insert(key.to_string(), val.as_bytes().to_vec());
+            StorageResult::Ok(())
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.remove_value(storage, key);
+        }
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        if let Ok(mut namespaces) = self.namespaces.write() {
+            match namespaces.get_mut(namespace).and_then(|map| map.remove(key)) {
+                Some(_) => StorageResult::Ok(()),
+                None => StorageResult::NotFound,
+            }
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        if let Ok(storage) = namespace.parse::<StorageId>() {
+            return self.count(storage);
+        }
+
+        if let Ok(namespaces) = self.namespaces.read() {
+            StorageResult::Ok(namespaces.get(namespace).map_or(0, |map| map.len()))
+        } else {
+            lock_poisoned_error()
         }
     }
 
     fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        self.expire_section(&storage);
         if let Ok(map) = self.get_storage(storage).read() {
             StorageResult::Ok(map.len())
         } else {
-            StorageResult::NotReady
+            lock_poisoned_error()
+        }
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        if key.is_empty() {
+            return empty_key_error();
+        }
+
+        self.expire_key(&storage, key);
+        if let Ok(map) = self.get_storage(storage).read() {
+            StorageResult::Ok(map.contains_key(key))
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage.clone()).read() {
+            ids.iter()
+                .map(|id| {
+                    let res = if id.is_empty() {
+                        empty_key_error()
+                    } else {
+                        match map.get(*id) {
+                            Some(data) => {
+                                self.touch(&storage, id);
+                                let mut individual = Individual::default();
+                                individual.set_raw(data);
+                                if parse_raw(&mut individual).is_ok() {
+                                    StorageResult::Ok(individual)
+                                } else {
+                                    StorageResult::UnprocessableEntity
+                                }
+                            },
+                            None => StorageResult::NotFound,
+                        }
+                    };
+                    (id.to_string(), res)
+                })
+                .collect()
+        } else {
+            ids.iter().map(|id| (id.to_string(), lock_poisoned_error())).collect()
+        }
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage).read() {
+            StorageResult::Ok(keys.iter().map(|key| map.contains_key(*key)).collect())
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage.clone()).read() {
+            keys.iter()
+                .map(|key| {
+                    if key.is_empty() {
+                        return empty_key_error();
+                    }
+                    match map.get(*key) {
+                        Some(val) => {
+                            self.touch(&storage, key);
+                            match std::str::from_utf8(val) {
+                                Ok(string_val) => StorageResult::Ok(string_val.to_string()),
+                                Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+                            }
+                        },
+                        None => StorageResult::NotFound,
+                    }
+                })
+                .collect()
+        } else {
+            keys.iter().map(|_| lock_poisoned_error()).collect()
+        }
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage).read() {
+            let mut keys: Vec<String> = map.keys().filter(|key| key.starts_with(prefix)).cloned().collect();
+            keys.sort();
+            StorageResult::Ok(keys)
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage).read() {
+            let mut entries: Vec<(String, Vec<u8>)> =
+                map.iter().filter(|(key, _)| key.as_str() >= start && key.as_str() < end).map(|(key, val)| (key.clone(), val.clone())).collect();
+            entries.sort();
+            StorageResult::Ok(entries)
+        } else {
+            lock_poisoned_error()
         }
     }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage).read() {
+            let mut counts = HashMap::new();
+            for key in map.keys() {
+                add_prefix_count(&mut counts, key, depth_separator);
+            }
+            StorageResult::Ok(counts)
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    /// Копия пар секции на момент вызова
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.expire_section(&storage);
+        if let Ok(map) = self.get_storage(storage).read() {
+            let pairs: Vec<ScanEntry> = map.iter().map(|(key, val)| (key.as_bytes().to_vec(), val.clone())).collect();
+            StorageResult::Ok(Box::new(pairs.into_iter()))
+        } else {
+            lock_poisoned_error()
+        }
+    }
+
+    /// Возвращает снимок всех секций на момент вызова
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        let mut entries = Vec::new();
+        for storage in StorageId::all() {
+            self.expire_section(&storage);
+            if let Ok(map) = self.get_storage(storage.clone()).read() {
+                entries.extend(map.iter().map(|(key, val)| (storage.clone(), key.clone(), val.clone())));
+            } else {
+                return lock_poisoned_error();
+            }
+        }
+        StorageResult::Ok(Box::new(entries.into_iter()))
+    }
+
+    /// Копирует секции, удерживая блокировки чтения всех трех одновременно
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        for storage in StorageId::all() {
+            self.expire_section(&storage);
+        }
+        let (Ok(individuals), Ok(tickets), Ok(az)) = (self.individuals.read(), self.tickets.read(), self.az.read()) else {
+            return lock_poisoned_error();
+        };
+        StorageResult::Ok(Box::new(MemorySnapshot {
+            sections: [individuals.clone(), tickets.clone(), az.clone()],
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -161,6 +1031,481 @@ mod tests {
         assert_eq!(count_result.unwrap_or_default(), 1);
     }
 
+    #[test]
+    fn test_health_check() {
+        let mut storage = MemoryStorage::new();
+        assert_eq!(storage.health_check(), StorageResult::Ok(()));
+    }
+
+    #[test]
+    fn test_non_empty_sections() {
+        let mut storage = MemoryStorage::new();
+        assert_eq!(storage.non_empty_sections(), StorageResult::Ok(vec![]));
+
+        assert!(storage.put_value(StorageId::Individuals, "key1", "value1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "key2", "value2").is_ok());
+        assert_eq!(storage.non_empty_sections(), StorageResult::Ok(vec![StorageId::Individuals, StorageId::Az]));
+
+        // Section becomes empty again after removal
+        assert!(storage.remove_value(StorageId::Az, "key2").is_ok());
+        assert_eq!(storage.non_empty_sections(), StorageResult::Ok(vec![StorageId::Individuals]));
+    }
+
+    #[test]
+    fn test_strict_utf8() {
+        let mut storage = MemoryStorage::new();
+        let binary = vec![0xff, 0xfe, 0x00];
+
+        // Binary values are allowed by default
+        assert!(storage.put_raw_value(StorageId::Individuals, "bin", binary.clone()).is_ok());
+
+        storage.set_strict_utf8(true);
+        assert_eq!(storage.put_raw_value(StorageId::Tickets, "bin", binary), StorageResult::UnprocessableEntity);
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "bin"), StorageResult::NotFound);
+
+        assert!(storage.put_raw_value(StorageId::Tickets, "text", "valid".as_bytes().to_vec()).is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "text"), StorageResult::Ok("valid".to_string()));
+
+        // replace_section keeps the section as is
+        let entries: [(&str, &[u8]); 2] = [("ok", b"v"), ("bin", &[0xff])];
+        assert_eq!(storage.replace_section(StorageId::Tickets, &entries), StorageResult::UnprocessableEntity);
+        assert_eq!(storage.get_value(StorageId::Tickets, "text"), StorageResult::Ok("valid".to_string()));
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "ok"), StorageResult::NotFound);
+    }
+
+    #[test]
+    fn test_replace_section() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Az, "old1", "v1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "old2", "v2").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "other", "v").is_ok());
+
+        let entries: [(&str, &[u8]); 2] = [("new1", b"n1"), ("new2", b"n2")];
+        assert!(storage.replace_section(StorageId::Az, &entries).is_ok());
+
+        assert_eq!(storage.get_value(StorageId::Az, "old1"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Az, "old2"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Az, "new1"), StorageResult::Ok("n1".to_string()));
+        assert_eq!(storage.get_value(StorageId::Az, "new2"), StorageResult::Ok("n2".to_string()));
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(2));
+
+        // Other sections are untouched
+        assert_eq!(storage.get_value(StorageId::Tickets, "other"), StorageResult::Ok("v".to_string()));
+    }
+
+    #[test]
+    fn test_increment_checked() {
+        let mut storage = MemoryStorage::new();
+
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 2, 5), StorageResult::Ok((2, false)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 2, 5), StorageResult::Ok((4, false)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 1, 5), StorageResult::Ok((5, true)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", 1, 5), StorageResult::Ok((6, true)));
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "quota", -3, 5), StorageResult::Ok((3, false)));
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "quota"), StorageResult::Ok(3i64.to_le_bytes().to_vec()));
+
+        // Non-counter values are rejected
+        assert!(storage.put_value(StorageId::Tickets, "text", "abc").is_ok());
+        assert_eq!(storage.increment_checked(StorageId::Tickets, "text", 1, 5), StorageResult::UnprocessableEntity);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        let mut storage = MemoryStorage::new();
+
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "lock", None, b"owner1"), StorageResult::Ok(true));
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "lock", None, b"owner2"), StorageResult::Ok(false));
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "lock", Some(b"owner2"), b"owner3"), StorageResult::Ok(false));
+        assert_eq!(storage.get_value(StorageId::Az, "lock"), StorageResult::Ok("owner1".to_string()));
+
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "lock", Some(b"owner1"), b"owner2"), StorageResult::Ok(true));
+        assert_eq!(storage.get_value(StorageId::Az, "lock"), StorageResult::Ok("owner2".to_string()));
+
+        // Expecting a value of a missing key never matches
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "missing", Some(b""), b"x"), StorageResult::Ok(false));
+        assert_eq!(storage.get_value(StorageId::Az, "missing"), StorageResult::NotFound);
+    }
+
+    #[test]
+    fn test_remove_matching() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Tickets, "t1", "done:1").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t2", "active:2").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t3", "done:3").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "t4", "done:4").is_ok());
+
+        let removed = storage.remove_matching(StorageId::Tickets, &mut |val| val.starts_with(b"done"));
+        assert_eq!(removed, StorageResult::Ok(2));
+
+        assert_eq!(storage.get_value(StorageId::Tickets, "t1"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Tickets, "t2"), StorageResult::Ok("active:2".to_string()));
+        assert_eq!(storage.get_value(StorageId::Tickets, "t3"), StorageResult::NotFound);
+        assert_eq!(storage.get_value(StorageId::Individuals, "t4"), StorageResult::Ok("done:4".to_string()));
+    }
+
+    #[test]
+    fn test_remove_prefix() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Az, "a:1", "1").is_ok());
+        assert!(storage.put_value(StorageId::Az, "a:2", "2").is_ok());
+        assert!(storage.put_value(StorageId::Az, "b:1", "3").is_ok());
+
+        assert_eq!(storage.remove_prefix(StorageId::Az, "a:"), StorageResult::Ok(2));
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(1));
+        assert_eq!(storage.get_value(StorageId::Az, "b:1"), StorageResult::Ok("3".to_string()));
+
+        // An empty prefix would wipe the section, clear is used for that
+        assert!(storage.remove_prefix(StorageId::Az, "").is_error());
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(1));
+    }
+
+    #[test]
+    fn test_copy_to_section() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Tickets, "ticket", "payload").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "ticket", "stale").is_ok());
+
+        assert_eq!(storage.copy_to_section(StorageId::Tickets, StorageId::Individuals, "ticket"), StorageResult::Ok(()));
+        assert_eq!(storage.get_value(StorageId::Tickets, "ticket"), StorageResult::Ok("payload".to_string()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "ticket"), StorageResult::Ok("payload".to_string()));
+
+        assert_eq!(storage.copy_to_section(StorageId::Tickets, StorageId::Az, "missing"), StorageResult::NotFound);
+        assert_eq!(storage.count(StorageId::Az), StorageResult::Ok(0));
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Individuals, "d:1", "same").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "d:2", "same").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "d:3", "other").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "d:4", "same").is_ok());
+        // Other sections are not included
+        assert!(storage.put_value(StorageId::Tickets, "t:1", "same").is_ok());
+
+        let mut groups = match storage.distinct_values(StorageId::Individuals) {
+            StorageResult::Ok(groups) => groups,
+            other => panic!("distinct_values failed: {:?}", other.map(|_| ())),
+        };
+        assert_eq!(groups.len(), 2);
+
+        let mut same = groups.remove(b"same".as_slice()).unwrap();
+        same.sort();
+        assert_eq!(same, vec!["d:1", "d:2", "d:4"]);
+        assert_eq!(groups.remove(b"other".as_slice()), Some(vec!["d:3".to_string()]));
+
+        let mut duplicates = 0;
+        assert!(storage.for_each_value_group(StorageId::Individuals, &mut |_, keys| {
+            if keys.len() > 1 {
+                duplicates += keys.len();
+            }
+        }).is_ok());
+        assert_eq!(duplicates, 3);
+    }
+
+    #[test]
+    fn test_count_by_prefix() {
+        let mut storage = MemoryStorage::new();
+        for key in ["user:1", "user:2", "org:1", "user:3:profile", "standalone"] {
+            assert!(storage.put_value(StorageId::Individuals, key, "v").is_ok());
+        }
+
+        let counts = storage.count_by_prefix(StorageId::Individuals, ':');
+        let expected: HashMap<String, usize> = [("user", 3), ("org", 1), ("standalone", 1)].iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        assert_eq!(counts, StorageResult::Ok(expected));
+
+        assert_eq!(storage.count_by_prefix(StorageId::Tickets, ':'), StorageResult::Ok(HashMap::new()));
+    }
+
+    #[test]
+    fn test_put_values_many_rejects_whole_batch() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_values_many(StorageId::Individuals, &[("a", "1"), ("b", "2")]).is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "b"), StorageResult::Ok("2".to_string()));
+
+        let res = storage.put_values_many(StorageId::Individuals, &[("c", "3"), ("", "4")]);
+        assert!(res.is_error());
+        assert_eq!(storage.get_value(StorageId::Individuals, "c"), StorageResult::NotFound);
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used() {
+        let mut storage = MemoryStorage::with_capacity(3);
+        for key in ["a", "b", "c"] {
+            assert!(storage.put_value(StorageId::Individuals, key, "v").is_ok());
+        }
+
+        // Reading "a" makes "b" the least recently used key
+        assert!(storage.get_value(StorageId::Individuals, "a").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "d", "v").is_ok());
+
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(3));
+        assert_eq!(storage.get_value(StorageId::Individuals, "b"), StorageResult::NotFound);
+        assert!(storage.get_value(StorageId::Individuals, "a").is_ok());
+
+        // Overwriting counts as an access too: "c" is now the oldest
+        assert!(storage.put_raw_value(StorageId::Individuals, "d", b"v2".to_vec()).is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "e", "v").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "c"), StorageResult::NotFound);
+        assert_eq!(storage.exists_many(StorageId::Individuals, &["a", "d", "e"]), StorageResult::Ok(vec![true, true, true]));
+
+        // Removed keys do not take part in eviction, sections are limited separately
+        assert!(storage.remove_value(StorageId::Individuals, "a").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "f", "v").is_ok());
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(3));
+        assert!(storage.put_value(StorageId::Tickets, "t", "v").is_ok());
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(3));
+    }
+
+    // Test clock: a fixed base advanced manually. The offset is per thread,
+    // so tests running in parallel do not move each other's clock
+    thread_local! {
+        static CLOCK_OFFSET_MS: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+    }
+
+    fn test_clock() -> Instant {
+        static BASE: std::sync::OnceLock<Instant> = std::sync::OnceLock::new();
+        *BASE.get_or_init(Instant::now) + Duration::from_millis(CLOCK_OFFSET_MS.with(|offset| offset.get()))
+    }
+
+    fn advance_clock(ms: u64) {
+        CLOCK_OFFSET_MS.with(|offset| offset.set(offset.get() + ms));
+    }
+
+    #[test]
+    fn test_put_value_with_ttl() {
+        let mut storage = MemoryStorage::new();
+        storage.set_clock(test_clock);
+
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "ticket", "t1", Duration::from_secs(60)).is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "renewed", "t2", Duration::from_secs(60)).is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "permanent", "p").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "renewed", "t2").is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Az, "ticket", "other", Duration::from_secs(600)).is_ok());
+
+        advance_clock(59_000);
+        assert_eq!(storage.get_value(StorageId::Tickets, "ticket"), StorageResult::Ok("t1".to_string()));
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(3));
+
+        advance_clock(1_000);
+        assert_eq!(storage.get_value(StorageId::Tickets, "ticket"), StorageResult::NotFound);
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "ticket"), StorageResult::NotFound);
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(2));
+
+        // put_value removed the TTL, other sections keep their own
+        assert_eq!(storage.get_value(StorageId::Tickets, "renewed"), StorageResult::Ok("t2".to_string()));
+        assert_eq!(storage.get_value(StorageId::Tickets, "permanent"), StorageResult::Ok("p".to_string()));
+        assert_eq!(storage.get_value(StorageId::Az, "ticket"), StorageResult::Ok("other".to_string()));
+
+        // Expired keys are gone without being read first
+        assert!(storage.put_value_with_ttl(StorageId::Individuals, "a", "1", Duration::from_secs(1)).is_ok());
+        advance_clock(1_000);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+        assert_eq!(storage.remove_value(StorageId::Individuals, "a"), StorageResult::NotFound);
+    }
+
+    #[test]
+    fn test_put_value_with_max_ttl_never_expires() {
+        let mut storage = MemoryStorage::new();
+        storage.set_clock(test_clock);
+
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "forever", "f", Duration::from_secs(60)).is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "forever", "f", Duration::MAX).is_ok());
+
+        // The earlier ttl is replaced, not kept
+        advance_clock(3_600_000);
+        assert_eq!(storage.get_value(StorageId::Tickets, "forever"), StorageResult::Ok("f".to_string()));
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(1));
+    }
+
+    #[test]
+    fn test_eviction_drops_ttl() {
+        let mut storage = MemoryStorage::with_capacity(2);
+        storage.set_clock(test_clock);
+
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "old", "1", Duration::from_secs(60)).is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "kept", "2", Duration::from_secs(60)).is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "new", "3").is_ok());
+
+        // The evicted key leaves no TTL behind, the surviving one keeps its own
+        assert_eq!(storage.get_value(StorageId::Tickets, "old"), StorageResult::NotFound);
+        let expiry = storage.expiry(&StorageId::Tickets);
+        assert!(!expiry.contains_key("old"));
+        assert!(expiry.contains_key("kept"));
+        drop(expiry);
+
+        // Eviction by a TTL write drops the TTL of the evicted key as well
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "newest", "4", Duration::from_secs(60)).is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "kept"), StorageResult::NotFound);
+        assert_eq!(storage.expiry(&StorageId::Tickets).keys().collect::<Vec<_>>(), vec!["newest"]);
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(2));
+    }
+
+    #[test]
+    fn test_new_is_unbounded() {
+        let mut storage = MemoryStorage::new();
+        assert_eq!(storage.capacity(), None);
+        for i in 0..100 {
+            assert!(storage.put_value(StorageId::Individuals, &format!("key{}", i), "v").is_ok());
+        }
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(100));
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let mut original = MemoryStorage::new();
+        assert!(original.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(original.put_raw_value(StorageId::Az, "acl", vec![0xff, 0x00]).is_ok());
+
+        let mut copy = original.clone();
+        assert_eq!(copy.get_raw_value(StorageId::Az, "acl"), StorageResult::Ok(vec![0xff, 0x00]));
+
+        assert!(copy.put_value(StorageId::Individuals, "a", "changed").is_ok());
+        assert!(copy.put_value(StorageId::Tickets, "t", "new").is_ok());
+        assert!(copy.remove_value(StorageId::Az, "acl").is_ok());
+
+        assert_eq!(original.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(original.count(StorageId::Tickets), StorageResult::Ok(0));
+        assert_eq!(original.get_raw_value(StorageId::Az, "acl"), StorageResult::Ok(vec![0xff, 0x00]));
+
+        // Generic wrapper is cloneable now as well
+        let generic = crate::vstorage::VMemoryStorage::new(original);
+        let mut generic_copy = generic.clone();
+        assert_eq!(generic_copy.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+    }
+
+    #[test]
+    fn test_get_values_many() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "b", "2").is_ok());
+
+        let res = storage.get_values_many(StorageId::Individuals, &["b", "missing", "a"]);
+        assert_eq!(res, vec![StorageResult::Ok("2".to_string()), StorageResult::NotFound, StorageResult::Ok("1".to_string())]);
+
+        assert!(storage.get_values_many(StorageId::Individuals, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_fill_individuals_reuses_vec() {
+        use v_individual_model::onto::individual2msgpack::to_msgpack;
+
+        let mut storage = MemoryStorage::new();
+        for id in ["test:a", "test:b", "test:c"] {
+            let mut individual = Individual::default();
+            individual.set_id(id);
+            individual.add_uri("rdf:type", "test:Class");
+            let mut raw = Vec::new();
+            assert!(to_msgpack(&individual, &mut raw).is_ok());
+            assert!(storage.put_raw_value(StorageId::Individuals, id, raw).is_ok());
+        }
+        assert!(storage.put_value(StorageId::Individuals, "test:broken", "not an individual").is_ok());
+
+        let mut out = Vec::new();
+        let loaded = storage.fill_individuals(StorageId::Individuals, &["test:a", "test:missing", "test:b", "test:broken"], &mut out);
+        assert_eq!(loaded, StorageResult::Ok(2));
+        let ids: Vec<&str> = out.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["test:a", "test:b"]);
+        assert_eq!(out[1].1.get_id(), "test:b");
+
+        // Second call on the same vec must not keep stale entries
+        let loaded = storage.fill_individuals(StorageId::Individuals, &["test:c"], &mut out);
+        assert_eq!(loaded, StorageResult::Ok(1));
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].0, "test:c");
+        assert_eq!(out[0].1.get_id(), "test:c");
+    }
+
+    #[test]
+    fn test_load_from_truncated_file() {
+        let path = format!("/tmp/test-memory-persist-{}.bin", std::process::id());
+
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Individuals, "a", "value-a").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "b", "value-b").is_ok());
+        assert!(storage.put_value(StorageId::Az, "c", "value-c").is_ok());
+        storage.save_to(&path).unwrap();
+
+        // Intact file loads completely
+        let (mut loaded, report) = MemoryStorage::load_from(&path).unwrap();
+        assert_eq!(report, LoadReport { recovered: 3, corrupt_at: None });
+        assert_eq!(loaded.get_value(StorageId::Az, "c"), StorageResult::Ok("value-c".to_string()));
+
+        // Cut the last record in half: each record here is 1 + 4 + 1 + 4 + 7 = 17 bytes
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(data.len(), 4 + 3 * 17);
+        std::fs::write(&path, &data[..data.len() - 8]).unwrap();
+
+        let (mut recovered, report) = MemoryStorage::load_from(&path).unwrap();
+        assert_eq!(report, LoadReport { recovered: 2, corrupt_at: Some(4 + 2 * 17) });
+        assert_eq!(recovered.count(StorageId::Individuals).unwrap_or_default()
+            + recovered.count(StorageId::Tickets).unwrap_or_default()
+            + recovered.count(StorageId::Az).unwrap_or_default(), 2);
+
+        let strict = MemoryStorage::load_from_strict(&path);
+        assert_eq!(strict.err().map(|e| e.kind()), Some(std::io::ErrorKind::InvalidData));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_save_load_binary_roundtrip() {
+        let path = std::path::PathBuf::from(format!("/tmp/test-memory-snapshot-{}.bin", std::process::id()));
+
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Individuals, "utf8", "значение").is_ok());
+        assert!(storage.put_raw_value(StorageId::Individuals, "binary", vec![0x00, 0xff, 0xfe, 0x80, 0x0a]).is_ok());
+        assert!(storage.put_raw_value(StorageId::Tickets, "empty", Vec::new()).is_ok());
+        assert!(storage.put_raw_value(StorageId::Az, "large", (0..=255u8).cycle().take(70_000).collect()).is_ok());
+        storage.save_snapshot(&path).unwrap();
+
+        let mut loaded = MemoryStorage::load_snapshot(&path).unwrap();
+        let mut expected: Vec<SectionEntry> = storage.iter_all().ok().unwrap().collect();
+        let mut actual: Vec<SectionEntry> = loaded.iter_all().ok().unwrap().collect();
+        expected.sort_by(|a, b| a.1.cmp(&b.1));
+        actual.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(actual.len(), 4);
+        assert_eq!(actual, expected);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_save_load_keeps_ttl() {
+        let path = std::path::PathBuf::from(format!("/tmp/test-memory-snapshot-ttl-{}.bin", std::process::id()));
+
+        let mut storage = MemoryStorage::new();
+        storage.set_clock(test_clock);
+        assert!(storage.put_value(StorageId::Individuals, "permanent", "p").is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Tickets, "expired", "e", Duration::from_secs(10)).is_ok());
+        assert!(storage.put_value_with_ttl(StorageId::Az, "ticket", "t", Duration::from_secs(60)).is_ok());
+        advance_clock(30_000);
+        storage.save_snapshot(&path).unwrap();
+
+        // The key keeps the 30 seconds it had left, the expired one is not saved
+        let saved_at = Instant::now();
+        let mut loaded = MemoryStorage::load_snapshot(&path).unwrap();
+        assert_eq!(loaded.get_value(StorageId::Az, "ticket"), StorageResult::Ok("t".to_string()));
+        assert_eq!(loaded.get_value(StorageId::Tickets, "expired"), StorageResult::NotFound);
+        assert_eq!(loaded.get_value(StorageId::Individuals, "permanent"), StorageResult::Ok("p".to_string()));
+        assert!(!loaded.expiry(&StorageId::Individuals).contains_key("permanent"));
+        let deadline = loaded.expiry(&StorageId::Az)["ticket"];
+        assert!(deadline > saved_at + Duration::from_secs(28) && deadline <= Instant::now() + Duration::from_secs(30));
+
+        // A key whose saved deadline has passed by the time of loading is skipped:
+        // the expiry of the only TTL record is the last 8 bytes of the file
+        let mut data = std::fs::read(&path).unwrap();
+        let at = data.len() - 8;
+        data[at..].copy_from_slice(&1u64.to_le_bytes());
+        std::fs::write(&path, &data).unwrap();
+        let (mut stale, report) = MemoryStorage::load_from(&path).unwrap();
+        assert_eq!(report, LoadReport { recovered: 1, corrupt_at: None });
+        assert_eq!(stale.get_value(StorageId::Az, "ticket"), StorageResult::NotFound);
+        assert_eq!(stale.get_value(StorageId::Individuals, "permanent"), StorageResult::Ok("p".to_string()));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn test_individual() {
         let mut storage = MemoryStorage::new();
@@ -280,4 +1625,97 @@ mod tests {
             assert_eq!(ac, 1);
         }
     }
+
+    #[test]
+    fn test_memory_usage() {
+        let mut storage = MemoryStorage::new();
+        assert_eq!(storage.memory_usage(), 0);
+
+        for i in 0..100 {
+            assert!(storage.put_raw_value(StorageId::Individuals, &format!("key{:04}", i), vec![0u8; 1000]).is_ok());
+        }
+        assert!(storage.put_raw_value(StorageId::Az, "az", vec![0u8; 10_000]).is_ok());
+        assert!(storage.put_value_ns("cache", "entry", &"x".repeat(5000)).is_ok());
+
+        // Keys and values alone take 100 * (7 + 1000) + (2 + 10000) + (5 + 5000) bytes
+        let payload = 100 * 1007 + 10_002 + 5005;
+        let usage = storage.memory_usage();
+        assert!(usage >= payload && usage <= payload + 102 * 100, "usage {} for payload {}", usage, payload);
+
+        assert!(storage.clear(StorageId::Individuals).is_ok());
+        assert!(storage.memory_usage() < usage - 100 * 1007);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_old_values() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t", "old").is_ok());
+
+        let mut snapshot = storage.snapshot().ok().unwrap();
+        assert!(storage.put_value(StorageId::Individuals, "a", "2").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "b", "new").is_ok());
+        assert!(storage.remove_value(StorageId::Tickets, "t").is_ok());
+
+        assert_eq!(snapshot.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(snapshot.get_value(StorageId::Individuals, "b"), StorageResult::NotFound);
+        assert_eq!(snapshot.get_raw_value(StorageId::Tickets, "t"), StorageResult::Ok(b"old".to_vec()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("2".to_string()));
+    }
+
+    #[test]
+    fn test_namespaces_are_isolated() {
+        let mut storage = MemoryStorage::new();
+
+        assert_eq!(storage.count_ns("sessions"), StorageResult::Ok(0));
+        assert!(storage.put_value_ns("sessions", "key", "s").is_ok());
+        assert!(storage.put_value_ns("settings", "key", "v").is_ok());
+        assert!(storage.put_value_ns("settings", "other", "w").is_ok());
+
+        assert_eq!(storage.get_value_ns("sessions", "key"), StorageResult::Ok("s".to_string()));
+        assert_eq!(storage.get_value_ns("settings", "key"), StorageResult::Ok("v".to_string()));
+        assert_eq!(storage.get_value_ns("settings", "missing"), StorageResult::NotFound);
+        assert_eq!(storage.count_ns("sessions"), StorageResult::Ok(1));
+        assert_eq!(storage.count_ns("settings"), StorageResult::Ok(2));
+
+        // Custom namespaces do not leak into the fixed sections
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+        assert!(storage.remove_value_ns("sessions", "key").is_ok());
+        assert_eq!(storage.remove_value_ns("sessions", "key"), StorageResult::NotFound);
+        assert_eq!(storage.get_value_ns("settings", "key"), StorageResult::Ok("v".to_string()));
+
+        // Reserved names map to the StorageId sections
+        assert!(storage.put_value_ns("tickets", "t", "1").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "t"), StorageResult::Ok("1".to_string()));
+        assert_eq!(storage.count_ns("tickets"), StorageResult::Ok(1));
+    }
+
+    #[test]
+    fn test_poisoned_lock_returns_error() {
+        let mut storage = MemoryStorage::new();
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+
+        // A thread panics while holding the write guard of the section
+        std::thread::scope(|s| {
+            let res = s
+                .spawn(|| {
+                    let _guard = storage.get_storage(StorageId::Individuals).write().unwrap();
+                    panic!("poison the individuals section");
+                })
+                .join();
+            assert!(res.is_err());
+        });
+
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), lock_poisoned_error());
+        assert_eq!(storage.get_individual(StorageId::Individuals, "key", &mut Individual::default()), lock_poisoned_error());
+        assert_eq!(storage.put_value(StorageId::Individuals, "other", "value"), lock_poisoned_error());
+        assert!(storage.remove_value(StorageId::Individuals, "key").is_error());
+        assert!(storage.count(StorageId::Individuals).is_error());
+        assert!(storage.get_values_many(StorageId::Individuals, &["key"]).iter().all(|res| res.is_error()));
+
+        // Other sections are not affected
+        assert!(storage.put_value(StorageId::Tickets, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "key"), StorageResult::Ok("value".to_string()));
+    }
 }
@@ -0,0 +1,85 @@
+// merge.rs
+
+use crate::common::{section_entries, Storage, StorageId, StorageResult};
+
+/// Правило разрешения конфликта: (ключ, значение в dst, значение в src) -> итоговое значение
+pub type ConflictResolver<'a> = dyn FnMut(&str, &[u8], &[u8]) -> Vec<u8> + 'a;
+
+/// Сливает секцию src в dst с разрешением конфликтов
+///
+/// Каждый ключ src, отсутствующий в dst, копируется как есть. Если ключ есть в
+/// обоих хранилищах, записывается `resolve(key, dst_val, src_val)`; результат,
+/// совпадающий со значением в dst, не записывается повторно. Возвращает число
+/// записанных ключей. src должен поддерживать iter_all.
+pub fn merge_into(
+    dst: &mut dyn Storage,
+    src: &mut dyn Storage,
+    storage: StorageId,
+    resolve: &mut ConflictResolver<'_>,
+) -> StorageResult<usize> {
+    let entries = match section_entries(src, storage.clone()) {
+        StorageResult::Ok(entries) => entries,
+        other => return other.map(|_| 0),
+    };
+
+    let mut written = 0;
+    for (key, src_val) in entries {
+        let val = match dst.get_raw_value(storage.clone(), &key) {
+            StorageResult::Ok(dst_val) => {
+                let resolved = resolve(&key, &dst_val, &src_val);
+                if resolved == dst_val {
+                    continue;
+                }
+                resolved
+            },
+            StorageResult::NotFound => src_val,
+            other => return other.map(|_| 0),
+        };
+
+        let res = dst.put_raw_value(storage.clone(), &key, val);
+        if !res.is_ok() {
+            return res.map(|_| 0);
+        }
+        written += 1;
+    }
+
+    StorageResult::Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_merge_keep_longer_value() {
+        let mut dst = MemoryStorage::new();
+        assert!(dst.put_value(StorageId::Individuals, "both_dst_longer", "long value").is_ok());
+        assert!(dst.put_value(StorageId::Individuals, "both_src_longer", "short").is_ok());
+        assert!(dst.put_value(StorageId::Individuals, "only_dst", "dst").is_ok());
+
+        let mut src = MemoryStorage::new();
+        assert!(src.put_value(StorageId::Individuals, "both_dst_longer", "short").is_ok());
+        assert!(src.put_value(StorageId::Individuals, "both_src_longer", "much longer").is_ok());
+        assert!(src.put_value(StorageId::Individuals, "only_src", "src").is_ok());
+        // Other sections are not merged
+        assert!(src.put_value(StorageId::Tickets, "ticket", "src").is_ok());
+
+        let mut conflicts = Vec::new();
+        let written = merge_into(&mut dst, &mut src, StorageId::Individuals, &mut |key, dst_val, src_val| {
+            conflicts.push(key.to_string());
+            if src_val.len() > dst_val.len() { src_val.to_vec() } else { dst_val.to_vec() }
+        });
+        // only_src inserted, both_src_longer overwritten, both_dst_longer kept
+        assert_eq!(written, StorageResult::Ok(2));
+
+        conflicts.sort();
+        assert_eq!(conflicts, vec!["both_dst_longer", "both_src_longer"]);
+
+        assert_eq!(dst.get_value(StorageId::Individuals, "both_dst_longer"), StorageResult::Ok("long value".to_string()));
+        assert_eq!(dst.get_value(StorageId::Individuals, "both_src_longer"), StorageResult::Ok("much longer".to_string()));
+        assert_eq!(dst.get_value(StorageId::Individuals, "only_dst"), StorageResult::Ok("dst".to_string()));
+        assert_eq!(dst.get_value(StorageId::Individuals, "only_src"), StorageResult::Ok("src".to_string()));
+        assert_eq!(dst.count(StorageId::Tickets), StorageResult::Ok(0));
+    }
+}
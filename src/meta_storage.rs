@@ -0,0 +1,144 @@
+// meta_storage.rs
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use v_individual_model::onto::individual::Individual;
+use crate::common::{decorator_inner, forward_to_inner, parse_individual_value, utf8_value, Storage, StorageId, StorageResult};
+
+/// Размер заголовка значения: время записи в наносекундах Unix (u64, little-endian)
+const TIMESTAMP_LEN: usize = 8;
+
+/// Декоратор, сохраняющий вместе с каждым значением время его записи
+///
+/// Значение хранится во внутреннем хранилище в виде `[8 байт наносекунд Unix][значение]`,
+/// поэтому бэкендам не нужны изменения схемы. Обычное чтение отрезает заголовок,
+/// а get_with_meta возвращает значение вместе со временем записи - этого достаточно
+/// для разрешения конфликтов по принципу "последняя запись побеждает" и проверки
+/// устаревания кеша. Отметки времени одного декоратора строго возрастают, даже если
+/// системные часы не успели сдвинуться между двумя записями.
+///
+/// Значения, записанные в обход декоратора, заголовка не имеют: их первые 8 байт
+/// будут приняты за время записи, а значение короче заголовка вернет ошибку.
+pub struct MetaStorage<S: Storage> {
+    inner: S,
+    last_stamp: u64,
+}
+
+impl<S: Storage> MetaStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            last_stamp: 0,
+        }
+    }
+
+    decorator_inner!(S);
+
+    /// Значение и время его последней записи через декоратор
+    pub fn get_with_meta(&mut self, storage: StorageId, key: &str) -> StorageResult<(Vec<u8>, SystemTime)> {
+        self.inner.get_raw_value(storage, key).and_then(|data| Self::unframe(key, data))
+    }
+
+    /// Следующая отметка времени: текущее время, но не раньше предыдущей записи + 1 нс
+    fn next_stamp(&mut self) -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default();
+        self.last_stamp = now.max(self.last_stamp + 1);
+        self.last_stamp
+    }
+
+    fn frame(&mut self, val: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(TIMESTAMP_LEN + val.len());
+        data.extend_from_slice(&self.next_stamp().to_le_bytes());
+        data.extend_from_slice(val);
+        data
+    }
+
+    fn unframe(key: &str, mut data: Vec<u8>) -> StorageResult<(Vec<u8>, SystemTime)> {
+        if data.len() < TIMESTAMP_LEN {
+            error!("MetaStorage: value for key=[{}] has no write timestamp", key);
+            return StorageResult::Error("Value has no write timestamp".to_string());
+        }
+        let mut stamp = [0u8; TIMESTAMP_LEN];
+        stamp.copy_from_slice(&data[..TIMESTAMP_LEN]);
+        data.drain(..TIMESTAMP_LEN);
+        StorageResult::Ok((data, UNIX_EPOCH + Duration::from_nanos(u64::from_le_bytes(stamp))))
+    }
+}
+
+impl<S: Storage> Storage for MetaStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        parse_individual_value(self.get_raw_value(storage, uri), iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(self.get_raw_value(storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.get_with_meta(storage, key).map(|(val, _)| val)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        self.put_raw_value(storage, key, val.as_bytes().to_vec())
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let data = self.frame(&val);
+        self.inner.put_raw_value(storage, key, data)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        self.inner.remove_value(storage, key)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.inner.clear(storage)
+    }
+
+    /// Ключи хранятся без изменений, заголовок значения не читается
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.inner.contains(storage, key)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.inner.keys_with_prefix(storage, prefix)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        self.inner.remove_prefix(storage, prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_timestamp_advances_between_writes() {
+        let mut storage = MetaStorage::new(MemoryStorage::new());
+
+        assert!(storage.put_value(StorageId::Individuals, "key", "first").is_ok());
+        let (val, first) = storage.get_with_meta(StorageId::Individuals, "key").unwrap();
+        assert_eq!(val, b"first".to_vec());
+
+        assert!(storage.put_value(StorageId::Individuals, "key", "second").is_ok());
+        let (val, second) = storage.get_with_meta(StorageId::Individuals, "key").unwrap();
+        assert_eq!(val, b"second".to_vec());
+        assert!(second > first);
+
+        // Plain reads strip the header
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("second".to_string()));
+        assert_eq!(storage.inner_mut().get_raw_value(StorageId::Individuals, "key").unwrap().len(), TIMESTAMP_LEN + 6);
+    }
+
+    #[test]
+    fn test_unframed_value_is_error() {
+        let mut storage = MetaStorage::new(MemoryStorage::new());
+
+        assert!(storage.inner_mut().put_value(StorageId::Tickets, "short", "abc").is_ok());
+        assert!(storage.get_with_meta(StorageId::Tickets, "short").is_error());
+        assert_eq!(storage.get_with_meta(StorageId::Tickets, "missing").map(|_| ()), StorageResult::NotFound);
+    }
+}
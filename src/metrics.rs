@@ -0,0 +1,133 @@
+// metrics.rs
+
+use std::time::Duration;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use crate::common::StorageId;
+use crate::observe::StorageObserver;
+
+/// Наблюдатель, экспортирующий метрики операций в формате Prometheus
+///
+/// Счетчик `vstorage_ops_total{op,storage,result}` и гистограмма
+/// `vstorage_op_duration_seconds{op,storage}`. op - get, put или remove;
+/// result - hit или miss для чтения, done для успешной записи и удаления и
+/// error для любой неуспешной операции. Копии разделяют одни и те же метрики, поэтому
+/// одну копию можно отдать в ObservedStorage, а другую использовать для render.
+#[derive(Clone)]
+pub struct PrometheusObserver {
+    registry: Registry,
+    ops: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl Default for PrometheusObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusObserver {
+    /// Метрики в собственном реестре
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new()).expect("metric definitions are valid")
+    }
+
+    /// Регистрирует метрики в переданном реестре, например общем для сервиса.
+    /// Ошибка, если метрики с такими именами в нем уже есть
+    pub fn with_registry(registry: Registry) -> prometheus::Result<Self> {
+        let ops = IntCounterVec::new(Opts::new("vstorage_ops_total", "Storage operations"), &["op", "storage", "result"])?;
+        let duration = HistogramVec::new(HistogramOpts::new("vstorage_op_duration_seconds", "Storage operation latency"), &["op", "storage"])?;
+        registry.register(Box::new(ops.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        Ok(Self {
+            registry,
+            ops,
+            duration,
+        })
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Метрики реестра в текстовом формате для сбора Prometheus
+    pub fn render(&self) -> String {
+        let mut buf = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buf) {
+            error!("PrometheusObserver: failed to encode metrics, err={:?}", e);
+        }
+        String::from_utf8(buf).unwrap_or_default()
+    }
+
+    fn record(&self, op: &str, storage: &StorageId, result: &str, elapsed: Duration) {
+        let storage = storage.as_str();
+        self.ops.with_label_values(&[op, storage, result]).inc();
+        self.duration.with_label_values(&[op, storage]).observe(elapsed.as_secs_f64());
+    }
+}
+
+impl StorageObserver for PrometheusObserver {
+    fn on_get(&mut self, storage: &StorageId, _key: &str, elapsed: Duration) {
+        self.record("get", storage, "hit", elapsed);
+    }
+
+    fn on_put(&mut self, storage: &StorageId, _key: &str, elapsed: Duration) {
+        self.record("put", storage, "done", elapsed);
+    }
+
+    fn on_remove(&mut self, storage: &StorageId, _key: &str, elapsed: Duration) {
+        self.record("remove", storage, "done", elapsed);
+    }
+
+    fn on_miss(&mut self, storage: &StorageId, _key: &str, elapsed: Duration) {
+        self.record("get", storage, "miss", elapsed);
+    }
+
+    fn on_error(&mut self, storage: &StorageId, _key: &str, op: &str, elapsed: Duration) {
+        self.record(op, storage, "error", elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{Storage, StorageResult};
+    use crate::memory_storage::MemoryStorage;
+    use crate::observe::ObservedStorage;
+
+    #[test]
+    fn test_rendered_counters() {
+        let metrics = PrometheusObserver::new();
+        let mut storage = ObservedStorage::new(MemoryStorage::new(), metrics.clone());
+
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "b", "2").is_ok());
+        assert!(storage.get_value(StorageId::Individuals, "a").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "missing"), StorageResult::NotFound);
+        assert!(storage.remove_value(StorageId::Individuals, "b").is_ok());
+
+        let text = metrics.render();
+        assert!(text.contains(r#"vstorage_ops_total{op="put",result="done",storage="individuals"} 2"#), "{}", text);
+        assert!(text.contains(r#"vstorage_ops_total{op="get",result="hit",storage="individuals"} 1"#), "{}", text);
+        assert!(text.contains(r#"vstorage_ops_total{op="get",result="miss",storage="tickets"} 1"#), "{}", text);
+        assert!(text.contains(r#"vstorage_ops_total{op="remove",result="done",storage="individuals"} 1"#), "{}", text);
+        assert!(text.contains(r#"vstorage_op_duration_seconds_count{op="put",storage="individuals"} 2"#), "{}", text);
+
+        // The same names can not be registered twice in one registry
+        assert!(PrometheusObserver::with_registry(metrics.registry().clone()).is_err());
+    }
+
+    #[test]
+    fn test_failures_are_rendered_as_errors() {
+        let metrics = PrometheusObserver::new();
+        let inner = crate::read_only_storage::ReadOnlyStorage::new(MemoryStorage::new());
+        let mut storage = ObservedStorage::new(inner, metrics.clone());
+
+        assert!(storage.put_value(StorageId::Az, "a", "1").is_error());
+        assert!(storage.remove_value(StorageId::Az, "a").is_error());
+
+        let text = metrics.render();
+        assert!(text.contains(r#"vstorage_ops_total{op="put",result="error",storage="az"} 1"#), "{}", text);
+        assert!(text.contains(r#"vstorage_ops_total{op="remove",result="error",storage="az"} 1"#), "{}", text);
+        assert!(!text.contains(r#"result="done""#), "{}", text);
+    }
+}
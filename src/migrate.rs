@@ -0,0 +1,95 @@
+// migrate.rs
+
+use crate::common::{section_entries, Storage, StorageId, StorageResult};
+
+/// Максимальное число ключей с ошибкой, перечисляемых в итоговом сообщении
+const MAX_REPORTED_FAILURES: usize = 10;
+
+/// Копирует все записи указанных секций src в dst
+///
+/// Значения переносятся как есть (get/put raw), существующие в dst ключи
+/// перезаписываются. Ошибка записи отдельного ключа не прерывает перенос:
+/// такие ключи собираются, и если они есть, возвращается Error со сводкой.
+/// При успехе возвращает число скопированных записей. src должен поддерживать
+/// iter_all (LMDB/MDBX, память).
+pub fn migrate(src: &mut dyn Storage, dst: &mut dyn Storage, storages: &[StorageId]) -> StorageResult<usize> {
+    let mut copied = 0;
+    let mut failed: Vec<(StorageId, String)> = Vec::new();
+
+    for storage in storages {
+        let entries = match section_entries(src, storage.clone()) {
+            StorageResult::Ok(entries) => entries,
+            other => return other.map(|_| 0),
+        };
+
+        for (key, val) in entries {
+            match dst.put_raw_value(storage.clone(), &key, val) {
+                StorageResult::Ok(()) => copied += 1,
+                res => {
+                    warn!("migrate: failed to copy key=[{}] of {:?}, res={:?}", key, storage, res);
+                    failed.push((storage.clone(), key));
+                },
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        return StorageResult::Ok(copied);
+    }
+
+    let listed: Vec<String> = failed.iter().take(MAX_REPORTED_FAILURES).map(|(storage, key)| format!("{:?}/{}", storage, key)).collect();
+    let more = if failed.len() > MAX_REPORTED_FAILURES {
+        format!(" and {} more", failed.len() - MAX_REPORTED_FAILURES)
+    } else {
+        String::new()
+    };
+    error!("migrate: copied {} records, {} failed", copied, failed.len());
+    StorageResult::Error(format!("Failed to migrate {} of {} records: {}{}", failed.len(), copied + failed.len(), listed.join(", "), more))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::StorageMode;
+    use crate::lmdb_storage::LMDBStorage;
+    use crate::memory_storage::MemoryStorage;
+    use crate::read_only_storage::ReadOnlyStorage;
+
+    #[test]
+    fn test_migrate_memory_to_lmdb() {
+        let temp_dir = format!("/tmp/test-migrate-lmdb-{}", std::process::id());
+
+        let mut src = MemoryStorage::new();
+        for i in 0..20 {
+            assert!(src.put_value(StorageId::Individuals, &format!("ind{}", i), &format!("value{}", i)).is_ok());
+        }
+        assert!(src.put_raw_value(StorageId::Tickets, "ticket", vec![0, 1, 2]).is_ok());
+        assert!(src.put_value(StorageId::Az, "not_migrated", "az").is_ok());
+
+        let mut dst = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+        assert_eq!(migrate(&mut src, &mut dst, &[StorageId::Individuals, StorageId::Tickets]), StorageResult::Ok(21));
+
+        assert_eq!(dst.count(StorageId::Individuals), src.count(StorageId::Individuals));
+        for i in 0..20 {
+            assert_eq!(dst.get_value(StorageId::Individuals, &format!("ind{}", i)), StorageResult::Ok(format!("value{}", i)));
+        }
+        assert_eq!(dst.get_raw_value(StorageId::Tickets, "ticket"), StorageResult::Ok(vec![0, 1, 2]));
+        assert_eq!(dst.count(StorageId::Az), StorageResult::Ok(0));
+
+        drop(dst);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_migrate_reports_failures() {
+        let mut src = MemoryStorage::new();
+        assert!(src.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(src.put_value(StorageId::Individuals, "b", "2").is_ok());
+
+        let mut dst = ReadOnlyStorage::new(MemoryStorage::new());
+        match migrate(&mut src, &mut dst, &[StorageId::Individuals]) {
+            StorageResult::Error(msg) => assert!(msg.starts_with("Failed to migrate 2 of 2 records"), "{}", msg),
+            other => panic!("expected error, got {:?}", other),
+        }
+    }
+}
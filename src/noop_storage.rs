@@ -0,0 +1,152 @@
+// noop_storage.rs
+
+use v_individual_model::onto::individual::Individual;
+use crate::common::{ScanEntry, SectionEntry, SnapshotReader, Storage, StorageId, StorageResult};
+use crate::memory_storage::MemoryStorage;
+
+/// Хранилище-заглушка, которое ничего не хранит
+///
+/// Любое чтение возвращает NotFound, любая запись - Ok(()) без сохранения данных,
+/// count - Ok(0). Подходит как приемник в тестах и как безопасная замена
+/// бэкенду, отключенному в конфигурации. Счетчик и compare_and_swap ведут себя
+/// так, будто ключа нет: increment возвращает delta, compare_and_swap успешен
+/// только при expected = None.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopStorage;
+
+impl NoopStorage {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Storage for NoopStorage {
+    fn get_individual(&mut self, _storage: StorageId, _id: &str, _iraw: &mut Individual) -> StorageResult<()> {
+        StorageResult::NotFound
+    }
+
+    fn get_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<String> {
+        StorageResult::NotFound
+    }
+
+    fn get_raw_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<Vec<u8>> {
+        StorageResult::NotFound
+    }
+
+    fn put_value(&mut self, _storage: StorageId, _key: &str, _val: &str) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn put_raw_value(&mut self, _storage: StorageId, _key: &str, _val: Vec<u8>) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn remove_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn count(&mut self, _storage: StorageId) -> StorageResult<usize> {
+        StorageResult::Ok(0)
+    }
+
+    fn replace_section(&mut self, _storage: StorageId, _entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn remove_matching(&mut self, _storage: StorageId, _pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        StorageResult::Ok(0)
+    }
+
+    fn remove_prefix(&mut self, _storage: StorageId, _prefix: &str) -> StorageResult<usize> {
+        StorageResult::Ok(0)
+    }
+
+    fn increment_checked(&mut self, _storage: StorageId, _key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        StorageResult::Ok((delta, delta >= limit))
+    }
+
+    fn compare_and_swap(&mut self, _storage: StorageId, _key: &str, expected: Option<&[u8]>, _new: &[u8]) -> StorageResult<bool> {
+        StorageResult::Ok(expected.is_none())
+    }
+
+    fn contains(&mut self, _storage: StorageId, _key: &str) -> StorageResult<bool> {
+        StorageResult::Ok(false)
+    }
+
+    fn get_value_ns(&mut self, _namespace: &str, _key: &str) -> StorageResult<String> {
+        StorageResult::NotFound
+    }
+
+    fn put_value_ns(&mut self, _namespace: &str, _key: &str, _val: &str) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn remove_value_ns(&mut self, _namespace: &str, _key: &str) -> StorageResult<()> {
+        StorageResult::Ok(())
+    }
+
+    fn count_ns(&mut self, _namespace: &str) -> StorageResult<usize> {
+        StorageResult::Ok(0)
+    }
+
+    fn keys_with_prefix(&mut self, _storage: StorageId, _prefix: &str) -> StorageResult<Vec<String>> {
+        StorageResult::Ok(Vec::new())
+    }
+
+    fn range(&mut self, _storage: StorageId, _start: &str, _end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        StorageResult::Ok(Vec::new())
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        StorageResult::Ok(Box::new(std::iter::empty()))
+    }
+
+    fn scan(&mut self, _storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        StorageResult::Ok(Box::new(std::iter::empty()))
+    }
+
+    /// Снимок пустого хранилища
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        MemoryStorage::new().snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage_factory::{DefaultStorageFactory, StorageConfig, StorageProvider};
+    use crate::vstorage::VStorage;
+
+    #[test]
+    fn test_noop_semantics() {
+        let mut storage = NoopStorage::new();
+
+        assert_eq!(storage.put_value(StorageId::Individuals, "key", "value"), StorageResult::Ok(()));
+        assert_eq!(storage.put_raw_value(StorageId::Tickets, "key", vec![1, 2]), StorageResult::Ok(()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::NotFound);
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "key"), StorageResult::NotFound);
+        assert_eq!(storage.get_individual(StorageId::Individuals, "key", &mut Individual::default()), StorageResult::NotFound);
+        assert_eq!(storage.remove_value(StorageId::Individuals, "key"), StorageResult::Ok(()));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+        assert_eq!(storage.contains(StorageId::Individuals, "key"), StorageResult::Ok(false));
+        assert_eq!(storage.clear(StorageId::Az), StorageResult::Ok(()));
+        assert_eq!(storage.increment(StorageId::Az, "n", 5), StorageResult::Ok(5));
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "n", None, b"1"), StorageResult::Ok(true));
+        assert_eq!(storage.compare_and_swap(StorageId::Az, "n", Some(b"1"), b"2"), StorageResult::Ok(false));
+        assert!(storage.iter_all().ok().is_some_and(|mut entries| entries.next().is_none()));
+        assert!(!storage.is_persistent());
+    }
+
+    #[test]
+    fn test_noop_in_vstorage() {
+        let mut storage = VStorage::new(StorageProvider::noop());
+        assert!(!storage.is_empty());
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::NotFound);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+        let mut storage = DefaultStorageFactory::new().create_storage_from_config(StorageConfig::Noop).unwrap();
+        assert!(storage.put_value(StorageId::Tickets, "key", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Tickets, "key"), StorageResult::NotFound);
+    }
+}
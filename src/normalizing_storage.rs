@@ -0,0 +1,206 @@
+// normalizing_storage.rs
+
+use std::borrow::Cow;
+use v_individual_model::onto::individual::Individual;
+use crate::common::{forward_to_inner, ScanEntry, SectionEntry, Storage, StorageId, StorageResult};
+
+/// Функция нормализации ключа
+pub type KeyNormalizer = fn(&str) -> Cow<'_, str>;
+
+/// Декоратор, приводящий каждый ключ к канонической форме
+///
+/// Нормализатор применяется симметрично при записи, чтении и удалении, поэтому
+/// вызывающий код может передавать ненормализованные ключи (`Foo:Bar`, ` foo:bar `)
+/// и попадать в одну и ту же запись. Ключи, записанные в хранилище в обход
+/// декоратора, должны быть уже нормализованы.
+pub struct NormalizingStorage<S: Storage> {
+    inner: S,
+    normalize: KeyNormalizer,
+}
+
+impl<S: Storage> NormalizingStorage<S> {
+    pub fn new(inner: S, normalize: KeyNormalizer) -> Self {
+        Self { inner, normalize }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Приводит ключ к форме, в которой он хранится
+    pub fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        (self.normalize)(key)
+    }
+}
+
+impl<S: Storage> Storage for NormalizingStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        let key = (self.normalize)(id);
+        self.inner.get_individual(storage, &key, iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        let key = (self.normalize)(key);
+        self.inner.get_value(storage, &key)
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let key = (self.normalize)(key);
+        self.inner.get_raw_value(storage, &key)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        let key = (self.normalize)(key);
+        self.inner.put_value(storage, &key, val)
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let key = (self.normalize)(key);
+        self.inner.put_raw_value(storage, &key, val)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let key = (self.normalize)(key);
+        self.inner.remove_value(storage, &key)
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        let keys: Vec<Cow<str>> = entries.iter().map(|(key, _)| (self.normalize)(key)).collect();
+        let entries: Vec<(&str, &[u8])> = keys.iter().zip(entries).map(|(key, (_, val))| (key.as_ref(), *val)).collect();
+        self.inner.put_many(storage, &entries)
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        let keys: Vec<Cow<str>> = pairs.iter().map(|(key, _)| (self.normalize)(key)).collect();
+        let pairs: Vec<(&str, &str)> = keys.iter().zip(pairs).map(|(key, (_, val))| (key.as_ref(), *val)).collect();
+        self.inner.put_values_many(storage, &pairs)
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        let keys: Vec<Cow<str>> = entries.iter().map(|(key, _)| (self.normalize)(key)).collect();
+        let entries: Vec<(&str, &[u8])> = keys.iter().zip(entries).map(|(key, (_, val))| (key.as_ref(), *val)).collect();
+        self.inner.replace_section(storage, &entries)
+    }
+
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        let key = (self.normalize)(key);
+        self.inner.get_if(storage, &key, pred)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        let key = (self.normalize)(key);
+        self.inner.read_value(storage, &key, f)
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        self.inner.remove_matching(storage, pred)
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        let prefix = (self.normalize)(prefix);
+        self.inner.remove_prefix(storage, &prefix)
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        let key = (self.normalize)(key);
+        self.inner.increment_checked(storage, &key, delta, limit)
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        let key = (self.normalize)(key);
+        self.inner.compare_and_swap(storage, &key, expected, new)
+    }
+
+    fn fill_individuals(&mut self, storage: StorageId, ids: &[&str], out: &mut Vec<(String, Individual)>) -> StorageResult<usize> {
+        let keys: Vec<Cow<str>> = ids.iter().map(|id| (self.normalize)(id)).collect();
+        let ids: Vec<&str> = keys.iter().map(|key| key.as_ref()).collect();
+        self.inner.fill_individuals(storage, &ids, out)
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        let keys: Vec<Cow<str>> = ids.iter().map(|id| (self.normalize)(id)).collect();
+        let ids: Vec<&str> = keys.iter().map(|key| key.as_ref()).collect();
+        self.inner.get_individuals(storage, &ids)
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        let key = (self.normalize)(key);
+        self.inner.contains(storage, &key)
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        let normalized: Vec<Cow<str>> = keys.iter().map(|key| (self.normalize)(key)).collect();
+        let keys: Vec<&str> = normalized.iter().map(|key| key.as_ref()).collect();
+        self.inner.exists_many(storage, &keys)
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        let normalized: Vec<Cow<str>> = keys.iter().map(|key| (self.normalize)(key)).collect();
+        let keys: Vec<&str> = normalized.iter().map(|key| key.as_ref()).collect();
+        self.inner.get_values_many(storage, &keys)
+    }
+
+    /// Префикс нормализуется так же, как ключи
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        let prefix = (self.normalize)(prefix);
+        self.inner.keys_with_prefix(storage, &prefix)
+    }
+
+    /// Границы нормализуются так же, как ключи
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        let start = (self.normalize)(start);
+        let end = (self.normalize)(end);
+        self.inner.range(storage, &start, &end)
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        self.inner.iter_all()
+    }
+
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.inner.scan(storage)
+    }
+
+    fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        self.inner.non_empty_sections()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    fn lowercase(key: &str) -> Cow<'_, str> {
+        if key.chars().any(|c| c.is_uppercase()) {
+            Cow::Owned(key.to_lowercase())
+        } else {
+            Cow::Borrowed(key)
+        }
+    }
+
+    #[test]
+    fn test_lowercase_normalizer() {
+        let mut storage = NormalizingStorage::new(MemoryStorage::new(), lowercase);
+
+        assert!(storage.put_value(StorageId::Individuals, "Foo:Bar", "value").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "foo:bar"), StorageResult::Ok("value".to_string()));
+        assert_eq!(storage.get_value(StorageId::Individuals, "FOO:BAR"), StorageResult::Ok("value".to_string()));
+
+        // Stored under the normalized key only
+        assert_eq!(storage.inner().get_test_data(StorageId::Individuals, "foo:bar"), Some(b"value".to_vec()));
+        assert_eq!(storage.inner().get_test_data(StorageId::Individuals, "Foo:Bar"), None);
+
+        assert!(storage.put_many(StorageId::Tickets, &[("A", b"1"), ("b", b"2")]).is_ok());
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "a"), StorageResult::Ok(b"1".to_vec()));
+
+        assert!(storage.remove_value(StorageId::Individuals, "FOO:bar").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "foo:bar"), StorageResult::NotFound);
+    }
+}
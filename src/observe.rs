@@ -0,0 +1,245 @@
+// observe.rs
+
+use std::time::{Duration, Instant};
+use v_individual_model::onto::individual::Individual;
+use crate::common::{forward_to_inner, ScanEntry, SectionEntry, SnapshotReader, Storage, StorageId, StorageResult};
+
+/// Получатель событий операций хранилища (метрики, логирование)
+///
+/// Каждый метод получает секцию, ключ и время выполнения вызова внутреннего
+/// хранилища. on_get, on_put и on_remove сообщают только об успешных (Ok) вызовах;
+/// чтение, завершившееся NotFound, сообщается через on_miss, а остальные неуспешные
+/// результаты (в том числе NotFound при удалении) - через on_error с именем операции
+/// (get, put или remove). Все методы по умолчанию ничего не делают.
+pub trait StorageObserver {
+    fn on_get(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {}
+    fn on_put(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {}
+    fn on_remove(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {}
+    fn on_miss(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {}
+    fn on_error(&mut self, _storage: &StorageId, _key: &str, _op: &str, _elapsed: Duration) {}
+}
+
+/// Наблюдатель, считающий операции каждого вида
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CountingObserver {
+    pub gets: usize,
+    pub puts: usize,
+    pub removes: usize,
+    pub misses: usize,
+    pub errors: usize,
+}
+
+impl StorageObserver for CountingObserver {
+    fn on_get(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {
+        self.gets += 1;
+    }
+
+    fn on_put(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {
+        self.puts += 1;
+    }
+
+    fn on_remove(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {
+        self.removes += 1;
+    }
+
+    fn on_miss(&mut self, _storage: &StorageId, _key: &str, _elapsed: Duration) {
+        self.misses += 1;
+    }
+
+    fn on_error(&mut self, _storage: &StorageId, _key: &str, _op: &str, _elapsed: Duration) {
+        self.errors += 1;
+    }
+}
+
+/// Декоратор, замеряющий время операций и сообщающий о них наблюдателю
+///
+/// Наблюдаются чтение, запись и удаление отдельных ключей. Пакетные операции
+/// (put_many, replace_section и т.п.) не пробрасываются во внутреннее хранилище,
+/// а выполняются реализациями по умолчанию через наблюдаемые методы, поэтому
+/// каждый ключ сообщается отдельно.
+pub struct ObservedStorage<S: Storage, O: StorageObserver> {
+    inner: S,
+    observer: O,
+}
+
+impl<S: Storage, O: StorageObserver> ObservedStorage<S, O> {
+    pub fn new(inner: S, observer: O) -> Self {
+        Self {
+            inner,
+            observer,
+        }
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn observe_get<T>(&mut self, storage: &StorageId, key: &str, started: Instant, res: StorageResult<T>) -> StorageResult<T> {
+        let elapsed = started.elapsed();
+        match res {
+            StorageResult::Ok(_) => self.observer.on_get(storage, key, elapsed),
+            StorageResult::NotFound => self.observer.on_miss(storage, key, elapsed),
+            _ => self.observer.on_error(storage, key, "get", elapsed),
+        }
+        res
+    }
+
+    fn observe_put(&mut self, storage: &StorageId, key: &str, started: Instant, res: StorageResult<()>) -> StorageResult<()> {
+        let elapsed = started.elapsed();
+        match res {
+            StorageResult::Ok(()) => self.observer.on_put(storage, key, elapsed),
+            _ => self.observer.on_error(storage, key, "put", elapsed),
+        }
+        res
+    }
+
+    fn observe_remove(&mut self, storage: &StorageId, key: &str, started: Instant, res: StorageResult<()>) -> StorageResult<()> {
+        let elapsed = started.elapsed();
+        match res {
+            StorageResult::Ok(()) => self.observer.on_remove(storage, key, elapsed),
+            _ => self.observer.on_error(storage, key, "remove", elapsed),
+        }
+        res
+    }
+}
+
+impl<S: Storage, O: StorageObserver> Storage for ObservedStorage<S, O> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        let started = Instant::now();
+        let res = self.inner.get_individual(storage.clone(), id, iraw);
+        self.observe_get(&storage, id, started, res)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        let started = Instant::now();
+        let res = self.inner.get_value(storage.clone(), key);
+        self.observe_get(&storage, key, started, res)
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let started = Instant::now();
+        let res = self.inner.get_raw_value(storage.clone(), key);
+        self.observe_get(&storage, key, started, res)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        let started = Instant::now();
+        let res = self.inner.read_value(storage.clone(), key, f);
+        self.observe_get(&storage, key, started, res)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        let started = Instant::now();
+        let res = self.inner.put_value(storage.clone(), key, val);
+        self.observe_put(&storage, key, started, res)
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let started = Instant::now();
+        let res = self.inner.put_raw_value(storage.clone(), key, val);
+        self.observe_put(&storage, key, started, res)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let started = Instant::now();
+        let res = self.inner.remove_value(storage.clone(), key);
+        self.observe_remove(&storage, key, started, res)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.inner.clear(storage)
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.inner.contains(storage, key)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.inner.keys_with_prefix(storage, prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.inner.range(storage, start, end)
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        self.inner.iter_all()
+    }
+
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.inner.scan(storage)
+    }
+
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        self.inner.snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_counting_observer_tallies() {
+        let mut storage = ObservedStorage::new(MemoryStorage::new(), CountingObserver::default());
+
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert!(storage.put_raw_value(StorageId::Tickets, "t", vec![1, 2]).is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::Ok("1".to_string()));
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "t"), StorageResult::Ok(vec![1, 2]));
+        assert!(storage.remove_value(StorageId::Individuals, "a").is_ok());
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::NotFound);
+
+        assert_eq!(
+            storage.observer(),
+            &CountingObserver {
+                gets: 2,
+                puts: 2,
+                removes: 1,
+                misses: 1,
+                errors: 0,
+            }
+        );
+
+        // Batch writes are reported per key
+        assert!(storage.put_values_many(StorageId::Az, &[("x", "1"), ("y", "2")]).is_ok());
+        assert_eq!(storage.observer().puts, 4);
+    }
+
+    #[test]
+    fn test_failures_are_reported_as_errors() {
+        let inner = crate::read_only_storage::ReadOnlyStorage::new(MemoryStorage::new());
+        let mut storage = ObservedStorage::new(inner, CountingObserver::default());
+
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_error());
+        assert!(storage.put_raw_value(StorageId::Individuals, "a", vec![1]).is_error());
+        assert!(storage.remove_value(StorageId::Individuals, "a").is_error());
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::NotFound);
+
+        assert_eq!(
+            storage.observer(),
+            &CountingObserver {
+                gets: 0,
+                puts: 0,
+                removes: 0,
+                misses: 1,
+                errors: 3,
+            }
+        );
+    }
+}
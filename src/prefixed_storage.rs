@@ -0,0 +1,414 @@
+// prefixed_storage.rs
+
+use std::collections::HashMap;
+use v_individual_model::onto::individual::Individual;
+use crate::common::{empty_key_error, ScanEntry, SectionEntry, Storage, StorageId, StorageResult};
+
+/// Декоратор, изолирующий пространство ключей арендатора фиксированным префиксом
+///
+/// Префикс (например `tenant-a:`) добавляется к каждому ключу перед обращением
+/// к внутреннему хранилищу и отрезается в результатах обхода и поиска по префиксу,
+/// поэтому несколько арендаторов могут делить одно окружение LMDB без коллизий ключей.
+/// `count` учитывает только ключи арендатора: первый вызов для секции ищет ключи
+/// по префиксу, дальше число поддерживается записями и удалениями через декоратор.
+/// Пока число известно, каждая одиночная запись стоит лишнего чтения: перед ней
+/// через `contains` проверяется, был ли ключ. Пакетные операции, `clear` и `inner_mut`
+/// сбрасывают число, и следующий `count` снова ищет по префиксу.
+///
+/// Изменения, прошедшие мимо декоратора, в число не попадают, и `count` может
+/// вернуть устаревшее значение: вытеснение ключей по лимиту (MemoryStorage с
+/// capacity), истечение TTL, запись через другой экземпляр на том же окружении.
+/// Если такое возможно, точное число дает `inner_mut().keys_with_prefix`.
+///
+/// Операции над секцией целиком (`clear`, `replace_section`, `remove_matching`)
+/// затрагивают только ключи арендатора и выполняются поштучно, не атомарно.
+pub struct PrefixedStorage<S: Storage> {
+    inner: S,
+    prefix: String,
+    /// Число ключей арендатора по секциям, если оно известно
+    counts: HashMap<StorageId, usize>,
+}
+
+impl<S: Storage> PrefixedStorage<S> {
+    pub fn new(inner: S, prefix: &str) -> Self {
+        Self {
+            inner,
+            prefix: prefix.to_string(),
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Изменения в обход декоратора не отражаются в числе ключей, поэтому оно сбрасывается
+    pub fn inner_mut(&mut self) -> &mut S {
+        self.counts.clear();
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Ключ, под которым значение хранится во внутреннем хранилище
+    fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    /// Ключи арендатора во внутреннем хранилище, с префиксом
+    fn own_keys(&mut self, storage: StorageId) -> StorageResult<Vec<String>> {
+        self.inner.keys_with_prefix(storage, &self.prefix)
+    }
+
+    /// Отсутствовал ли ключ до записи; None, если число ключей секции неизвестно
+    fn is_new_key(&mut self, storage: &StorageId, key: &str) -> Option<bool> {
+        if !self.counts.contains_key(storage) {
+            return None;
+        }
+        match self.inner.contains(storage.clone(), key) {
+            StorageResult::Ok(found) => Some(!found),
+            _ => {
+                self.counts.remove(storage);
+                None
+            },
+        }
+    }
+
+    /// Изменяет известное число ключей секции на delta, None сбрасывает его
+    fn adjust_count(&mut self, storage: &StorageId, delta: Option<isize>) {
+        match delta {
+            Some(delta) => {
+                if let Some(count) = self.counts.get_mut(storage) {
+                    *count = count.saturating_add_signed(delta);
+                }
+            },
+            None => {
+                self.counts.remove(storage);
+            },
+        }
+    }
+
+    /// Учитывает запись ключа, о котором is_new_key сообщил new
+    fn count_put<T>(&mut self, storage: &StorageId, new: Option<bool>, res: &StorageResult<T>) {
+        let delta = if res.is_ok() { new.map(isize::from) } else { None };
+        self.adjust_count(storage, delta);
+    }
+
+    fn remove_own(&mut self, storage: &StorageId, keys: &[String]) -> StorageResult<()> {
+        for key in keys {
+            match self.inner.remove_value(storage.clone(), key) {
+                StorageResult::Ok(()) | StorageResult::NotFound => {},
+                other => return other,
+            }
+        }
+        StorageResult::Ok(())
+    }
+}
+
+impl<S: Storage> Storage for PrefixedStorage<S> {
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        let key = self.full_key(id);
+        self.inner.get_individual(storage, &key, iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        let key = self.full_key(key);
+        self.inner.get_value(storage, &key)
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let key = self.full_key(key);
+        self.inner.get_raw_value(storage, &key)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        let key = self.full_key(key);
+        let new = self.is_new_key(&storage, &key);
+        let res = self.inner.put_value(storage.clone(), &key, val);
+        self.count_put(&storage, new, &res);
+        res
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let key = self.full_key(key);
+        let new = self.is_new_key(&storage, &key);
+        let res = self.inner.put_raw_value(storage.clone(), &key, val);
+        self.count_put(&storage, new, &res);
+        res
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let key = self.full_key(key);
+        let res = self.inner.remove_value(storage.clone(), &key);
+        let delta = match res {
+            StorageResult::Ok(()) => Some(-1),
+            StorageResult::NotFound => Some(0),
+            _ => None,
+        };
+        self.adjust_count(&storage, delta);
+        res
+    }
+
+    fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        if let Some(count) = self.counts.get(&storage) {
+            return StorageResult::Ok(*count);
+        }
+        self.own_keys(storage.clone()).map(|keys| {
+            self.counts.insert(storage, keys.len());
+            keys.len()
+        })
+    }
+
+    fn is_persistent(&self) -> bool {
+        self.inner.is_persistent()
+    }
+
+    fn sync(&mut self, force: bool) -> StorageResult<()> {
+        self.inner.sync(force)
+    }
+
+    fn health_check(&mut self) -> StorageResult<()> {
+        self.inner.health_check()
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        let keys: Vec<String> = entries.iter().map(|(key, _)| self.full_key(key)).collect();
+        let entries: Vec<(&str, &[u8])> = keys.iter().zip(entries).map(|(key, (_, val))| (key.as_str(), *val)).collect();
+        self.adjust_count(&storage, None);
+        self.inner.put_many(storage, &entries)
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        let keys: Vec<String> = pairs.iter().map(|(key, _)| self.full_key(key)).collect();
+        let pairs: Vec<(&str, &str)> = keys.iter().zip(pairs).map(|(key, (_, val))| (key.as_str(), *val)).collect();
+        self.adjust_count(&storage, None);
+        self.inner.put_values_many(storage, &pairs)
+    }
+
+    /// Удаляет ключи арендатора и записывает entries; ключи других арендаторов не затрагиваются
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        let res = self.clear(storage.clone());
+        if !res.is_ok() {
+            return res;
+        }
+        self.put_many(storage, entries)
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.adjust_count(&storage, None);
+        match self.own_keys(storage.clone()) {
+            StorageResult::Ok(keys) => self.remove_own(&storage, &keys),
+            other => other.map(|_| ()),
+        }
+    }
+
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        let key = self.full_key(key);
+        self.inner.get_if(storage, &key, pred)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        let key = self.full_key(key);
+        self.inner.read_value(storage, &key, f)
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        let keys = match self.own_keys(storage.clone()) {
+            StorageResult::Ok(keys) => keys,
+            other => return other.map(|_| 0),
+        };
+
+        let mut matched = Vec::new();
+        for key in keys {
+            match self.inner.get_raw_value(storage.clone(), &key) {
+                StorageResult::Ok(val) if pred(&val) => matched.push(key),
+                StorageResult::Ok(_) | StorageResult::NotFound => {},
+                other => return other.map(|_| 0),
+            }
+        }
+        self.adjust_count(&storage, None);
+        self.remove_own(&storage, &matched).map(|_| matched.len())
+    }
+
+    /// Пустой префикс отклоняется: иначе удалились бы все ключи арендатора
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        if prefix.is_empty() {
+            return empty_key_error();
+        }
+        let full_prefix = self.full_key(prefix);
+        let res = self.inner.remove_prefix(storage.clone(), &full_prefix);
+        let delta = match res {
+            StorageResult::Ok(removed) => Some(-(removed as isize)),
+            _ => None,
+        };
+        self.adjust_count(&storage, delta);
+        res
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        let key = self.full_key(key);
+        let new = self.is_new_key(&storage, &key);
+        let res = self.inner.increment_checked(storage.clone(), &key, delta, limit);
+        self.count_put(&storage, new, &res);
+        res
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        let key = self.full_key(key);
+        let res = self.inner.compare_and_swap(storage.clone(), &key, expected, new);
+        let delta = match res {
+            StorageResult::Ok(true) if expected.is_none() => Some(1),
+            StorageResult::Ok(_) => Some(0),
+            _ => None,
+        };
+        self.adjust_count(&storage, delta);
+        res
+    }
+
+    fn fill_individuals(&mut self, storage: StorageId, ids: &[&str], out: &mut Vec<(String, Individual)>) -> StorageResult<usize> {
+        let keys: Vec<String> = ids.iter().map(|id| self.full_key(id)).collect();
+        let ids: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        let res = self.inner.fill_individuals(storage, &ids, out);
+        let prefix_len = self.prefix.len();
+        for (id, _) in out.iter_mut() {
+            id.drain(..prefix_len);
+        }
+        res
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        let keys: Vec<String> = ids.iter().map(|id| self.full_key(id)).collect();
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        let prefix_len = self.prefix.len();
+        let mut individuals = self.inner.get_individuals(storage, &keys);
+        for (id, _) in individuals.iter_mut() {
+            id.drain(..prefix_len);
+        }
+        individuals
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        let key = self.full_key(key);
+        self.inner.contains(storage, &key)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        let full_prefix = self.full_key(prefix);
+        let prefix_len = self.prefix.len();
+        self.inner
+            .keys_with_prefix(storage, &full_prefix)
+            .map(|keys| keys.into_iter().map(|key| key[prefix_len..].to_string()).collect())
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        let start = self.full_key(start);
+        let end = self.full_key(end);
+        let prefix_len = self.prefix.len();
+        self.inner.range(storage, &start, &end).map(|entries| entries.into_iter().map(|(key, val)| (key[prefix_len..].to_string(), val)).collect())
+    }
+
+    /// Только записи арендатора, ключи без префикса
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        let prefix = self.prefix.clone();
+        self.inner.iter_all().map(|entries| {
+            let entries = entries.filter_map(move |(storage, key, val)| key.strip_prefix(&prefix).map(|key| (storage, key.to_string(), val)));
+            Box::new(entries) as Box<dyn Iterator<Item = SectionEntry>>
+        })
+    }
+
+    /// Только пары арендатора, ключи без префикса
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        let prefix = self.prefix.clone().into_bytes();
+        self.inner.scan(storage).map(|pairs| {
+            let pairs = pairs.filter_map(move |(key, val)| key.strip_prefix(prefix.as_slice()).map(|key| (key.to_vec(), val)));
+            Box::new(pairs) as Box<dyn Iterator<Item = ScanEntry>>
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+
+    #[test]
+    fn test_tenants_are_isolated() {
+        let mut tenant_a = PrefixedStorage::new(MemoryStorage::new(), "tenant-a:");
+        assert!(tenant_a.put_value(StorageId::Individuals, "doc:1", "a1").is_ok());
+        assert!(tenant_a.put_value(StorageId::Individuals, "doc:2", "a2").is_ok());
+
+        // Second tenant on the same physical storage
+        let mut tenant_b = PrefixedStorage::new(tenant_a.into_inner(), "tenant-b:");
+        assert_eq!(tenant_b.get_value(StorageId::Individuals, "doc:1"), StorageResult::NotFound);
+        assert_eq!(tenant_b.count(StorageId::Individuals), StorageResult::Ok(0));
+        assert!(tenant_b.put_value(StorageId::Individuals, "doc:1", "b1").is_ok());
+        assert_eq!(tenant_b.keys_with_prefix(StorageId::Individuals, "doc:"), StorageResult::Ok(vec!["doc:1".to_string()]));
+
+        // Clearing one tenant keeps the other
+        assert!(tenant_b.clear(StorageId::Individuals).is_ok());
+        assert_eq!(tenant_b.inner_mut().count(StorageId::Individuals), StorageResult::Ok(2));
+
+        let mut tenant_a = PrefixedStorage::new(tenant_b.into_inner(), "tenant-a:");
+        assert_eq!(tenant_a.get_value(StorageId::Individuals, "doc:1"), StorageResult::Ok("a1".to_string()));
+        assert_eq!(tenant_a.count(StorageId::Individuals), StorageResult::Ok(2));
+        assert_eq!(tenant_a.keys_with_prefix(StorageId::Individuals, ""), StorageResult::Ok(vec!["doc:1".to_string(), "doc:2".to_string()]));
+        assert_eq!(tenant_a.inner().get_test_data(StorageId::Individuals, "tenant-a:doc:2"), Some(b"a2".to_vec()));
+    }
+
+    #[test]
+    fn test_iteration_strips_prefix() {
+        let mut inner = MemoryStorage::new();
+        assert!(inner.put_value(StorageId::Tickets, "tenant-b:t", "other").is_ok());
+
+        let mut storage = PrefixedStorage::new(inner, "tenant-a:");
+        assert!(storage.put_value(StorageId::Tickets, "t", "mine").is_ok());
+
+        let entries: Vec<SectionEntry> = storage.iter_all().ok().map(|entries| entries.collect()).unwrap_or_default();
+        assert_eq!(entries, vec![(StorageId::Tickets, "t".to_string(), b"mine".to_vec())]);
+
+        assert_eq!(storage.remove_matching(StorageId::Tickets, &mut |_| true), StorageResult::Ok(1));
+        assert_eq!(storage.inner_mut().count(StorageId::Tickets), StorageResult::Ok(1));
+    }
+
+    #[test]
+    fn test_cached_count_matches_scan() {
+        let mut inner = MemoryStorage::new();
+        assert!(inner.put_value(StorageId::Individuals, "tenant-b:x", "other").is_ok());
+
+        let mut storage = PrefixedStorage::new(inner, "tenant-a:");
+        assert!(storage.put_value(StorageId::Individuals, "a", "1").is_ok());
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(1));
+
+        // Overwrites, removals of missing keys and counters after the count is cached
+        assert!(storage.put_value(StorageId::Individuals, "b", "2").is_ok());
+        assert!(storage.put_raw_value(StorageId::Individuals, "a", b"1'".to_vec()).is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "log:1", "x").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "log:2", "y").is_ok());
+        assert!(storage.remove_value(StorageId::Individuals, "b").is_ok());
+        assert_eq!(storage.remove_value(StorageId::Individuals, "missing"), StorageResult::NotFound);
+        assert!(storage.increment_checked(StorageId::Individuals, "counter", 1, 10).is_ok());
+        assert!(storage.increment_checked(StorageId::Individuals, "counter", 1, 10).is_ok());
+        assert_eq!(storage.compare_and_swap(StorageId::Individuals, "cas", None, b"v"), StorageResult::Ok(true));
+        assert_eq!(storage.compare_and_swap(StorageId::Individuals, "cas", None, b"v"), StorageResult::Ok(false));
+        assert_eq!(storage.remove_prefix(StorageId::Individuals, "log:"), StorageResult::Ok(2));
+
+        let scanned = storage.inner.keys_with_prefix(StorageId::Individuals, "tenant-a:").unwrap().len();
+        assert_eq!(scanned, 3);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(scanned));
+
+        // Batch writes and clear drop the cached count, the next count scans again
+        assert!(storage.put_values_many(StorageId::Individuals, &[("a", "1"), ("c", "3")]).is_ok());
+        assert!(!storage.counts.contains_key(&StorageId::Individuals));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(4));
+        assert!(storage.clear(StorageId::Individuals).is_ok());
+        assert!(!storage.counts.contains_key(&StorageId::Individuals));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+        assert_eq!(storage.inner_mut().count(StorageId::Individuals), StorageResult::Ok(1));
+    }
+}
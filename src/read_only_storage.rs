@@ -0,0 +1,209 @@
+// read_only_storage.rs
+
+use std::collections::HashMap;
+use v_individual_model::onto::individual::Individual;
+use crate::common::{forward_to_inner, ScanEntry, SectionEntry, SnapshotReader, Storage, StorageId, StorageResult};
+
+/// Ошибка изменяющей операции через ReadOnlyStorage; не путать с common::READ_ONLY_ERROR
+/// бэкенда, открытого в режиме StorageMode::ReadOnly
+pub const READ_ONLY_VIEW_ERROR: &str = "read-only view";
+
+/// Обертка, запрещающая любые изменения хранилища
+///
+/// Все операции чтения делегируются внутреннему хранилищу, а каждая изменяющая
+/// операция возвращает `StorageResult::Error("read-only view")`. В отличие от
+/// `StorageMode::ReadOnly` работает для любого бэкенда, включая память.
+pub struct ReadOnlyStorage<S: Storage> {
+    inner: S,
+}
+
+impl<S: Storage> ReadOnlyStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn rejected<T>(op: &str, storage: &StorageId, key: &str) -> StorageResult<T> {
+        warn!("ReadOnlyStorage: rejected {} for key=[{}] in {:?}", op, key, storage);
+        StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string())
+    }
+}
+
+impl<S: Storage> Storage for ReadOnlyStorage<S> {
+    forward_to_inner!();
+
+    fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        self.inner.get_individual(storage, id, iraw)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        self.inner.get_value(storage, key)
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.inner.get_raw_value(storage, key)
+    }
+
+    fn put_value(&mut self, storage: StorageId, key: &str, _val: &str) -> StorageResult<()> {
+        Self::rejected("put_value", &storage, key)
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, key: &str, _val: Vec<u8>) -> StorageResult<()> {
+        Self::rejected("put_raw_value", &storage, key)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        Self::rejected("remove_value", &storage, key)
+    }
+
+    fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        self.inner.get_value_ns(namespace, key)
+    }
+
+    fn put_value_ns(&mut self, namespace: &str, key: &str, _val: &str) -> StorageResult<()> {
+        warn!("ReadOnlyStorage: rejected put_value_ns for key=[{}] in namespace [{}]", key, namespace);
+        StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string())
+    }
+
+    fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        warn!("ReadOnlyStorage: rejected remove_value_ns for key=[{}] in namespace [{}]", key, namespace);
+        StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string())
+    }
+
+    fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        self.inner.count_ns(namespace)
+    }
+
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        self.inner.snapshot()
+    }
+
+    fn put_many(&mut self, storage: StorageId, _entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        Self::rejected("put_many", &storage, "*")
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, _pairs: &[(&str, &str)]) -> StorageResult<()> {
+        Self::rejected("put_values_many", &storage, "*")
+    }
+
+    fn copy_to_section(&mut self, _from: StorageId, to: StorageId, key: &str) -> StorageResult<()> {
+        Self::rejected("copy_to_section", &to, key)
+    }
+
+    fn replace_section(&mut self, storage: StorageId, _entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        Self::rejected("replace_section", &storage, "*")
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        Self::rejected("clear", &storage, "*")
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, _pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        Self::rejected("remove_matching", &storage, "*")
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        Self::rejected("remove_prefix", &storage, prefix)
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, _delta: i64, _limit: i64) -> StorageResult<(i64, bool)> {
+        Self::rejected("increment_checked", &storage, key)
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, _expected: Option<&[u8]>, _new: &[u8]) -> StorageResult<bool> {
+        Self::rejected("compare_and_swap", &storage, key)
+    }
+
+    fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        self.inner.non_empty_sections()
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.inner.contains(storage, key)
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        self.inner.read_value(storage, key, f)
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        self.inner.get_individuals(storage, ids)
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        self.inner.exists_many(storage, keys)
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        self.inner.get_values_many(storage, keys)
+    }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        self.inner.count_by_prefix(storage, depth_separator)
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.inner.keys_with_prefix(storage, prefix)
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.inner.range(storage, start, end)
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        self.inner.iter_all()
+    }
+
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.inner.scan(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::MemoryStorage;
+    use crate::storage_factory::{DefaultStorageFactory, StorageConfig};
+
+    #[test]
+    fn test_reads_pass_writes_rejected() {
+        let mut inner = MemoryStorage::new();
+        assert!(inner.put_value(StorageId::Individuals, "key", "value").is_ok());
+
+        let mut storage = ReadOnlyStorage::new(inner);
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "key"), StorageResult::Ok(b"value".to_vec()));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(1));
+
+        let rejected = StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string());
+        assert_eq!(storage.put_value(StorageId::Individuals, "key", "new"), rejected);
+        assert_eq!(storage.put_raw_value(StorageId::Individuals, "other", vec![1]), rejected);
+        assert_eq!(storage.remove_value(StorageId::Individuals, "key"), rejected);
+        assert_eq!(storage.replace_section(StorageId::Individuals, &[]), rejected);
+        assert_eq!(storage.increment_checked(StorageId::Individuals, "n", 1, 10), StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string()));
+        assert_eq!(storage.compare_and_swap(StorageId::Individuals, "n", None, b"1"), StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string()));
+
+        // Nothing changed underneath
+        let mut inner = storage.into_inner();
+        assert_eq!(inner.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(inner.count(StorageId::Individuals), StorageResult::Ok(1));
+    }
+
+    #[test]
+    fn test_read_only_config() {
+        let config = StorageConfig::ReadOnly {
+            inner: Box::new(StorageConfig::Memory),
+        };
+        let mut storage = DefaultStorageFactory::new().create_storage_from_config(config).unwrap();
+
+        assert_eq!(storage.put_value(StorageId::Tickets, "key", "value"), StorageResult::Error(READ_ONLY_VIEW_ERROR.to_string()));
+        assert_eq!(storage.get_value(StorageId::Tickets, "key"), StorageResult::NotFound);
+    }
+}
@@ -1,8 +1,14 @@
 use v_individual_model::onto::individual::Individual;
 use v_individual_model::onto::parser::parse_raw;
 use crate::common::{Storage, StorageId, StorageResult};
-use nng::{Message, Protocol, Socket};
+use nng::options::{Options, RecvBufferSize, RecvMaxSize, RecvTimeout, SendTimeout};
+use nng::{Error, Message, Protocol, Socket};
+use std::collections::HashMap;
 use std::str;
+use std::time::{Duration, Instant};
+
+/// Время, в течение которого count возвращает последний полученный ответ без запроса
+pub const DEFAULT_COUNT_CACHE_TTL: Duration = Duration::from_secs(1);
 
 // Remote client
 
@@ -10,6 +16,11 @@ pub struct StorageROClient {
     pub soc: Socket,
     pub addr: String,
     pub is_ready: bool,
+    protocol: Protocol,
+    dialed: bool,
+    max_retries: u32,
+    count_cache_ttl: Duration,
+    count_cache: HashMap<StorageId, (usize, Instant)>,
 }
 
 impl Default for StorageROClient {
@@ -18,109 +29,517 @@ impl Default for StorageROClient {
             soc: Socket::new(Protocol::Req0).unwrap(),
             addr: "".to_owned(),
             is_ready: false,
+            protocol: Protocol::Req0,
+            dialed: false,
+            max_retries: 0,
+            count_cache_ttl: DEFAULT_COUNT_CACHE_TTL,
+            count_cache: HashMap::new(),
         }
     }
 }
 
 impl StorageROClient {
     pub fn new(addr: &str) -> Self {
+        StorageROClient::with_protocol(addr, Protocol::Req0)
+    }
+
+    /// Клиент с заданным протоколом nng (по умолчанию используется Req0)
+    pub fn with_protocol(addr: &str, protocol: Protocol) -> Self {
         StorageROClient {
-            soc: Socket::new(Protocol::Req0).unwrap(),
+            soc: Socket::new(protocol).unwrap(),
             addr: addr.to_string(),
             is_ready: false,
+            protocol,
+            dialed: false,
+            max_retries: 0,
+            count_cache_ttl: DEFAULT_COUNT_CACHE_TTL,
+            count_cache: HashMap::new(),
+        }
+    }
+
+    /// Клиент с таймаутом ожидания ответа: при таймауте и других временных ошибках
+    /// запрос повторяется до max_retries раз, затем выполняется одно переподключение
+    /// с последней попыткой, после чего возвращается NotReady. Зависший storage_manager
+    /// обходится не дольше (max_retries + 2) * recv_timeout
+    pub fn with_options(addr: &str, recv_timeout: Duration, max_retries: u32) -> Self {
+        let mut client = StorageROClient::new(addr);
+        if let Err(e) = client.soc.set_opt::<RecvTimeout>(Some(recv_timeout)) {
+            error!("fail set recv timeout for storage_manager ({}), err={:?}", addr, e);
+        }
+        if let Err(e) = client.soc.set_opt::<SendTimeout>(Some(recv_timeout)) {
+            error!("fail set send timeout for storage_manager ({}), err={:?}", addr, e);
         }
+        client.max_retries = max_retries;
+        client
     }
 
+    /// Размер буфера приема (в сообщениях); задается до connect
+    pub fn set_recv_buffer_size(&mut self, size: i32) -> bool {
+        if let Err(e) = self.soc.set_opt::<RecvBufferSize>(size) {
+            error!("fail set recv buffer size for storage_manager ({}), err={:?}", self.addr, e);
+            return false;
+        }
+        true
+    }
+
+    /// Максимальный размер принимаемого сообщения в байтах (0 - без ограничения); задается до connect
+    pub fn set_max_message_size(&mut self, size: usize) -> bool {
+        if let Err(e) = self.soc.set_opt::<RecvMaxSize>(size) {
+            error!("fail set max message size for storage_manager ({}), err={:?}", self.addr, e);
+            return false;
+        }
+        true
+    }
+
+    /// Время жизни закешированного ответа count; Duration::ZERO отключает кеш
+    pub fn set_count_cache_ttl(&mut self, ttl: Duration) {
+        self.count_cache_ttl = ttl;
+        self.count_cache.clear();
+    }
+
+    /// Подключение к storage_manager; если сокет уже подключался раньше, он пересоздается
+    /// с теми же настройками, а не подключается повторно
     pub fn connect(&mut self) -> bool {
+        if self.dialed {
+            self.reopen_socket();
+        }
         if let Err(e) = self.soc.dial(&self.addr) {
             error!("fail connect to storage_manager ({}), err={:?}", self.addr, e);
             self.is_ready = false;
         } else {
             info!("success connect connect to storage_manager ({})", self.addr);
+            self.dialed = true;
             self.is_ready = true;
         }
         self.is_ready
     }
 
+    // Новый сокет вместо оборванного: таймауты и размеры буферов переносятся со старого
+    fn reopen_socket(&mut self) {
+        let soc = match Socket::new(self.protocol) {
+            Ok(soc) => soc,
+            Err(e) => {
+                error!("fail create socket for storage_manager ({}), err={:?}", self.addr, e);
+                return;
+            },
+        };
+        if let Ok(timeout) = self.soc.get_opt::<RecvTimeout>() {
+            let _ = soc.set_opt::<RecvTimeout>(timeout);
+        }
+        if let Ok(timeout) = self.soc.get_opt::<SendTimeout>() {
+            let _ = soc.set_opt::<SendTimeout>(timeout);
+        }
+        if let Ok(size) = self.soc.get_opt::<RecvBufferSize>() {
+            let _ = soc.set_opt::<RecvBufferSize>(size);
+        }
+        if let Ok(size) = self.soc.get_opt::<RecvMaxSize>() {
+            let _ = soc.set_opt::<RecvMaxSize>(size);
+        }
+        std::mem::replace(&mut self.soc, soc).close();
+        self.dialed = false;
+    }
+
     pub fn get_individual_from_db(&mut self, db_id: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
+        self.request(key_request("", db_id, id)).and_then(|msg| individual_reply(&msg, id, iraw))
+    }
+
+    /// Бинарное представление individual без разбора (запрос "i,<id>" / "t,<id>")
+    pub fn get_individual_raw_from_db(&mut self, db_id: StorageId, id: &str) -> StorageResult<Vec<u8>> {
+        self.request(key_request("", db_id, id)).map(|msg| msg.as_slice().to_vec())
+    }
+
+    /// Значение ключа как есть (запрос "v,i,<key>" / "v,t,<key>")
+    pub fn get_raw_value_from_db(&mut self, db_id: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.request(key_request("v,", db_id, key)).map(|msg| msg.as_slice().to_vec())
+    }
+
+    /// Количество записей в хранилище (запрос "c,i" / "c,t" / "c,a", ответ - десятичное число)
+    ///
+    /// Успешный ответ кешируется на count_cache_ttl, чтобы частые вызовы в цикле
+    /// не нагружали storage_manager; любая попытка записи через клиент сбрасывает кеш
+    pub fn count_in_db(&mut self, db_id: StorageId) -> StorageResult<usize> {
+        if let Some((count, at)) = self.count_cache.get(&db_id)
+            && at.elapsed() < self.count_cache_ttl
+        {
+            return StorageResult::Ok(*count);
+        }
+
+        let res = self.request(count_request(&db_id)).and_then(|msg| count_reply(&msg, &db_id));
+        if let StorageResult::Ok(count) = res
+            && !self.count_cache_ttl.is_zero()
+        {
+            self.count_cache.insert(db_id, (count, Instant::now()));
+        }
+        res
+    }
+
+    /// Проверка связи с storage_manager (запрос "p"); подходит любой ответ
+    pub fn ping(&mut self) -> StorageResult<()> {
+        match self.request("p".to_string()) {
+            StorageResult::Ok(_) | StorageResult::NotFound => StorageResult::Ok(()),
+            other => other.map(|_| ()),
+        }
+    }
+
+    // Отправка запроса и ожидание ответа; "[]" означает отсутствие ключа.
+    // Если соединение оборвалось (например, storage_manager перезапущен),
+    // выполняется одно переподключение и повтор запроса; счетчик повторов
+    // при этом не сбрасывается
+    fn request(&mut self, req: String) -> StorageResult<Message> {
         if !self.is_ready && !self.connect() {
             error!("REMOTE STORAGE: fail send to storage_manager, not ready");
             return StorageResult::NotReady;
         }
 
-        let req = if db_id == StorageId::Tickets {
-            Message::from(("t,".to_string() + id).as_bytes())
-        } else {
-            Message::from(("i,".to_string() + id).as_bytes())
-        };
+        let mut attempt = 0;
+        let mut reconnected = false;
+        loop {
+            match self.send_recv(req.as_bytes()) {
+                Ok(msg) => {
+                    if msg.as_slice() == b"[]" {
+                        return StorageResult::NotFound;
+                    }
+                    return StorageResult::Ok(msg);
+                },
+                Err(e) if is_transient(e) && attempt < self.max_retries => {
+                    attempt += 1;
+                    warn!("REMOTE STORAGE: retry {}/{} after err={:?}", attempt, self.max_retries, e);
+                },
+                Err(_) if !reconnected => {
+                    reconnected = true;
+                    self.is_ready = false;
+                    warn!("REMOTE STORAGE: connection to storage_manager ({}) lost, reconnect", self.addr);
+                    if !self.connect() {
+                        return StorageResult::NotReady;
+                    }
+                },
+                Err(_) => {
+                    self.is_ready = false;
+                    return StorageResult::NotReady;
+                },
+            }
+        }
+    }
 
-        if let Err(e) = self.soc.send(req) {
+    fn send_recv(&mut self, req: &[u8]) -> Result<Message, Error> {
+        if let Err((_, e)) = self.soc.send(Message::from(req)) {
             error!("REMOTE STORAGE: fail send to storage_manager, err={:?}", e);
-            return StorageResult::NotReady;
+            return Err(e);
         }
 
         // Wait for the response from the server.
-        match self.soc.recv() {
-            Err(e) => {
-                error!("REMOTE STORAGE: fail recv from main module, err={:?}", e);
-                StorageResult::NotReady
-            },
+        self.soc.recv().inspect_err(|e| error!("REMOTE STORAGE: fail recv from main module, err={:?}", e))
+    }
+
+    pub fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        self.count_in_db(storage)
+    }
+}
 
-            Ok(msg) => {
-                let data = msg.as_slice();
-                if data == b"[]" {
-                    return StorageResult::NotFound;
-                }
+// Ошибки, после которых запрос имеет смысл повторить
+pub(crate) fn is_transient(e: Error) -> bool {
+    matches!(e, Error::TimedOut | Error::TryAgain | Error::ConnectionReset | Error::ConnectionAborted)
+}
 
-                iraw.set_raw(data);
+// Запрос количества записей: "c,i", "c,t" или "c,a"
+pub(crate) fn count_request(db_id: &StorageId) -> String {
+    let namespace = match db_id {
+        StorageId::Individuals => "i",
+        StorageId::Tickets => "t",
+        StorageId::Az => "a",
+    };
+    format!("c,{}", namespace)
+}
 
-                if parse_raw(iraw).is_ok() {
-                    StorageResult::Ok(())
-                } else {
-                    error!("REMOTE STORAGE: fail parse binobj, len={}, uri=[{}]", iraw.get_raw_len(), id);
-                    StorageResult::UnprocessableEntity
-                }
-            },
-        }
+// Ответ на запрос количества: десятичное число
+pub(crate) fn count_reply(msg: &[u8], db_id: &StorageId) -> StorageResult<usize> {
+    match str::from_utf8(msg).ok().and_then(|s| s.trim().parse::<usize>().ok()) {
+        Some(count) => StorageResult::Ok(count),
+        None => {
+            error!("REMOTE STORAGE: fail parse count, len={}, db=[{:?}]", msg.len(), db_id);
+            StorageResult::UnprocessableEntity
+        },
     }
+}
+
+// Разбор individual из ответа на запрос по ключу
+pub(crate) fn individual_reply(msg: &[u8], id: &str, iraw: &mut Individual) -> StorageResult<()> {
+    iraw.set_raw(msg);
 
-    pub fn count(&mut self, _storage: StorageId) -> StorageResult<usize> {
-        StorageResult::Error("Remote storage does not support count".to_string())
+    if parse_raw(iraw).is_ok() {
+        StorageResult::Ok(())
+    } else {
+        error!("REMOTE STORAGE: fail parse binobj, len={}, uri=[{}]", iraw.get_raw_len(), id);
+        StorageResult::UnprocessableEntity
     }
 }
 
+// Запрос значения по ключу: "<kind>i,<key>" или "<kind>t,<key>".
+// storage_manager не различает individuals и az в запросах по ключу,
+// поэтому для Az используется префикс "i,"
+pub(crate) fn key_request(kind: &str, db_id: StorageId, key: &str) -> String {
+    let namespace = if db_id == StorageId::Tickets {
+        "t,"
+    } else {
+        "i,"
+    };
+    format!("{}{}{}", kind, namespace, key)
+}
+
 impl Storage for StorageROClient {
     fn get_individual(&mut self, storage: StorageId, id: &str, iraw: &mut Individual) -> StorageResult<()> {
         self.get_individual_from_db(storage, id, iraw)
     }
 
-    fn get_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<String> {
-        // Remote storage пока не поддерживает get_value
-        StorageResult::Error("Remote storage does not support get_value".to_string())
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        self.get_raw_value_from_db(storage, key).and_then(|data| match String::from_utf8(data) {
+            Ok(s) => StorageResult::Ok(s),
+            Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
+        })
     }
 
-    fn get_raw_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<Vec<u8>> {
-        // Remote storage пока не поддерживает get_raw_value
-        StorageResult::Error("Remote storage does not support get_raw_value".to_string())
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        self.get_raw_value_from_db(storage, key)
     }
 
     fn put_value(&mut self, _storage: StorageId, _key: &str, _val: &str) -> StorageResult<()> {
         // Remote storage пока не поддерживает put_value (read-only client)
+        self.count_cache.clear();
         StorageResult::Error("Remote storage is read-only".to_string())
     }
 
     fn put_raw_value(&mut self, _storage: StorageId, _key: &str, _val: Vec<u8>) -> StorageResult<()> {
         // Remote storage пока не поддерживает put_raw_value (read-only client)
+        self.count_cache.clear();
         StorageResult::Error("Remote storage is read-only".to_string())
     }
 
     fn remove_value(&mut self, _storage: StorageId, _key: &str) -> StorageResult<()> {
         // Remote storage пока не поддерживает remove_value (read-only client)
+        self.count_cache.clear();
         StorageResult::Error("Remote storage is read-only".to_string())
     }
 
-    fn count(&mut self, _storage: StorageId) -> StorageResult<usize> {
-        // Remote storage пока не поддерживает count
-        StorageResult::Error("Remote storage does not support count".to_string())
+    fn contains(&mut self, _storage: StorageId, _key: &str) -> StorageResult<bool> {
+        // Протокол пока не поддерживает проверку наличия ключа
+        StorageResult::Error("Remote storage does not support contains".to_string())
+    }
+
+    fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        self.count_in_db(storage)
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    fn health_check(&mut self) -> StorageResult<()> {
+        self.ping()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_protocol_connect() {
+        let addr = format!("inproc://test-remote-protocol-{}", std::process::id());
+
+        // Fake storage_manager speaking Pair0
+        let server = Socket::new(Protocol::Pair0).unwrap();
+        server.listen(&addr).unwrap();
+
+        let mut client = StorageROClient::with_protocol(&addr, Protocol::Pair0);
+        assert!(client.set_recv_buffer_size(16));
+        assert!(client.set_max_message_size(1024 * 1024));
+        assert!(client.connect());
+        assert!(client.is_ready);
+
+        let mut missing = StorageROClient::with_protocol(&format!("{}-missing", addr), Protocol::Pair0);
+        assert!(!missing.connect());
+        assert!(!missing.is_ready);
+
+        server.close();
+    }
+
+    #[test]
+    fn test_timeout_returns_not_ready() {
+        let addr = format!("inproc://test-remote-timeout-{}", std::process::id());
+        let timeout = Duration::from_millis(100);
+
+        // Nobody listens on the address
+        let mut dead = StorageROClient::with_options(&format!("{}-dead", addr), timeout, 2);
+        let started = std::time::Instant::now();
+        assert_eq!(dead.get_value(StorageId::Individuals, "key"), StorageResult::NotReady);
+        assert!(started.elapsed() < timeout);
+
+        // storage_manager accepts the request but never replies
+        let server = Socket::new(Protocol::Rep0).unwrap();
+        server.listen(&addr).unwrap();
+
+        let mut client = StorageROClient::with_options(&addr, timeout, 2);
+        let started = std::time::Instant::now();
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::NotReady);
+        let elapsed = started.elapsed();
+        assert!(elapsed >= timeout * 3, "expected at least three attempts, took {:?}", elapsed);
+        // Two retries and one attempt after the reconnect, the retries are not repeated
+        assert!(elapsed < timeout * 5, "expected at most four attempts, took {:?}", elapsed);
+
+        server.close();
+    }
+
+    // Mock storage_manager answering `requests` requests with `reply`, then shutting down
+    fn serve(addr: &str, requests: usize, reply: &'static [u8]) -> std::thread::JoinHandle<()> {
+        let server = Socket::new(Protocol::Rep0).unwrap();
+        server.listen(addr).unwrap();
+        std::thread::spawn(move || {
+            for _ in 0..requests {
+                server.recv().unwrap();
+                server.send(Message::from(reply)).unwrap();
+            }
+            server.close();
+        })
+    }
+
+    #[test]
+    fn test_reconnect_after_restart() {
+        let addr = format!("inproc://test-remote-reconnect-{}", std::process::id());
+
+        let mut client = StorageROClient::new(&addr);
+        let first = serve(&addr, 1, b"first");
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::Ok("first".to_string()));
+        first.join().unwrap();
+
+        // The connection is dropped, storage_manager comes back on the same address
+        let second = serve(&addr, 1, b"second");
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::Ok("second".to_string()));
+        assert!(client.is_ready);
+        second.join().unwrap();
+
+        // Nobody to reconnect to
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::NotReady);
+        assert!(!client.is_ready);
+    }
+
+    #[test]
+    fn test_reconnect_keeps_socket_options() {
+        let addr = format!("inproc://test-remote-reconnect-options-{}", std::process::id());
+        let timeout = Duration::from_millis(200);
+
+        let mut client = StorageROClient::with_options(&addr, timeout, 0);
+        assert!(client.set_max_message_size(1024));
+        let first = serve(&addr, 1, b"first");
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::Ok("first".to_string()));
+        first.join().unwrap();
+
+        // The reconnect goes through a new socket configured like the old one
+        let second = serve(&addr, 1, b"second");
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::Ok("second".to_string()));
+        second.join().unwrap();
+        assert_eq!(client.soc.get_opt::<RecvTimeout>().unwrap(), Some(timeout));
+        assert_eq!(client.soc.get_opt::<SendTimeout>().unwrap(), Some(timeout));
+        assert_eq!(client.soc.get_opt::<RecvMaxSize>().unwrap(), 1024);
+    }
+
+    #[test]
+    fn test_health_check() {
+        let addr = format!("inproc://test-remote-health-{}", std::process::id());
+
+        let mut unreachable = StorageROClient::new(&format!("{}-missing", addr));
+        assert_eq!(unreachable.health_check(), StorageResult::NotReady);
+
+        let server = serve(&addr, 1, b"[]");
+        let mut client = StorageROClient::new(&addr);
+        assert_eq!(client.health_check(), StorageResult::Ok(()));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_value_over_protocol() {
+        let addr = format!("inproc://test-remote-value-{}", std::process::id());
+
+        // Mock storage_manager: answers value requests for "v,t,key" only
+        let server = Socket::new(Protocol::Rep0).unwrap();
+        server.listen(&addr).unwrap();
+        let responder = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let req = server.recv().unwrap();
+                let reply: &[u8] = if req.as_slice() == b"v,t,key" {
+                    b"value"
+                } else {
+                    b"[]"
+                };
+                server.send(Message::from(reply)).unwrap();
+            }
+            server.close();
+        });
+
+        let mut client = StorageROClient::new(&addr);
+        assert_eq!(client.get_value(StorageId::Tickets, "key"), StorageResult::Ok("value".to_string()));
+        assert_eq!(client.get_raw_value(StorageId::Tickets, "key"), StorageResult::Ok(b"value".to_vec()));
+        assert_eq!(client.get_value(StorageId::Individuals, "key"), StorageResult::NotFound);
+
+        responder.join().unwrap();
+    }
+
+    #[test]
+    fn test_request_bytes() {
+        let addr = format!("inproc://test-remote-request-bytes-{}", std::process::id());
+
+        // Mock storage_manager recording every request
+        let server = Socket::new(Protocol::Rep0).unwrap();
+        server.listen(&addr).unwrap();
+        let recorder = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..9 {
+                requests.push(server.recv().unwrap().as_slice().to_vec());
+                server.send(Message::from(b"[]")).unwrap();
+            }
+            server.close();
+            requests
+        });
+
+        let mut client = StorageROClient::new(&addr);
+        client.set_count_cache_ttl(Duration::ZERO);
+        for db_id in [StorageId::Individuals, StorageId::Tickets, StorageId::Az] {
+            assert_eq!(client.get_individual_raw_from_db(db_id.clone(), "id"), StorageResult::NotFound);
+            assert_eq!(client.get_raw_value_from_db(db_id.clone(), "key"), StorageResult::NotFound);
+            assert_eq!(client.count_in_db(db_id), StorageResult::NotFound);
+        }
+
+        let expected: Vec<&[u8]> = vec![b"i,id", b"v,i,key", b"c,i", b"t,id", b"v,t,key", b"c,t", b"i,id", b"v,i,key", b"c,a"];
+        assert_eq!(recorder.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_count_fresh() {
+        let addr = format!("inproc://test-remote-count-{}", std::process::id());
+
+        let server = serve(&addr, 2, b"42");
+        let mut client = StorageROClient::new(&addr);
+        client.set_count_cache_ttl(Duration::ZERO);
+        assert_eq!(client.count(StorageId::Individuals), StorageResult::Ok(42));
+        assert_eq!(Storage::count(&mut client, StorageId::Individuals), StorageResult::Ok(42));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_count_cached_within_ttl() {
+        let addr = format!("inproc://test-remote-count-cache-{}", std::process::id());
+
+        // storage_manager answers a single request and goes away
+        let server = serve(&addr, 1, b"7");
+        let mut client = StorageROClient::new(&addr);
+        assert_eq!(client.count(StorageId::Az), StorageResult::Ok(7));
+        server.join().unwrap();
+        assert_eq!(client.count(StorageId::Az), StorageResult::Ok(7));
+
+        // Other sections are not cached
+        assert_eq!(client.count(StorageId::Individuals), StorageResult::NotReady);
+
+        // A write attempt drops the cached value
+        assert!(client.put_value(StorageId::Az, "key", "value").is_error());
+        assert_eq!(client.count(StorageId::Az), StorageResult::NotReady);
     }
 }
@@ -1,17 +1,24 @@
-use crate::common::{Storage, StorageMode};
+use crate::common::{Storage, StorageMode, LOCK_POISONED_ERROR};
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
 
 /// Абстрактная фабрика для создания различных типов хранилищ
 pub trait StorageFactory {
     fn create_storage(&self) -> Result<Box<dyn Storage>, StorageError>;
 }
 
-/// Ошибки создания хранилищ
+/// Ошибки создания хранилищ и операций с ними (см. StorageResult::into_result)
 #[derive(Debug)]
 pub enum StorageError {
     ConnectionFailed(String),
     InvalidConfiguration(String),
     IoError(String),
+    NotFound,
+    NotReady,
+    Unprocessable,
+    Backend(String),
 }
 
 impl fmt::Display for StorageError {
@@ -20,20 +27,34 @@ impl fmt::Display for StorageError {
             StorageError::ConnectionFailed(msg) => write!(f, "Connection failed: {}", msg),
             StorageError::InvalidConfiguration(msg) => write!(f, "Invalid configuration: {}", msg),
             StorageError::IoError(msg) => write!(f, "IO error: {}", msg),
+            StorageError::NotFound => write!(f, "Not found"),
+            StorageError::NotReady => write!(f, "Storage is not ready"),
+            StorageError::Unprocessable => write!(f, "Unprocessable entity"),
+            StorageError::Backend(msg) => write!(f, "Storage error: {}", msg),
         }
     }
 }
 
 impl std::error::Error for StorageError {}
 
-/// Конфигурация для различных типов хранилищ
-#[derive(Debug, Clone)]
+/// Конфигурация для различных типов хранилищ.
+/// С feature "serde" читается из TOML/JSON, тип задается полем type:
+/// `type = "lmdb"`, `path = "/data"`, `mode = "read_write"`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "snake_case"))]
 pub enum StorageConfig {
     Memory,
+    /// Хранилище, которое ничего не хранит (см. NoopStorage)
+    Noop,
     Lmdb {
         path: String,
         mode: StorageMode,
         max_read_counter_reopen: Option<u64>,
+        /// Порог для логирования медленных транзакций (None - измерение отключено)
+        slow_op_threshold: Option<Duration>,
+        /// Размер отображения окружения (None - 10GB). Окружение общее для пути,
+        /// действует размер, заданный при первом открытии
+        map_size_bytes: Option<usize>,
     },
     Mdbx {
         path: String,
@@ -49,6 +70,91 @@ pub enum StorageConfig {
         login: String,
         password: String,
     },
+    /// Представление только для чтения поверх любой другой конфигурации
+    ReadOnly {
+        inner: Box<StorageConfig>,
+    },
+    /// Кеш чтения в памяти (не больше capacity записей в секции) поверх другой конфигурации
+    Cached {
+        inner: Box<StorageConfig>,
+        capacity: usize,
+    },
+    /// Пользовательское хранилище, зарегистрированное в StorageRegistry под именем name
+    Custom {
+        name: String,
+    },
+}
+
+impl StorageConfig {
+    /// Конфигурация из переменных окружения:
+    /// V_STORAGE_TYPE (memory/lmdb/mdbx/remote),
+    /// для lmdb/mdbx - V_STORAGE_PATH, V_STORAGE_MODE (read_only/read_write, по умолчанию read_write)
+    /// и V_STORAGE_MAX_READ_COUNTER, для remote - V_STORAGE_REMOTE_ADDR
+    pub fn from_env() -> Result<Self, StorageError> {
+        match required_env("V_STORAGE_TYPE")?.as_str() {
+            "memory" => Ok(StorageConfig::Memory),
+            "lmdb" => Ok(StorageConfig::Lmdb {
+                path: required_env("V_STORAGE_PATH")?,
+                mode: mode_from_env()?,
+                max_read_counter_reopen: max_read_counter_from_env()?,
+                slow_op_threshold: None,
+                map_size_bytes: None,
+            }),
+            "mdbx" => Ok(StorageConfig::Mdbx {
+                path: required_env("V_STORAGE_PATH")?,
+                mode: mode_from_env()?,
+                max_read_counter_reopen: max_read_counter_from_env()?,
+            }),
+            "remote" => Ok(StorageConfig::Remote {
+                address: required_env("V_STORAGE_REMOTE_ADDR")?,
+            }),
+            other => Err(StorageError::InvalidConfiguration(format!(
+                "V_STORAGE_TYPE has unknown storage type [{}], expected memory, lmdb, mdbx or remote",
+                other
+            ))),
+        }
+    }
+}
+
+fn required_env(name: &str) -> Result<String, StorageError> {
+    match std::env::var(name) {
+        Ok(value) if !value.is_empty() => Ok(value),
+        _ => Err(StorageError::InvalidConfiguration(format!("Environment variable {} is not set", name))),
+    }
+}
+
+fn mode_from_env() -> Result<StorageMode, StorageError> {
+    match std::env::var("V_STORAGE_MODE").as_deref() {
+        Err(_) | Ok("") | Ok("read_write") => Ok(StorageMode::ReadWrite),
+        Ok("read_only") => Ok(StorageMode::ReadOnly),
+        Ok(other) => Err(StorageError::InvalidConfiguration(format!(
+            "V_STORAGE_MODE has unknown mode [{}], expected read_only or read_write",
+            other
+        ))),
+    }
+}
+
+fn max_read_counter_from_env() -> Result<Option<u64>, StorageError> {
+    match std::env::var("V_STORAGE_MAX_READ_COUNTER") {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map(Some)
+            .map_err(|_| StorageError::InvalidConfiguration(format!("V_STORAGE_MAX_READ_COUNTER is not a number: [{}]", value))),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(feature = "serde")]
+impl StorageConfig {
+    /// Разбор конфигурации из TOML
+    pub fn from_toml_str(s: &str) -> Result<Self, StorageError> {
+        toml::from_str(s).map_err(|e| StorageError::InvalidConfiguration(format!("Failed to parse TOML config: {}", e)))
+    }
+
+    /// Разбор конфигурации из JSON
+    pub fn from_json_str(s: &str) -> Result<Self, StorageError> {
+        serde_json::from_str(s).map_err(|e| StorageError::InvalidConfiguration(format!("Failed to parse JSON config: {}", e)))
+    }
 }
 
 /// Билдер для создания хранилищ через фабрику
@@ -66,15 +172,38 @@ impl StorageBuilder {
         self
     }
 
+    pub fn noop(mut self) -> Self {
+        self.config = Some(StorageConfig::Noop);
+        self
+    }
+
     pub fn lmdb(mut self, path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>) -> Self {
         self.config = Some(StorageConfig::Lmdb {
             path: path.to_string(),
             mode,
             max_read_counter_reopen,
+            slow_op_threshold: None,
+            map_size_bytes: None,
         });
         self
     }
 
+    /// Задает порог медленных транзакций для LMDB хранилища
+    pub fn slow_op_threshold(mut self, threshold: Duration) -> Self {
+        if let Some(StorageConfig::Lmdb { slow_op_threshold, .. }) = &mut self.config {
+            *slow_op_threshold = Some(threshold);
+        }
+        self
+    }
+
+    /// Задает размер отображения для LMDB хранилища
+    pub fn map_size(mut self, bytes: usize) -> Self {
+        if let Some(StorageConfig::Lmdb { map_size_bytes, .. }) = &mut self.config {
+            *map_size_bytes = Some(bytes);
+        }
+        self
+    }
+
     pub fn mdbx(mut self, path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>) -> Self {
         self.config = Some(StorageConfig::Mdbx {
             path: path.to_string(),
@@ -101,6 +230,31 @@ impl StorageBuilder {
         self
     }
 
+    /// Пользовательское хранилище из StorageRegistry
+    pub fn custom(mut self, name: &str) -> Self {
+        self.config = Some(StorageConfig::Custom {
+            name: name.to_string(),
+        });
+        self
+    }
+
+    /// Оборачивает текущую конфигурацию в представление только для чтения
+    pub fn read_only(mut self) -> Self {
+        self.config = self.config.map(|inner| StorageConfig::ReadOnly {
+            inner: Box::new(inner),
+        });
+        self
+    }
+
+    /// Добавляет к текущей конфигурации кеш чтения на capacity записей в секции
+    pub fn cached(mut self, capacity: usize) -> Self {
+        self.config = self.config.map(|inner| StorageConfig::Cached {
+            inner: Box::new(inner),
+            capacity,
+        });
+        self
+    }
+
     pub fn build(self) -> Result<Box<dyn Storage>, StorageError> {
         let config = self.config.ok_or_else(|| {
             StorageError::InvalidConfiguration("No storage type specified".to_string())
@@ -126,8 +280,10 @@ impl StorageBuilder {
 
     /// Создает generic LMDB хранилище
     pub fn build_lmdb_generic(self) -> Result<crate::vstorage::VLMDBStorage, StorageError> {
-        if let Some(StorageConfig::Lmdb { path, mode, max_read_counter_reopen }) = self.config {
-            Ok(crate::vstorage::VLMDBStorage::new(crate::lmdb_storage::LMDBStorage::new(&path, mode, max_read_counter_reopen)))
+        if let Some(StorageConfig::Lmdb { path, mode, max_read_counter_reopen, slow_op_threshold, map_size_bytes }) = self.config {
+            let mut storage = crate::lmdb_storage::LMDBStorage::try_new(&path, mode, max_read_counter_reopen, map_size_bytes)?;
+            storage.set_slow_op_threshold(slow_op_threshold);
+            Ok(crate::vstorage::VLMDBStorage::new(storage))
         } else {
             Err(StorageError::InvalidConfiguration(
                 "Builder is not configured for LMDB storage".to_string()
@@ -138,7 +294,8 @@ impl StorageBuilder {
     /// Создает generic MDBX хранилище
     pub fn build_mdbx_generic(self) -> Result<crate::vstorage::VMDBXStorage, StorageError> {
         if let Some(StorageConfig::Mdbx { path, mode, max_read_counter_reopen }) = self.config {
-            Ok(crate::vstorage::VMDBXStorage::new(crate::mdbx_storage::MDBXStorage::new(&path, mode, max_read_counter_reopen)))
+            let storage = crate::mdbx_storage::MDBXStorage::try_new(&path, mode, max_read_counter_reopen, crate::common::DEFAULT_OPEN_ATTEMPTS)?;
+            Ok(crate::vstorage::VMDBXStorage::new(storage))
         } else {
             Err(StorageError::InvalidConfiguration(
                 "Builder is not configured for MDBX storage".to_string()
@@ -176,6 +333,61 @@ impl Default for StorageBuilder {
     }
 }
 
+// ========================================================================================
+// STORAGE REGISTRY - ПОЛЬЗОВАТЕЛЬСКИЕ ХРАНИЛИЩА
+// ========================================================================================
+
+type CustomStorageFactory = Arc<dyn Fn() -> Box<dyn Storage> + Send + Sync>;
+
+static CUSTOM_FACTORIES: OnceLock<Mutex<HashMap<String, CustomStorageFactory>>> = OnceLock::new();
+
+fn custom_factories() -> Result<MutexGuard<'static, HashMap<String, CustomStorageFactory>>, StorageError> {
+    CUSTOM_FACTORIES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| StorageError::Backend(LOCK_POISONED_ERROR.to_string()))
+}
+
+/// Реестр пользовательских хранилищ (общий для процесса).
+/// Зарегистрированные фабрики создают хранилища для StorageConfig::Custom,
+/// так сторонние реализации Storage подключаются через те же конфигурации
+/// и VStorage::from_config
+pub struct StorageRegistry;
+
+impl StorageRegistry {
+    /// Регистрирует фабрику под именем name, заменяя ранее зарегистрированную.
+    /// Все методы реестра возвращают StorageError::Backend, если его блокировка
+    /// отравлена паникой другого потока
+    pub fn register<F>(name: &str, factory: F) -> Result<(), StorageError>
+    where
+        F: Fn() -> Box<dyn Storage> + Send + Sync + 'static,
+    {
+        if custom_factories()?.insert(name.to_string(), Arc::new(factory)).is_some() {
+            log::warn!("Storage factory [{}] replaced", name);
+        }
+        Ok(())
+    }
+
+    /// Удаляет фабрику, возвращает true если она была зарегистрирована
+    pub fn unregister(name: &str) -> Result<bool, StorageError> {
+        Ok(custom_factories()?.remove(name).is_some())
+    }
+
+    pub fn is_registered(name: &str) -> Result<bool, StorageError> {
+        Ok(custom_factories()?.contains_key(name))
+    }
+
+    /// Создает хранилище зарегистрированной фабрикой. Фабрика вызывается после
+    /// освобождения блокировки реестра, поэтому может сама обращаться к реестру
+    pub fn create(name: &str) -> Result<Box<dyn Storage>, StorageError> {
+        let factory = custom_factories()?.get(name).cloned();
+        match factory {
+            Some(factory) => Ok(factory()),
+            None => Err(StorageError::InvalidConfiguration(format!("Storage [{}] is not registered", name))),
+        }
+    }
+}
+
 // ========================================================================================
 // STORAGE PROVIDER - ФАБРИЧНЫЕ МЕТОДЫ ДЛЯ УДОБНОГО СОЗДАНИЯ
 // ========================================================================================
@@ -195,13 +407,21 @@ impl StorageProvider {
         Box::new(crate::memory_storage::MemoryStorage::new())
     }
 
-    /// Создает новое LMDB хранилище (dynamic dispatch)
+    /// Создает хранилище, которое ничего не хранит (dynamic dispatch)
+    pub fn noop() -> Box<dyn Storage> {
+        log::info!("Creating no-op storage");
+        Box::new(crate::noop_storage::NoopStorage::new())
+    }
+
+    /// Создает новое LMDB хранилище (dynamic dispatch).
+    /// Паникует, если окружение не удалось открыть; ошибку возвращает StorageBuilder::build
     pub fn lmdb(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>) -> Box<dyn Storage> {
         log::info!("Trying to connect to [LMDB], path: {}", db_path);
-        Box::new(crate::lmdb_storage::LMDBStorage::new(db_path, mode, max_read_counter_reopen))
+        Box::new(crate::lmdb_storage::LMDBStorage::new(db_path, mode, max_read_counter_reopen, None))
     }
 
-    /// Создает новое MDBX хранилище (dynamic dispatch)
+    /// Создает новое MDBX хранилище (dynamic dispatch).
+    /// Паникует, если базу не удалось открыть; ошибку возвращает StorageBuilder::build
     pub fn mdbx(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>) -> Box<dyn Storage> {
         log::info!("Trying to connect to [MDBX], path: {}", db_path);
         Box::new(crate::mdbx_storage::MDBXStorage::new(db_path, mode, max_read_counter_reopen))
@@ -259,7 +479,7 @@ impl StorageProvider {
     /// Создает generic LMDB хранилище
     pub fn lmdb_generic(db_path: &str, mode: StorageMode, max_read_counter_reopen: Option<u64>) -> crate::vstorage::VLMDBStorage {
         log::info!("Creating generic LMDB storage, path: {}", db_path);
-        crate::vstorage::VLMDBStorage::new(crate::lmdb_storage::LMDBStorage::new(db_path, mode, max_read_counter_reopen))
+        crate::vstorage::VLMDBStorage::new(crate::lmdb_storage::LMDBStorage::new(db_path, mode, max_read_counter_reopen, None))
     }
 
     /// Создает generic MDBX хранилище
@@ -295,11 +515,18 @@ impl DefaultStorageFactory {
             StorageConfig::Memory => {
                 Ok(StorageProvider::memory())
             }
-            StorageConfig::Lmdb { path, mode, max_read_counter_reopen } => {
-                Ok(StorageProvider::lmdb(&path, mode, max_read_counter_reopen))
+            StorageConfig::Noop => {
+                Ok(StorageProvider::noop())
+            }
+            StorageConfig::Lmdb { path, mode, max_read_counter_reopen, slow_op_threshold, map_size_bytes } => {
+                log::info!("Trying to connect to [LMDB], path: {}", path);
+                let mut storage = crate::lmdb_storage::LMDBStorage::try_new(&path, mode, max_read_counter_reopen, map_size_bytes)?;
+                storage.set_slow_op_threshold(slow_op_threshold);
+                Ok(Box::new(storage))
             }
             StorageConfig::Mdbx { path, mode, max_read_counter_reopen } => {
-                Ok(StorageProvider::mdbx(&path, mode, max_read_counter_reopen))
+                let storage = crate::mdbx_storage::MDBXStorage::try_new(&path, mode, max_read_counter_reopen, crate::common::DEFAULT_OPEN_ATTEMPTS)?;
+                Ok(Box::new(storage))
             }
             StorageConfig::Remote { address } => {
                 Ok(StorageProvider::remote(&address))
@@ -308,6 +535,18 @@ impl DefaultStorageFactory {
             StorageConfig::Tarantool { uri, login, password } => {
                 Ok(StorageProvider::tarantool(uri, &login, &password))
             }
+            StorageConfig::ReadOnly { inner } => {
+                let storage = self.create_storage_from_config(*inner)?;
+                Ok(Box::new(crate::read_only_storage::ReadOnlyStorage::new(storage)))
+            }
+            StorageConfig::Cached { inner, capacity } => {
+                let storage = self.create_storage_from_config(*inner)?;
+                Ok(Box::new(crate::cached_storage::CachedStorage::new(storage, capacity)))
+            }
+            StorageConfig::Custom { name } => {
+                log::info!("Creating custom storage [{}]", name);
+                StorageRegistry::create(&name)
+            }
         }
     }
 }
@@ -322,6 +561,139 @@ impl Default for DefaultStorageFactory {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_from_env() {
+        // All cases run in one test: the environment is shared between test threads
+        let vars = ["V_STORAGE_TYPE", "V_STORAGE_PATH", "V_STORAGE_MODE", "V_STORAGE_MAX_READ_COUNTER", "V_STORAGE_REMOTE_ADDR"];
+        let set = |pairs: &[(&str, &str)]| unsafe {
+            for name in vars {
+                std::env::remove_var(name);
+            }
+            for (name, value) in pairs {
+                std::env::set_var(name, value);
+            }
+        };
+
+        set(&[("V_STORAGE_TYPE", "memory")]);
+        assert_eq!(StorageConfig::from_env().unwrap(), StorageConfig::Memory);
+
+        set(&[("V_STORAGE_TYPE", "lmdb"), ("V_STORAGE_PATH", "/data/lmdb"), ("V_STORAGE_MAX_READ_COUNTER", "500")]);
+        assert_eq!(
+            StorageConfig::from_env().unwrap(),
+            StorageConfig::Lmdb {
+                path: "/data/lmdb".to_string(),
+                mode: StorageMode::ReadWrite,
+                max_read_counter_reopen: Some(500),
+                slow_op_threshold: None,
+                map_size_bytes: None,
+            }
+        );
+
+        set(&[("V_STORAGE_TYPE", "mdbx"), ("V_STORAGE_PATH", "/data/mdbx"), ("V_STORAGE_MODE", "read_only")]);
+        assert_eq!(
+            StorageConfig::from_env().unwrap(),
+            StorageConfig::Mdbx {
+                path: "/data/mdbx".to_string(),
+                mode: StorageMode::ReadOnly,
+                max_read_counter_reopen: None,
+            }
+        );
+
+        set(&[("V_STORAGE_TYPE", "remote"), ("V_STORAGE_REMOTE_ADDR", "tcp://127.0.0.1:8080")]);
+        assert_eq!(
+            StorageConfig::from_env().unwrap(),
+            StorageConfig::Remote {
+                address: "tcp://127.0.0.1:8080".to_string(),
+            }
+        );
+
+        // Errors name the offending variable
+        let error_cases: [(&[(&str, &str)], &str); 5] = [
+            (&[], "V_STORAGE_TYPE"),
+            (&[("V_STORAGE_TYPE", "redis")], "V_STORAGE_TYPE"),
+            (&[("V_STORAGE_TYPE", "lmdb")], "V_STORAGE_PATH"),
+            (&[("V_STORAGE_TYPE", "mdbx"), ("V_STORAGE_PATH", "/data"), ("V_STORAGE_MODE", "append")], "V_STORAGE_MODE"),
+            (&[("V_STORAGE_TYPE", "remote")], "V_STORAGE_REMOTE_ADDR"),
+        ];
+        for (pairs, name) in error_cases {
+            set(pairs);
+            match StorageConfig::from_env() {
+                Err(StorageError::InvalidConfiguration(msg)) => assert!(msg.contains(name), "{}", msg),
+                other => panic!("expected InvalidConfiguration for {}, got {:?}", name, other),
+            }
+        }
+
+        set(&[]);
+    }
+
+    #[cfg(feature = "serde")]
+    fn assert_config_roundtrip(config: StorageConfig) {
+        let toml = toml::to_string(&config).unwrap();
+        assert_eq!(StorageConfig::from_toml_str(&toml).unwrap(), config, "toml: {}", toml);
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(StorageConfig::from_json_str(&json).unwrap(), config, "json: {}", json);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_config_serde_roundtrip() {
+        assert_config_roundtrip(StorageConfig::Memory);
+        assert_config_roundtrip(StorageConfig::Noop);
+        assert_config_roundtrip(StorageConfig::Lmdb {
+            path: "/data/lmdb".to_string(),
+            mode: StorageMode::ReadWrite,
+            max_read_counter_reopen: Some(500),
+            slow_op_threshold: Some(Duration::from_millis(250)),
+            map_size_bytes: Some(1 << 30),
+        });
+        assert_config_roundtrip(StorageConfig::Mdbx {
+            path: "/data/mdbx".to_string(),
+            mode: StorageMode::ReadOnly,
+            max_read_counter_reopen: None,
+        });
+        assert_config_roundtrip(StorageConfig::Remote {
+            address: "tcp://127.0.0.1:8080".to_string(),
+        });
+        assert_config_roundtrip(StorageConfig::ReadOnly {
+            inner: Box::new(StorageConfig::Memory),
+        });
+        assert_config_roundtrip(StorageConfig::Cached {
+            inner: Box::new(StorageConfig::Remote {
+                address: "tcp://127.0.0.1:8080".to_string(),
+            }),
+            capacity: 1000,
+        });
+        #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+        assert_config_roundtrip(StorageConfig::Tarantool {
+            uri: "127.0.0.1:3301".to_string(),
+            login: "user".to_string(),
+            password: "pass".to_string(),
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_config_from_toml_file() {
+        let config = StorageConfig::from_toml_str("type = \"lmdb\"\npath = \"/data\"\nmode = \"read_write\"\n").unwrap();
+        assert_eq!(
+            config,
+            StorageConfig::Lmdb {
+                path: "/data".to_string(),
+                mode: StorageMode::ReadWrite,
+                max_read_counter_reopen: None,
+                slow_op_threshold: None,
+                map_size_bytes: None,
+            }
+        );
+
+        let config = StorageConfig::from_json_str(r#"{"type": "read_only", "inner": {"type": "mdbx", "path": "/data", "mode": "read_only"}}"#).unwrap();
+        assert!(matches!(config, StorageConfig::ReadOnly { .. }));
+
+        assert!(matches!(StorageConfig::from_toml_str("type = \"unknown\""), Err(StorageError::InvalidConfiguration(_))));
+        assert!(matches!(StorageConfig::from_json_str("{\"type\": \"lmdb\"}"), Err(StorageError::InvalidConfiguration(_))));
+    }
+
     #[test]
     fn test_storage_builder_memory() {
         let storage = StorageBuilder::new()
@@ -348,11 +720,34 @@ mod tests {
 
     #[test]
     fn test_generic_lmdb_builder() {
+        let temp_dir = format!("/tmp/test-generic-lmdb-builder-{}", std::process::id());
+        // Read-only open needs a database created by a ReadWrite instance
         let storage = StorageBuilder::new()
-            .lmdb("/tmp/test", StorageMode::ReadOnly, None)
+            .lmdb(&temp_dir, StorageMode::ReadWrite, None)
             .build_lmdb_generic();
+        assert!(storage.is_ok());
+        drop(storage);
 
+        let storage = StorageBuilder::new()
+            .lmdb(&temp_dir, StorageMode::ReadOnly, None)
+            .build_lmdb_generic();
         assert!(storage.is_ok());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_lmdb_read_only_missing_path_fails_fast() {
+        let temp_dir = format!("/tmp/test-lmdb-read-only-missing-{}", std::process::id());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+
+        let started = std::time::Instant::now();
+        let storage = StorageBuilder::new()
+            .lmdb(&temp_dir, StorageMode::ReadOnly, None)
+            .build();
+        assert!(matches!(storage, Err(StorageError::IoError(_))));
+        assert!(started.elapsed() < crate::common::OPEN_RETRY_DELAY, "missing path must not be retried");
+        assert!(!std::path::Path::new(&temp_dir).exists());
     }
 
     #[test]
@@ -374,6 +769,24 @@ mod tests {
         assert!(storage.is_ok());
     }
 
+    #[test]
+    fn test_lmdb_bad_path_is_error() {
+        let storage = StorageBuilder::new()
+            .lmdb("/proc/nonexistent", StorageMode::ReadWrite, None)
+            .build();
+        assert!(matches!(storage, Err(StorageError::IoError(_))));
+
+        let config = StorageConfig::Lmdb {
+            path: "/tmp/test-lmdb-zero-map".to_string(),
+            mode: StorageMode::ReadWrite,
+            max_read_counter_reopen: None,
+            slow_op_threshold: None,
+            map_size_bytes: Some(0),
+        };
+        let storage = DefaultStorageFactory::new().create_storage_from_config(config);
+        assert!(matches!(storage, Err(StorageError::InvalidConfiguration(_))));
+    }
+
     #[test]
     fn test_generic_builder_wrong_config() {
         let storage = StorageBuilder::new()
@@ -423,8 +836,10 @@ mod tests {
 
     #[test]
     fn test_storage_provider_lmdb() {
-        let _storage = StorageProvider::lmdb("/tmp/test", StorageMode::ReadOnly, None);
+        let temp_dir = format!("/tmp/test-storage-provider-lmdb-{}", std::process::id());
+        let _storage = StorageProvider::lmdb(&temp_dir, StorageMode::ReadWrite, None);
         // Проверяем что создание прошло без panic
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
@@ -439,4 +854,89 @@ mod tests {
         let _storage = StorageProvider::tarantool("127.0.0.1:3301".to_string(), "user", "pass");
         // Проверяем что создание прошло без panic
     }
+
+    #[test]
+    fn test_storage_result_into_result() {
+        use crate::common::StorageResult;
+
+        assert_eq!(StorageResult::Ok(5).into_result().unwrap(), 5);
+        assert!(matches!(StorageResult::<()>::NotFound.into_result(), Err(StorageError::NotFound)));
+        assert!(matches!(StorageResult::<()>::NotReady.into_result(), Err(StorageError::NotReady)));
+        assert!(matches!(StorageResult::<()>::UnprocessableEntity.into_result(), Err(StorageError::Unprocessable)));
+        match StorageResult::<()>::Error("disk full".to_string()).into_result() {
+            Err(StorageError::Backend(msg)) => assert_eq!(msg, "disk full"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(StorageResult::Ok("v").ok(), Some("v"));
+        assert_eq!(StorageResult::<()>::NotFound.ok(), None);
+        assert_eq!(StorageResult::<()>::Error("e".to_string()).ok(), None);
+    }
+
+    #[test]
+    fn test_storage_result_unwrap() {
+        use crate::common::StorageResult;
+
+        assert_eq!(StorageResult::Ok(5).unwrap(), 5);
+        assert_eq!(StorageResult::Ok("v").expect("value must be present"), "v");
+
+        let panic = std::panic::catch_unwind(|| StorageResult::<()>::Error("disk full".to_string()).unwrap()).unwrap_err();
+        assert_eq!(panic.downcast_ref::<String>().unwrap(), "called `StorageResult::unwrap()` on a `Error(\"disk full\")` value");
+    }
+
+    #[test]
+    #[should_panic(expected = "ticket must exist: NotFound")]
+    fn test_storage_result_expect_not_found() {
+        use crate::common::StorageResult;
+
+        StorageResult::<String>::NotFound.expect("ticket must exist");
+    }
+
+    #[test]
+    fn test_storage_result_from_option_and_result() {
+        use crate::common::StorageResult;
+
+        assert_eq!(StorageResult::from(Some(7)), StorageResult::Ok(7));
+        assert_eq!(StorageResult::<i32>::from(None), StorageResult::NotFound);
+
+        assert_eq!(StorageResult::from(Ok::<_, StorageError>("v")), StorageResult::Ok("v"));
+        let res: StorageResult<i32> = "x".parse::<i32>().into();
+        assert_eq!(res, StorageResult::Error("invalid digit found in string".to_string()));
+        let res: StorageResult<()> = Err(StorageError::IoError("disk full".to_string())).into();
+        assert_eq!(res, StorageResult::Error("IO error: disk full".to_string()));
+    }
+
+    #[test]
+    fn test_storage_result_ok_or_else_propagation() {
+        use crate::common::StorageResult;
+
+        #[derive(Debug, PartialEq)]
+        struct AppError(StorageResult<()>);
+
+        fn load(res: StorageResult<String>) -> Result<usize, AppError> {
+            let value = res.ok_or_else(AppError)?;
+            Ok(value.len())
+        }
+
+        assert_eq!(load(StorageResult::Ok("abc".to_string())), Ok(3));
+        assert_eq!(load(StorageResult::NotFound), Err(AppError(StorageResult::NotFound)));
+        assert_eq!(load(StorageResult::NotReady), Err(AppError(StorageResult::NotReady)));
+        assert_eq!(load(StorageResult::UnprocessableEntity), Err(AppError(StorageResult::UnprocessableEntity)));
+        assert_eq!(load(StorageResult::Error("io".to_string())), Err(AppError(StorageResult::Error("io".to_string()))));
+    }
+
+    #[test]
+    fn test_registry_factory_may_use_registry() {
+        let inner = "test-registry-inner";
+        let outer = "test-registry-outer";
+        StorageRegistry::register(inner, || Box::new(crate::memory_storage::MemoryStorage::new())).unwrap();
+        // The outer factory goes through the registry itself, so the lock must not be held while it runs
+        StorageRegistry::register(outer, move || StorageRegistry::create(inner).unwrap()).unwrap();
+
+        assert!(StorageRegistry::create(outer).is_ok());
+        assert!(StorageRegistry::unregister(outer).unwrap());
+        assert!(!StorageRegistry::unregister(outer).unwrap());
+        assert!(StorageRegistry::unregister(inner).unwrap());
+        assert!(!StorageRegistry::is_registered(inner).unwrap());
+    }
 } 
\ No newline at end of file
@@ -1,182 +1,661 @@
 use super::tt_wrapper::{Client, ClientConfig, IteratorType};
 use v_individual_model::onto::individual::Individual;
 use v_individual_model::onto::parser::parse_raw;
-use crate::common::{Storage, StorageId, StorageResult};
+use crate::async_storage::AsyncStorage;
+use crate::common::{utf8_value, Storage, StorageId, StorageResult};
+use crate::storage_factory::StorageError;
 use crate::RuntimeWrapper;
-use std::str;
+use std::io;
 
-pub struct TTStorage {
+pub struct TTStorage<C = Client> {
     rt: RuntimeWrapper,
-    client: Client,
+    client: C,
+    config: TTStorageConfig,
+}
+
+/// Requests TTStorage sends to Tarantool. Implemented for the rusty_tarantool client;
+/// other implementations (e.g. an in-memory mock) let the storage run without a server.
+#[allow(async_fn_in_trait)]
+pub trait TTClient {
+    /// Raw response of a primary key select
+    async fn select_key(&self, space: i32, key: &str) -> io::Result<Vec<u8>>;
+    async fn replace_value(&self, space: i32, key: &str, val: &str) -> io::Result<()>;
+    /// Replace with an already msgpack-encoded tuple
+    async fn replace_tuple(&self, space: i32, tuple: Vec<u8>) -> io::Result<()>;
+    async fn delete_key(&self, space: i32, key: &str) -> io::Result<()>;
+    /// Number of tuples in the space
+    async fn space_len(&self, space: i32) -> io::Result<u64>;
+    async fn ping(&self) -> io::Result<()>;
+}
+
+impl TTClient for Client {
+    async fn select_key(&self, space: i32, key: &str) -> io::Result<Vec<u8>> {
+        Client::select(self, space, 0, &(key,), 0, 100, IteratorType::EQ).await.map(|v| v.data.to_vec())
+    }
+
+    async fn replace_value(&self, space: i32, key: &str, val: &str) -> io::Result<()> {
+        Client::replace(self, space, &(key, val)).await.map(|_| ())
+    }
+
+    async fn replace_tuple(&self, space: i32, tuple: Vec<u8>) -> io::Result<()> {
+        Client::replace_raw(self, space, tuple).await.map(|_| ())
+    }
+
+    async fn delete_key(&self, space: i32, key: &str) -> io::Result<()> {
+        Client::delete(self, space, &(key,)).await.map(|_| ())
+    }
+
+    async fn space_len(&self, space: i32) -> io::Result<u64> {
+        Client::eval(self, count_expr(space), &(0,)).await?.decode::<(u64,)>().map(|res| res.0)
+    }
+
+    async fn ping(&self) -> io::Result<()> {
+        Client::eval(self, "return box.info.status".to_string(), &(0,)).await.map(|_| ())
+    }
 }
 
 const INDIVIDUALS_SPACE_ID: i32 = 512;
 const TICKETS_SPACE_ID: i32 = 513;
 const AZ_SPACE_ID: i32 = 514;
 
-impl TTStorage {
-    pub fn new(tt_uri: String, login: &str, pass: &str) -> TTStorage {
-        TTStorage {
-            rt: RuntimeWrapper::new(),
-            client: ClientConfig::new(tt_uri, login, pass).set_timeout_time_ms(1000).set_reconnect_time_ms(10000).build(),
+/// Request timeout and reconnect window used by new
+const DEFAULT_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_RECONNECT_MS: u64 = 10000;
+
+/// Spaces the storage works with: (name, section)
+const SPACES: [(&str, StorageId); 3] = [("INDIVIDUALS", StorageId::Individuals), ("TICKETS", StorageId::Tickets), ("AZ", StorageId::Az)];
+
+/// What with_config does about configured spaces missing on the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TTSchemaCheck {
+    /// No check, the spaces are assumed to exist
+    #[default]
+    Skip,
+    /// Fail with an error listing the missing spaces
+    Verify,
+    /// Create the missing spaces with the configured ids
+    AutoCreate,
+}
+
+/// Settings every TTStorage constructor is built on: ids of the Tarantool spaces
+/// backing each section, the client timeouts and the schema check. The default
+/// matches the ids the spaces are created with by ensure_schema (512, 513, 514)
+/// and the timeouts of new, and skips the check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TTStorageConfig {
+    pub individuals_space: i32,
+    pub tickets_space: i32,
+    pub az_space: i32,
+    /// Request timeout
+    pub timeout_ms: u64,
+    /// Reconnect window
+    pub reconnect_ms: u64,
+    pub schema: TTSchemaCheck,
+}
+
+impl Default for TTStorageConfig {
+    fn default() -> Self {
+        TTStorageConfig {
+            individuals_space: INDIVIDUALS_SPACE_ID,
+            tickets_space: TICKETS_SPACE_ID,
+            az_space: AZ_SPACE_ID,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            reconnect_ms: DEFAULT_RECONNECT_MS,
+            schema: TTSchemaCheck::Skip,
         }
     }
 }
 
-impl Storage for TTStorage {
-    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
-        let space = if storage == StorageId::Tickets {
-            TICKETS_SPACE_ID
-        } else if storage == StorageId::Az {
-            AZ_SPACE_ID
-        } else {
-            INDIVIDUALS_SPACE_ID
+impl TTStorageConfig {
+    /// Space of the given storage
+    pub fn space_id(&self, storage: &StorageId) -> i32 {
+        match storage {
+            StorageId::Tickets => self.tickets_space,
+            StorageId::Az => self.az_space,
+            StorageId::Individuals => self.individuals_space,
+        }
+    }
+}
+
+impl TTStorage {
+    pub fn new(tt_uri: String, login: &str, pass: &str) -> TTStorage {
+        TTStorage::connect(tt_uri, login, pass, TTStorageConfig::default())
+    }
+
+    /// Connect with a custom request timeout and reconnect window, e.g. for slow networks
+    pub fn with_timeouts(tt_uri: String, login: &str, pass: &str, timeout_ms: u64, reconnect_ms: u64) -> TTStorage {
+        let config = TTStorageConfig {
+            timeout_ms,
+            reconnect_ms,
+            ..TTStorageConfig::default()
         };
+        TTStorage::connect(tt_uri, login, pass, config)
+    }
 
-        let key = (uri,);
-
-        match self.rt.block_on(self.client.select(space, 0, &key, 0, 100, IteratorType::EQ)) {
-            Ok(v) => {
-                if !v.data.is_empty() {
-                    iraw.set_raw(&v.data[5..]);
-                    if parse_raw(iraw).is_ok() {
-                        return StorageResult::Ok(());
-                    } else {
-                        return StorageResult::UnprocessableEntity;
-                    }
-                }
-                StorageResult::NotFound
+    /// Connect to an instance whose spaces were created with non-default ids, see with_config
+    pub fn with_spaces(tt_uri: String, login: &str, pass: &str, spaces: TTStorageConfig) -> Result<TTStorage, StorageError> {
+        TTStorage::with_config(tt_uri, login, pass, spaces)
+    }
+
+    /// Connect and verify that all default spaces exist, see ensure_schema.
+    /// Use with_config to check spaces with non-default ids.
+    pub fn new_with_schema(tt_uri: String, login: &str, pass: &str, auto_create_spaces: bool) -> Result<TTStorage, StorageError> {
+        let config = TTStorageConfig {
+            schema: if auto_create_spaces {
+                TTSchemaCheck::AutoCreate
+            } else {
+                TTSchemaCheck::Verify
             },
-            Err(_) => StorageResult::UnprocessableEntity,
+            ..TTStorageConfig::default()
+        };
+        TTStorage::with_config(tt_uri, login, pass, config)
+    }
+
+    /// Connect with the given space ids and timeouts, e.g. to an instance whose
+    /// spaces were created with non-default ids, then run the configured schema check
+    pub fn with_config(tt_uri: String, login: &str, pass: &str, config: TTStorageConfig) -> Result<TTStorage, StorageError> {
+        let mut storage = TTStorage::connect(tt_uri, login, pass, config);
+        match config.schema {
+            TTSchemaCheck::Skip => {},
+            TTSchemaCheck::Verify => storage.ensure_schema(false)?,
+            TTSchemaCheck::AutoCreate => storage.ensure_schema(true)?,
         }
+        Ok(storage)
     }
 
-    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
-        let space = if storage == StorageId::Tickets {
-            TICKETS_SPACE_ID
-        } else if storage == StorageId::Az {
-            AZ_SPACE_ID
-        } else {
-            INDIVIDUALS_SPACE_ID
-        };
+    // The client connects lazily, so building the storage never fails
+    fn connect(tt_uri: String, login: &str, pass: &str, config: TTStorageConfig) -> TTStorage {
+        let client = ClientConfig::new(tt_uri, login, pass).set_timeout_time_ms(config.timeout_ms).set_reconnect_time_ms(config.reconnect_ms).build();
+        TTStorage::with_client(client, config)
+    }
 
-        let key_tuple = (key,);
+    /// Request timeout the client was built with
+    pub fn timeout_ms(&self) -> u64 {
+        self.config.timeout_ms
+    }
 
-        match self.rt.block_on(self.client.select(space, 0, &key_tuple, 0, 100, IteratorType::EQ)) {
-            Ok(v) => {
-                match std::str::from_utf8(&v.data[5..]) {
-                    Ok(s) => StorageResult::Ok(s.to_string()),
-                    Err(_) => StorageResult::Error("Invalid UTF-8 data".to_string()),
-                }
+    /// Reconnect window the client was built with
+    pub fn reconnect_ms(&self) -> u64 {
+        self.config.reconnect_ms
+    }
+}
+
+impl<C: TTClient> TTStorage<C> {
+    /// Storage on top of an already built client; the timeouts of config are not applied to it
+    pub fn with_client(client: C, config: TTStorageConfig) -> TTStorage<C> {
+        TTStorage {
+            rt: RuntimeWrapper::new(),
+            client,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &TTStorageConfig {
+        &self.config
+    }
+}
+
+impl TTStorage {
+    /// Check that all configured spaces exist. Missing spaces are created when
+    /// auto_create_spaces is set, otherwise an error listing them is returned,
+    /// instead of failing later with obscure count/select errors.
+    pub fn ensure_schema(&mut self, auto_create_spaces: bool) -> Result<(), StorageError> {
+        let config = self.config;
+        let missing = missing_spaces(&config, |id| self.space_exists(id))?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !auto_create_spaces {
+            return Err(missing_spaces_error(&missing));
+        }
+
+        for (name, storage) in SPACES.iter().filter(|(name, _)| missing.contains(name)) {
+            let id = self.config.space_id(storage);
+            self.create_space(name, id)?;
+            info!("TTStorage: created missing space [{}], id={}", name, id);
+        }
+        Ok(())
+    }
+
+    fn space_exists(&mut self, id: i32) -> Result<bool, StorageError> {
+        match self.rt.block_on(self.client.eval(format!("return box.space[{}] ~= nil", id), &(0,))) {
+            Ok(response) => match response.decode::<(bool,)>() {
+                Ok(res) => Ok(res.0),
+                Err(e) => Err(StorageError::ConnectionFailed(format!("Failed to decode space check for {}: {:?}", id, e))),
             },
+            Err(e) => Err(StorageError::ConnectionFailed(format!("Failed to check space {}: {:?}", id, e))),
+        }
+    }
+
+    fn create_space(&mut self, name: &str, id: i32) -> Result<(), StorageError> {
+        let expr = format!(
+            "box.schema.space.create('{name}', {{id = {id}, if_not_exists = true}}) \
+             box.space.{name}:create_index('primary', {{type = 'TREE', parts = {{1, 'string'}}, if_not_exists = true}})",
+            name = name,
+            id = id
+        );
+        match self.rt.block_on(self.client.eval(expr, &(0,))) {
+            Ok(_) => Ok(()),
             Err(e) => {
-                error!("TTStorage: fail get [{}] from tarantool, err={:?}", key, e);
-                StorageResult::NotFound
+                error!("TTStorage: failed to create space [{}], err={:?}", name, e);
+                Err(StorageError::ConnectionFailed(format!("Failed to create space {}: {:?}", name, e)))
             },
         }
     }
+}
 
-    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
-        let space = if storage == StorageId::Tickets {
-            TICKETS_SPACE_ID
-        } else if storage == StorageId::Az {
-            AZ_SPACE_ID
-        } else {
-            INDIVIDUALS_SPACE_ID
-        };
+/// Lua expression returning the number of tuples in the space
+fn count_expr(space: i32) -> String {
+    format!("return box.space[{}]:len()", space)
+}
 
-        let key_tuple = (key,);
+/// Length of the msgpack header preceding the payload in a select response
+const TUPLE_HEADER_LEN: usize = 5;
 
-        match self.rt.block_on(self.client.select(space, 0, &key_tuple, 0, 100, IteratorType::EQ)) {
-            Ok(v) => StorageResult::Ok(v.data[5..].to_vec()),
-            Err(e) => {
-                error!("TTStorage: fail get raw [{}] from tarantool, err={:?}", key, e);
-                StorageResult::NotFound
-            },
+/// Payload of a select response, None for empty or truncated responses
+fn tuple_payload(data: &[u8]) -> Option<&[u8]> {
+    if data.len() < TUPLE_HEADER_LEN {
+        None
+    } else {
+        Some(&data[TUPLE_HEADER_LEN..])
+    }
+}
+
+/// Names of spaces whose configured id the probe reports as missing
+fn missing_spaces(spaces: &TTStorageConfig, mut probe: impl FnMut(i32) -> Result<bool, StorageError>) -> Result<Vec<&'static str>, StorageError> {
+    let mut missing = Vec::new();
+    for (name, storage) in SPACES.iter() {
+        if !probe(spaces.space_id(storage))? {
+            missing.push(*name);
         }
     }
+    Ok(missing)
+}
+
+fn missing_spaces_error(missing: &[&str]) -> StorageError {
+    StorageError::InvalidConfiguration(format!(
+        "Tarantool spaces do not exist: {} (create them or enable auto_create_spaces)",
+        missing.join(", ")
+    ))
+}
+
+/// Maps the select response of get_individual; a transport error yields `unreachable`
+/// (the sync impl keeps its historical results, the async one reports NotReady)
+fn individual_response(res: io::Result<Vec<u8>>, uri: &str, iraw: &mut Individual, unreachable: StorageResult<()>) -> StorageResult<()> {
+    match res {
+        Ok(data) => match tuple_payload(&data) {
+            Some(payload) => {
+                iraw.set_raw(payload);
+                if parse_raw(iraw).is_ok() {
+                    StorageResult::Ok(())
+                } else {
+                    StorageResult::UnprocessableEntity
+                }
+            },
+            None => StorageResult::NotFound,
+        },
+        Err(e) => {
+            error!("TTStorage: fail get individual [{}] from tarantool, err={:?}", uri, e);
+            unreachable
+        },
+    }
+}
+
+/// Maps the select response of get_raw_value and get_value
+fn value_response(res: io::Result<Vec<u8>>, key: &str, unreachable: StorageResult<Vec<u8>>) -> StorageResult<Vec<u8>> {
+    match res {
+        Ok(data) => match tuple_payload(&data) {
+            Some(payload) => StorageResult::Ok(payload.to_vec()),
+            None => StorageResult::NotFound,
+        },
+        Err(e) => {
+            error!("TTStorage: fail get [{}] from tarantool, err={:?}", key, e);
+            unreachable
+        },
+    }
+}
+
+fn put_response(res: io::Result<()>, storage: &StorageId, raw: bool) -> StorageResult<()> {
+    match res {
+        Ok(_) => StorageResult::Ok(()),
+        Err(e) if raw => {
+            error!("tarantool: fail replace raw, db [{:?}], err = {:?}", storage, e);
+            StorageResult::Error(format!("Failed to put raw value: {:?}", e))
+        },
+        Err(e) => {
+            error!("tarantool: fail replace, db [{:?}], err = {:?}", storage, e);
+            StorageResult::Error(format!("Failed to put value: {:?}", e))
+        },
+    }
+}
+
+fn remove_response(res: io::Result<()>, storage: &StorageId, unreachable: StorageResult<()>) -> StorageResult<()> {
+    match res {
+        Ok(_) => StorageResult::Ok(()),
+        Err(e) => {
+            error!("tarantool: fail remove, db [{:?}], err = {:?}", storage, e);
+            unreachable
+        },
+    }
+}
+
+fn count_response(res: io::Result<u64>, storage: &StorageId, space: i32) -> StorageResult<usize> {
+    match res {
+        Ok(count) => StorageResult::Ok(count as usize),
+        Err(e) => {
+            error!("failed to count the number of records: db [{:?}], space {}, err = {:?}", storage, space, e);
+            StorageResult::Error(format!("Failed to count records: {:?}", e))
+        },
+    }
+}
+
+impl<C: TTClient> Storage for TTStorage<C> {
+    fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        let res = self.rt.block_on(self.client.select_key(self.config.space_id(&storage), uri));
+        individual_response(res, uri, iraw, StorageResult::UnprocessableEntity)
+    }
+
+    fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(Storage::get_raw_value(self, storage, key))
+    }
+
+    fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let res = self.rt.block_on(self.client.select_key(self.config.space_id(&storage), key));
+        value_response(res, key, StorageResult::NotFound)
+    }
 
     fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
-        let space = if storage == StorageId::Tickets {
-            TICKETS_SPACE_ID
-        } else if storage == StorageId::Az {
-            AZ_SPACE_ID
-        } else {
-            INDIVIDUALS_SPACE_ID
-        };
+        let res = self.rt.block_on(self.client.replace_value(self.config.space_id(&storage), key, val));
+        put_response(res, &storage, false)
+    }
+
+    fn put_raw_value(&mut self, storage: StorageId, _key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let res = self.rt.block_on(self.client.replace_tuple(self.config.space_id(&storage), val));
+        put_response(res, &storage, true)
+    }
+
+    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let res = self.rt.block_on(self.client.delete_key(self.config.space_id(&storage), key));
+        remove_response(res, &storage, StorageResult::NotFound)
+    }
+
+    fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        let space = self.config.space_id(&storage);
+        let res = self.rt.block_on(self.client.space_len(space));
+        count_response(res, &storage, space)
+    }
 
-        let tuple = (key, val);
+    fn is_persistent(&self) -> bool {
+        true
+    }
 
-        match self.rt.block_on(self.client.replace(space, &tuple)) {
+    fn health_check(&mut self) -> StorageResult<()> {
+        match self.rt.block_on(self.client.ping()) {
             Ok(_) => StorageResult::Ok(()),
             Err(e) => {
-                error!("tarantool: fail replace, db [{:?}], err = {:?}", storage, e);
-                StorageResult::Error(format!("Failed to put value: {:?}", e))
+                error!("TTStorage: health check failed, err={:?}", e);
+                StorageResult::NotReady
             },
         }
     }
+}
 
-    fn put_raw_value(&mut self, storage: StorageId, _key: &str, val: Vec<u8>) -> StorageResult<()> {
-        let space = if storage == StorageId::Tickets {
-            TICKETS_SPACE_ID
-        } else if storage == StorageId::Az {
-            AZ_SPACE_ID
-        } else {
-            INDIVIDUALS_SPACE_ID
-        };
+/// Same requests as the Storage impl, awaited on the caller's runtime instead of block_on
+impl<C: TTClient> AsyncStorage for TTStorage<C> {
+    async fn get_individual(&mut self, storage: StorageId, uri: &str, iraw: &mut Individual) -> StorageResult<()> {
+        let res = self.client.select_key(self.config.space_id(&storage), uri).await;
+        individual_response(res, uri, iraw, StorageResult::NotReady)
+    }
 
-        match self.rt.block_on(self.client.replace_raw(space, val)) {
-            Ok(_) => StorageResult::Ok(()),
-            Err(e) => {
-                error!("tarantool: fail replace raw, db [{:?}], err = {:?}", storage, e);
-                StorageResult::Error(format!("Failed to put raw value: {:?}", e))
-            },
+    async fn get_value(&mut self, storage: StorageId, key: &str) -> StorageResult<String> {
+        utf8_value(AsyncStorage::get_raw_value(self, storage, key).await)
+    }
+
+    async fn get_raw_value(&mut self, storage: StorageId, key: &str) -> StorageResult<Vec<u8>> {
+        let res = self.client.select_key(self.config.space_id(&storage), key).await;
+        value_response(res, key, StorageResult::NotReady)
+    }
+
+    async fn put_value(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<()> {
+        let res = self.client.replace_value(self.config.space_id(&storage), key, val).await;
+        put_response(res, &storage, false)
+    }
+
+    async fn put_raw_value(&mut self, storage: StorageId, _key: &str, val: Vec<u8>) -> StorageResult<()> {
+        let res = self.client.replace_tuple(self.config.space_id(&storage), val).await;
+        put_response(res, &storage, true)
+    }
+
+    async fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
+        let res = self.client.delete_key(self.config.space_id(&storage), key).await;
+        remove_response(res, &storage, StorageResult::NotReady)
+    }
+
+    async fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
+        let space = self.config.space_id(&storage);
+        let res = self.client.space_len(space).await;
+        count_response(res, &storage, space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    // Tarantool-less client: keeps tuples in memory and records the space of every request
+    #[derive(Clone, Default)]
+    struct MockClient {
+        tuples: Arc<Mutex<HashMap<(i32, String), String>>>,
+        requests: Arc<Mutex<Vec<(&'static str, i32)>>>,
+        down: bool,
+    }
+
+    impl MockClient {
+        fn record(&self, op: &'static str, space: i32) -> io::Result<()> {
+            self.requests.lock().unwrap().push((op, space));
+            if self.down {
+                Err(io::Error::new(io::ErrorKind::ConnectionRefused, "tarantool is down"))
+            } else {
+                Ok(())
+            }
         }
     }
 
-    fn remove_value(&mut self, storage: StorageId, key: &str) -> StorageResult<()> {
-        let space = if storage == StorageId::Tickets {
-            TICKETS_SPACE_ID
-        } else if storage == StorageId::Az {
-            AZ_SPACE_ID
-        } else {
-            INDIVIDUALS_SPACE_ID
-        };
+    impl TTClient for MockClient {
+        async fn select_key(&self, space: i32, key: &str) -> io::Result<Vec<u8>> {
+            self.record("select", space)?;
+            Ok(match self.tuples.lock().unwrap().get(&(space, key.to_string())) {
+                Some(val) => [&[0u8; TUPLE_HEADER_LEN][..], val.as_bytes()].concat(),
+                None => Vec::new(),
+            })
+        }
 
-        let tuple = (key,);
+        async fn replace_value(&self, space: i32, key: &str, val: &str) -> io::Result<()> {
+            self.record("replace", space)?;
+            self.tuples.lock().unwrap().insert((space, key.to_string()), val.to_string());
+            Ok(())
+        }
 
-        match self.rt.block_on(self.client.delete(space, &tuple)) {
-            Ok(_) => StorageResult::Ok(()),
-            Err(e) => {
-                error!("tarantool: fail remove, db [{:?}], err = {:?}", storage, e);
-                StorageResult::NotFound
-            },
+        async fn replace_tuple(&self, space: i32, _tuple: Vec<u8>) -> io::Result<()> {
+            self.record("replace", space)
+        }
+
+        async fn delete_key(&self, space: i32, key: &str) -> io::Result<()> {
+            self.record("delete", space)?;
+            self.tuples.lock().unwrap().remove(&(space, key.to_string()));
+            Ok(())
+        }
+
+        async fn space_len(&self, space: i32) -> io::Result<u64> {
+            self.record("len", space)?;
+            Ok(self.tuples.lock().unwrap().keys().filter(|(s, _)| *s == space).count() as u64)
+        }
+
+        async fn ping(&self) -> io::Result<()> {
+            self.record("ping", 0)
         }
     }
 
-    fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
-        let space_name = if storage == StorageId::Tickets {
-            "TICKETS"
-        } else if storage == StorageId::Az {
-            "AZ"
-        } else {
-            "INDIVIDUALS"
+    #[test]
+    fn test_async_storage_with_mock_client() {
+        let client = MockClient::default();
+        let mut storage = TTStorage::with_client(client.clone(), TTStorageConfig::default());
+
+        let mut rt = RuntimeWrapper::new();
+        rt.block_on(async {
+            assert_eq!(AsyncStorage::put_value(&mut storage, StorageId::Tickets, "key", "value").await, StorageResult::Ok(()));
+            assert_eq!(AsyncStorage::get_value(&mut storage, StorageId::Tickets, "key").await, StorageResult::Ok("value".to_string()));
+            assert_eq!(AsyncStorage::get_raw_value(&mut storage, StorageId::Individuals, "key").await, StorageResult::NotFound);
+            assert_eq!(AsyncStorage::count(&mut storage, StorageId::Tickets).await, StorageResult::Ok(1));
+            assert_eq!(AsyncStorage::remove_value(&mut storage, StorageId::Tickets, "key").await, StorageResult::Ok(()));
+            assert_eq!(AsyncStorage::get_value(&mut storage, StorageId::Tickets, "key").await, StorageResult::NotFound);
+        });
+
+        // The sync impl sees the same data through the same mapping
+        assert!(Storage::put_value(&mut storage, StorageId::Az, "k", "v").is_ok());
+        assert_eq!(Storage::get_value(&mut storage, StorageId::Az, "k"), StorageResult::Ok("v".to_string()));
+    }
+
+    #[test]
+    fn test_transport_errors() {
+        let client = MockClient {
+            down: true,
+            ..MockClient::default()
         };
+        let mut storage = TTStorage::with_client(client, TTStorageConfig::default());
 
-        match self.rt.block_on(self.client.eval(format!("return box.space.{}:len()", space_name), &(0,))) {
-            Ok(response) => {
-                match response.decode::<(u64,)>() {
-                    Ok(res) => StorageResult::Ok(res.0 as usize),
-                    Err(e) => {
-                        error!("failed to decode count response: db [{}], err = {:?}", space_name, e);
-                        StorageResult::Error("Failed to decode count response".to_string())
-                    },
-                }
-            },
-            Err(e) => {
-                error!("failed to count the number of records: db [{}], err = {:?}", space_name, e);
-                StorageResult::Error(format!("Failed to count records: {:?}", e))
-            },
-        }
+        let mut iraw = Individual::default();
+        // The sync impl keeps the results it always had
+        assert_eq!(Storage::get_individual(&mut storage, StorageId::Individuals, "uri", &mut iraw), StorageResult::UnprocessableEntity);
+        assert_eq!(Storage::get_value(&mut storage, StorageId::Individuals, "key"), StorageResult::NotFound);
+        assert_eq!(Storage::get_raw_value(&mut storage, StorageId::Individuals, "key"), StorageResult::NotFound);
+        assert_eq!(Storage::remove_value(&mut storage, StorageId::Individuals, "key"), StorageResult::NotFound);
+        assert_eq!(storage.health_check(), StorageResult::NotReady);
+        assert!(Storage::put_value(&mut storage, StorageId::Individuals, "key", "value").is_error());
+
+        let mut rt = RuntimeWrapper::new();
+        rt.block_on(async {
+            assert_eq!(AsyncStorage::get_individual(&mut storage, StorageId::Individuals, "uri", &mut iraw).await, StorageResult::NotReady);
+            assert_eq!(AsyncStorage::get_value(&mut storage, StorageId::Individuals, "key").await, StorageResult::NotReady);
+            assert_eq!(AsyncStorage::get_raw_value(&mut storage, StorageId::Individuals, "key").await, StorageResult::NotReady);
+            assert_eq!(AsyncStorage::remove_value(&mut storage, StorageId::Individuals, "key").await, StorageResult::NotReady);
+        });
+    }
+
+    #[test]
+    fn test_short_response_has_no_payload() {
+        assert_eq!(tuple_payload(&[]), None);
+        assert_eq!(tuple_payload(&[0x91, 0x92, 0xa3, 0x6b]), None);
+        assert_eq!(tuple_payload(&[0x91, 0x92, 0xa3, 0x6b, 0x65]), Some(&[][..]));
+        assert_eq!(tuple_payload(&[0x91, 0x92, 0xa3, 0x6b, 0x65, b'v']), Some(&b"v"[..]));
+    }
+
+    #[test]
+    fn test_missing_spaces_detection() {
+        // Mocked server that only has the INDIVIDUALS space
+        let spaces = TTStorageConfig::default();
+        let missing = missing_spaces(&spaces, |id| Ok(id == INDIVIDUALS_SPACE_ID)).unwrap();
+        assert_eq!(missing, vec!["TICKETS", "AZ"]);
+
+        let err = missing_spaces_error(&missing);
+        assert!(matches!(err, StorageError::InvalidConfiguration(_)));
+        assert!(err.to_string().contains("TICKETS, AZ"));
+
+        assert!(missing_spaces(&spaces, |_| Ok(true)).unwrap().is_empty());
+
+        // Spaces are probed by their configured ids
+        let custom = TTStorageConfig {
+            individuals_space: 600,
+            tickets_space: 601,
+            az_space: 602,
+            ..TTStorageConfig::default()
+        };
+        let mut probed = Vec::new();
+        let missing = missing_spaces(&custom, |id| {
+            probed.push(id);
+            Ok(id != 602)
+        })
+        .unwrap();
+        assert_eq!(probed, vec![600, 601, 602]);
+        assert_eq!(missing, vec!["AZ"]);
+
+        // Probe failures are reported as is
+        let failed = missing_spaces(&spaces, |_| Err(StorageError::ConnectionFailed("timeout".to_string())));
+        assert!(matches!(failed, Err(StorageError::ConnectionFailed(_))));
+    }
+
+    #[test]
+    fn test_custom_space_ids() {
+        let default = TTStorageConfig::default();
+        assert_eq!(default.space_id(&StorageId::Individuals), 512);
+        assert_eq!(default.space_id(&StorageId::Tickets), 513);
+        assert_eq!(default.space_id(&StorageId::Az), 514);
+
+        let config = TTStorageConfig {
+            individuals_space: 600,
+            tickets_space: 601,
+            az_space: 602,
+            ..TTStorageConfig::default()
+        };
+        // The client connects lazily, so no server is needed to build the storage
+        let storage = TTStorage::with_spaces("127.0.0.1:1".to_string(), "user", "pass", config).unwrap();
+        assert_eq!(storage.config().space_id(&StorageId::Individuals), 600);
+        assert_eq!(storage.config().space_id(&StorageId::Tickets), 601);
+        assert_eq!(storage.config().space_id(&StorageId::Az), 602);
+
+        // count goes to the configured space as well
+        assert_eq!(count_expr(storage.config().space_id(&StorageId::Tickets)), "return box.space[601]:len()");
+    }
+
+    #[test]
+    fn test_requests_target_configured_spaces() {
+        let client = MockClient::default();
+        let config = TTStorageConfig {
+            individuals_space: 600,
+            tickets_space: 601,
+            az_space: 602,
+            ..TTStorageConfig::default()
+        };
+        let mut storage = TTStorage::with_client(client.clone(), config);
+
+        assert_eq!(Storage::get_value(&mut storage, StorageId::Tickets, "key"), StorageResult::NotFound);
+        assert!(Storage::put_value(&mut storage, StorageId::Az, "key", "value").is_ok());
+        assert_eq!(Storage::get_value(&mut storage, StorageId::Az, "key"), StorageResult::Ok("value".to_string()));
+        let mut iraw = Individual::default();
+        assert_eq!(Storage::get_individual(&mut storage, StorageId::Individuals, "uri", &mut iraw), StorageResult::NotFound);
+        assert!(Storage::remove_value(&mut storage, StorageId::Tickets, "key").is_ok());
+        assert_eq!(Storage::count(&mut storage, StorageId::Az), StorageResult::Ok(1));
+
+        assert_eq!(
+            *client.requests.lock().unwrap(),
+            vec![("select", 601), ("replace", 602), ("select", 602), ("select", 600), ("delete", 601), ("len", 602)]
+        );
+    }
+
+    #[test]
+    fn test_custom_timeouts() {
+        let storage = TTStorage::new("127.0.0.1:1".to_string(), "user", "pass");
+        assert_eq!(storage.timeout_ms(), DEFAULT_TIMEOUT_MS);
+        assert_eq!(storage.reconnect_ms(), DEFAULT_RECONNECT_MS);
+
+        let storage = TTStorage::with_timeouts("127.0.0.1:1".to_string(), "user", "pass", 5000, 60000);
+        assert_eq!(storage.timeout_ms(), 5000);
+        assert_eq!(storage.reconnect_ms(), 60000);
+        assert_eq!(storage.config().space_id(&StorageId::Az), AZ_SPACE_ID);
+        assert_eq!(storage.config().schema, TTSchemaCheck::Skip);
+
+        // Custom spaces and timeouts together
+        let config = TTStorageConfig {
+            az_space: 700,
+            timeout_ms: 3000,
+            ..TTStorageConfig::default()
+        };
+        let storage = TTStorage::with_config("127.0.0.1:1".to_string(), "user", "pass", config).unwrap();
+        assert_eq!(storage.config().space_id(&StorageId::Az), 700);
+        assert_eq!(storage.timeout_ms(), 3000);
+        assert_eq!(storage.reconnect_ms(), DEFAULT_RECONNECT_MS);
     }
 }
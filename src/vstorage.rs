@@ -1,5 +1,6 @@
 use v_individual_model::onto::individual::Individual;
-use crate::common::{Storage, StorageId, StorageResult, StorageDispatcher};
+use std::collections::HashMap;
+use crate::common::{FromMdbValue, ScanEntry, SectionEntry, SnapshotReader, Storage, StorageId, StorageResult, StorageDispatcher, StorageStats};
 
 // ========================================================================================
 // ОПТИМИЗИРОВАННАЯ ENUM-BASED ВЕРСИЯ ДЛЯ КРИТИЧНЫХ ПО ПРОИЗВОДИТЕЛЬНОСТИ СЛУЧАЕВ
@@ -27,6 +28,7 @@ pub enum VStorageEnum {
     None,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for VStorageEnum {
     fn default() -> Self {
         VStorageEnum::None
@@ -41,7 +43,7 @@ impl VStorageEnum {
 
     /// Создает LMDB хранилище
     pub fn lmdb(path: &str, mode: crate::common::StorageMode, max_read_counter_reopen: Option<u64>) -> Self {
-        VStorageEnum::Lmdb(crate::lmdb_storage::LMDBStorage::new(path, mode, max_read_counter_reopen))
+        VStorageEnum::Lmdb(crate::lmdb_storage::LMDBStorage::new(path, mode, max_read_counter_reopen, None))
     }
 
     /// Создает MDBX хранилище
@@ -64,6 +66,69 @@ impl VStorageEnum {
     pub fn is_empty(&self) -> bool {
         matches!(self, VStorageEnum::None)
     }
+
+    /// Доступ к MemoryStorage, если enum содержит этот вариант
+    pub fn as_memory_mut(&mut self) -> Option<&mut crate::memory_storage::MemoryStorage> {
+        match self {
+            VStorageEnum::Memory(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Доступ к LMDBStorage (например, к begin_ro_txn для чтения без копирования),
+    /// если enum содержит этот вариант
+    pub fn as_lmdb_mut(&mut self) -> Option<&mut crate::lmdb_storage::LMDBStorage> {
+        match self {
+            VStorageEnum::Lmdb(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Доступ к MDBXStorage, если enum содержит этот вариант
+    pub fn as_mdbx_mut(&mut self) -> Option<&mut crate::mdbx_storage::MDBXStorage> {
+        match self {
+            VStorageEnum::Mdbx(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Доступ к StorageROClient, если enum содержит этот вариант
+    pub fn as_remote_mut(&mut self) -> Option<&mut crate::remote_storage_client::StorageROClient> {
+        match self {
+            VStorageEnum::Remote(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Доступ к TTStorage, если enum содержит этот вариант
+    #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+    pub fn as_tarantool_mut(&mut self) -> Option<&mut crate::tt_storage::TTStorage> {
+        match self {
+            VStorageEnum::Tarantool(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[deprecated(since = "0.3.1", note = "Use as_memory_mut instead")]
+    pub fn as_memory(&mut self) -> Option<&mut crate::memory_storage::MemoryStorage> {
+        self.as_memory_mut()
+    }
+
+    #[deprecated(since = "0.3.1", note = "Use as_lmdb_mut instead")]
+    pub fn as_lmdb(&mut self) -> Option<&mut crate::lmdb_storage::LMDBStorage> {
+        self.as_lmdb_mut()
+    }
+
+    #[deprecated(since = "0.3.1", note = "Use as_mdbx_mut instead")]
+    pub fn as_mdbx(&mut self) -> Option<&mut crate::mdbx_storage::MDBXStorage> {
+        self.as_mdbx_mut()
+    }
+
+    #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+    #[deprecated(since = "0.3.1", note = "Use as_tarantool_mut instead")]
+    pub fn as_tarantool(&mut self) -> Option<&mut crate::tt_storage::TTStorage> {
+        self.as_tarantool_mut()
+    }
 }
 
 impl Storage for VStorageEnum {
@@ -115,6 +180,18 @@ impl Storage for VStorageEnum {
         }
     }
 
+    fn put_value_sized(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<usize> {
+        match self {
+            VStorageEnum::Memory(s) => s.put_value_sized(storage, key, val),
+            VStorageEnum::Lmdb(s) => s.put_value_sized(storage, key, val),
+            VStorageEnum::Mdbx(s) => s.put_value_sized(storage, key, val),
+            VStorageEnum::Remote(s) => s.put_value_sized(storage, key, val),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.put_value_sized(storage, key, val),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
     fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
         match self {
             VStorageEnum::Memory(s) => s.put_raw_value(storage, key, val),
@@ -150,6 +227,354 @@ impl Storage for VStorageEnum {
             VStorageEnum::None => StorageResult::NotReady,
         }
     }
+
+    fn stats(&mut self, storage: StorageId) -> StorageResult<StorageStats> {
+        match self {
+            VStorageEnum::Memory(s) => s.stats(storage),
+            VStorageEnum::Lmdb(s) => s.stats(storage),
+            VStorageEnum::Mdbx(s) => s.stats(storage),
+            VStorageEnum::Remote(s) => s.stats(storage),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.stats(storage),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn is_persistent(&self) -> bool {
+        match self {
+            VStorageEnum::Memory(s) => s.is_persistent(),
+            VStorageEnum::Lmdb(s) => s.is_persistent(),
+            VStorageEnum::Mdbx(s) => s.is_persistent(),
+            VStorageEnum::Remote(s) => s.is_persistent(),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.is_persistent(),
+            VStorageEnum::None => false,
+        }
+    }
+
+    fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        match self {
+            VStorageEnum::Memory(s) => s.snapshot(),
+            VStorageEnum::Lmdb(s) => s.snapshot(),
+            VStorageEnum::Mdbx(s) => s.snapshot(),
+            VStorageEnum::Remote(s) => s.snapshot(),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.snapshot(),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        match self {
+            VStorageEnum::Memory(s) => s.remove_matching(storage, pred),
+            VStorageEnum::Lmdb(s) => s.remove_matching(storage, pred),
+            VStorageEnum::Mdbx(s) => s.remove_matching(storage, pred),
+            VStorageEnum::Remote(s) => s.remove_matching(storage, pred),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.remove_matching(storage, pred),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        match self {
+            VStorageEnum::Memory(s) => s.remove_prefix(storage, prefix),
+            VStorageEnum::Lmdb(s) => s.remove_prefix(storage, prefix),
+            VStorageEnum::Mdbx(s) => s.remove_prefix(storage, prefix),
+            VStorageEnum::Remote(s) => s.remove_prefix(storage, prefix),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.remove_prefix(storage, prefix),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn get_if(&mut self, storage: StorageId, key: &str, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        match self {
+            VStorageEnum::Memory(s) => s.get_if(storage, key, pred),
+            VStorageEnum::Lmdb(s) => s.get_if(storage, key, pred),
+            VStorageEnum::Mdbx(s) => s.get_if(storage, key, pred),
+            VStorageEnum::Remote(s) => s.get_if(storage, key, pred),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.get_if(storage, key, pred),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn read_value(&mut self, storage: StorageId, key: &str, f: &mut dyn FnMut(&[u8])) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.read_value(storage, key, f),
+            VStorageEnum::Lmdb(s) => s.read_value(storage, key, f),
+            VStorageEnum::Mdbx(s) => s.read_value(storage, key, f),
+            VStorageEnum::Remote(s) => s.read_value(storage, key, f),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.read_value(storage, key, f),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        match self {
+            VStorageEnum::Memory(s) => s.increment_checked(storage, key, delta, limit),
+            VStorageEnum::Lmdb(s) => s.increment_checked(storage, key, delta, limit),
+            VStorageEnum::Mdbx(s) => s.increment_checked(storage, key, delta, limit),
+            VStorageEnum::Remote(s) => s.increment_checked(storage, key, delta, limit),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.increment_checked(storage, key, delta, limit),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        match self {
+            VStorageEnum::Memory(s) => s.compare_and_swap(storage, key, expected, new),
+            VStorageEnum::Lmdb(s) => s.compare_and_swap(storage, key, expected, new),
+            VStorageEnum::Mdbx(s) => s.compare_and_swap(storage, key, expected, new),
+            VStorageEnum::Remote(s) => s.compare_and_swap(storage, key, expected, new),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.compare_and_swap(storage, key, expected, new),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        match self {
+            VStorageEnum::Memory(s) => s.count_by_prefix(storage, depth_separator),
+            VStorageEnum::Lmdb(s) => s.count_by_prefix(storage, depth_separator),
+            VStorageEnum::Mdbx(s) => s.count_by_prefix(storage, depth_separator),
+            VStorageEnum::Remote(s) => s.count_by_prefix(storage, depth_separator),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.count_by_prefix(storage, depth_separator),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        match self {
+            VStorageEnum::Memory(s) => s.contains(storage, key),
+            VStorageEnum::Lmdb(s) => s.contains(storage, key),
+            VStorageEnum::Mdbx(s) => s.contains(storage, key),
+            VStorageEnum::Remote(s) => s.contains(storage, key),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.contains(storage, key),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        match self {
+            VStorageEnum::Memory(s) => s.keys_with_prefix(storage, prefix),
+            VStorageEnum::Lmdb(s) => s.keys_with_prefix(storage, prefix),
+            VStorageEnum::Mdbx(s) => s.keys_with_prefix(storage, prefix),
+            VStorageEnum::Remote(s) => s.keys_with_prefix(storage, prefix),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.keys_with_prefix(storage, prefix),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        match self {
+            VStorageEnum::Memory(s) => s.range(storage, start, end),
+            VStorageEnum::Lmdb(s) => s.range(storage, start, end),
+            VStorageEnum::Mdbx(s) => s.range(storage, start, end),
+            VStorageEnum::Remote(s) => s.range(storage, start, end),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.range(storage, start, end),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        match self {
+            VStorageEnum::Memory(s) => s.get_value_ns(namespace, key),
+            VStorageEnum::Lmdb(s) => s.get_value_ns(namespace, key),
+            VStorageEnum::Mdbx(s) => s.get_value_ns(namespace, key),
+            VStorageEnum::Remote(s) => s.get_value_ns(namespace, key),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.get_value_ns(namespace, key),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.put_value_ns(namespace, key, val),
+            VStorageEnum::Lmdb(s) => s.put_value_ns(namespace, key, val),
+            VStorageEnum::Mdbx(s) => s.put_value_ns(namespace, key, val),
+            VStorageEnum::Remote(s) => s.put_value_ns(namespace, key, val),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.put_value_ns(namespace, key, val),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.remove_value_ns(namespace, key),
+            VStorageEnum::Lmdb(s) => s.remove_value_ns(namespace, key),
+            VStorageEnum::Mdbx(s) => s.remove_value_ns(namespace, key),
+            VStorageEnum::Remote(s) => s.remove_value_ns(namespace, key),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.remove_value_ns(namespace, key),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        match self {
+            VStorageEnum::Memory(s) => s.count_ns(namespace),
+            VStorageEnum::Lmdb(s) => s.count_ns(namespace),
+            VStorageEnum::Mdbx(s) => s.count_ns(namespace),
+            VStorageEnum::Remote(s) => s.count_ns(namespace),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.count_ns(namespace),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        match self {
+            VStorageEnum::Memory(s) => s.exists_many(storage, keys),
+            VStorageEnum::Lmdb(s) => s.exists_many(storage, keys),
+            VStorageEnum::Mdbx(s) => s.exists_many(storage, keys),
+            VStorageEnum::Remote(s) => s.exists_many(storage, keys),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.exists_many(storage, keys),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        match self {
+            VStorageEnum::Memory(s) => s.get_individuals(storage, ids),
+            VStorageEnum::Lmdb(s) => s.get_individuals(storage, ids),
+            VStorageEnum::Mdbx(s) => s.get_individuals(storage, ids),
+            VStorageEnum::Remote(s) => s.get_individuals(storage, ids),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.get_individuals(storage, ids),
+            VStorageEnum::None => ids.iter().map(|id| (id.to_string(), StorageResult::NotReady)).collect(),
+        }
+    }
+
+    fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        match self {
+            VStorageEnum::Memory(s) => s.get_values_many(storage, keys),
+            VStorageEnum::Lmdb(s) => s.get_values_many(storage, keys),
+            VStorageEnum::Mdbx(s) => s.get_values_many(storage, keys),
+            VStorageEnum::Remote(s) => s.get_values_many(storage, keys),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.get_values_many(storage, keys),
+            VStorageEnum::None => keys.iter().map(|_| StorageResult::NotReady).collect(),
+        }
+    }
+
+    fn copy_to_section(&mut self, from: StorageId, to: StorageId, key: &str) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.copy_to_section(from, to, key),
+            VStorageEnum::Lmdb(s) => s.copy_to_section(from, to, key),
+            VStorageEnum::Mdbx(s) => s.copy_to_section(from, to, key),
+            VStorageEnum::Remote(s) => s.copy_to_section(from, to, key),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.copy_to_section(from, to, key),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        match self {
+            VStorageEnum::Memory(s) => s.iter_all(),
+            VStorageEnum::Lmdb(s) => s.iter_all(),
+            VStorageEnum::Mdbx(s) => s.iter_all(),
+            VStorageEnum::Remote(s) => s.iter_all(),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.iter_all(),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        match self {
+            VStorageEnum::Memory(s) => s.scan(storage),
+            VStorageEnum::Lmdb(s) => s.scan(storage),
+            VStorageEnum::Mdbx(s) => s.scan(storage),
+            VStorageEnum::Remote(s) => s.scan(storage),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.scan(storage),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.put_many(storage, entries),
+            VStorageEnum::Lmdb(s) => s.put_many(storage, entries),
+            VStorageEnum::Mdbx(s) => s.put_many(storage, entries),
+            VStorageEnum::Remote(s) => s.put_many(storage, entries),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.put_many(storage, entries),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.put_values_many(storage, pairs),
+            VStorageEnum::Lmdb(s) => s.put_values_many(storage, pairs),
+            VStorageEnum::Mdbx(s) => s.put_values_many(storage, pairs),
+            VStorageEnum::Remote(s) => s.put_values_many(storage, pairs),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.put_values_many(storage, pairs),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.clear(storage),
+            VStorageEnum::Lmdb(s) => s.clear(storage),
+            VStorageEnum::Mdbx(s) => s.clear(storage),
+            VStorageEnum::Remote(s) => s.clear(storage),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.clear(storage),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn sync(&mut self, force: bool) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.sync(force),
+            VStorageEnum::Lmdb(s) => s.sync(force),
+            VStorageEnum::Mdbx(s) => s.sync(force),
+            VStorageEnum::Remote(s) => s.sync(force),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.sync(force),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn health_check(&mut self) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.health_check(),
+            VStorageEnum::Lmdb(s) => s.health_check(),
+            VStorageEnum::Mdbx(s) => s.health_check(),
+            VStorageEnum::Remote(s) => s.health_check(),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.health_check(),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
+
+    fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        match self {
+            VStorageEnum::Memory(s) => s.replace_section(storage, entries),
+            VStorageEnum::Lmdb(s) => s.replace_section(storage, entries),
+            VStorageEnum::Mdbx(s) => s.replace_section(storage, entries),
+            VStorageEnum::Remote(s) => s.replace_section(storage, entries),
+            #[cfg(any(feature = "tt_2", feature = "tt_3"))]
+            VStorageEnum::Tarantool(s) => s.replace_section(storage, entries),
+            VStorageEnum::None => StorageResult::NotReady,
+        }
+    }
 }
 
 // ========================================================================================
@@ -193,6 +618,19 @@ impl VStorage {
         self.storage.is_none()
     }
 
+    /// Сохраняются ли данные между перезапусками (false для пустого хранилища)
+    pub fn is_persistent(&self) -> bool {
+        self.storage.as_ref().is_some_and(|s| s.is_persistent())
+    }
+
+    /// Снимок для согласованного чтения нескольких ключей, см. Storage::snapshot
+    pub fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        match self.storage.as_ref() {
+            Some(s) => s.snapshot(),
+            None => StorageResult::NotReady,
+        }
+    }
+
     /// Основной конструктор принимающий готовое хранилище
     pub fn new(storage: Box<dyn Storage>) -> VStorage {
         VStorage {
@@ -212,6 +650,14 @@ impl VStorage {
         Ok(VStorage::new(storage))
     }
 
+    /// Возвращает представление секции с семантикой map
+    pub fn map_view(&mut self, section: StorageId) -> MapView<'_> {
+        MapView {
+            storage: self,
+            section,
+        }
+    }
+
     // ========================================================================================
     // ПУБЛИЧНЫЕ МЕТОДЫ API - УНИФИЦИРОВАННОЕ ИМЕНОВАНИЕ
     // ========================================================================================
@@ -236,6 +682,16 @@ impl VStorage {
         self.with_storage_result(|s| s.put_value(storage, key, val))
     }
 
+    /// Читает значение и декодирует его как T (см. Storage::get_typed)
+    pub fn get_typed<T: FromMdbValue>(&mut self, storage: StorageId, key: &str) -> StorageResult<T> {
+        self.with_storage(StorageResult::NotReady, |s| s.get_typed(storage, key))
+    }
+
+    /// Записывает значение и возвращает число записанных байт (ключ + значение)
+    pub fn put_value_sized(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.put_value_sized(storage, key, val))
+    }
+
     pub fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
         self.with_storage_result(|s| s.put_raw_value(storage, key, val))
     }
@@ -248,6 +704,153 @@ impl VStorage {
         self.with_storage_value(|s| s.count(storage))
     }
 
+    pub fn stats(&mut self, storage: StorageId) -> StorageResult<StorageStats> {
+        self.with_storage_value(|s| s.stats(storage))
+    }
+
+    pub fn fill_individuals(&mut self, storage: StorageId, ids: &[&str], out: &mut Vec<(String, Individual)>) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.fill_individuals(storage, ids, out))
+    }
+
+    pub fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.remove_matching(storage, pred))
+    }
+
+    pub fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.remove_prefix(storage, prefix))
+    }
+
+    pub fn get_if(&mut self, storage: StorageId, key: &str, pred: impl FnOnce(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        let mut pred = Some(pred);
+        self.with_storage_value(|s| s.get_if(storage, key, &mut |val| pred.take().is_some_and(|p| p(val))))
+    }
+
+    /// Результат f над значением без копирования в Vec (см. Storage::read_value)
+    pub fn with_value<R>(&mut self, storage: StorageId, key: &str, f: impl FnOnce(&[u8]) -> R) -> StorageResult<R> {
+        let mut f = Some(f);
+        let mut out = None;
+        self.with_storage_result(|s| {
+            s.read_value(storage, key, &mut |val| {
+                if let Some(f) = f.take() {
+                    out = Some(f(val));
+                }
+            })
+        })
+        .and_then(|_| out.map_or(StorageResult::NotFound, StorageResult::Ok))
+    }
+
+    pub fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        self.with_storage_value(|s| s.increment_checked(storage, key, delta, limit))
+    }
+
+    pub fn increment(&mut self, storage: StorageId, key: &str, delta: i64) -> StorageResult<i64> {
+        self.with_storage_value(|s| s.increment(storage, key, delta))
+    }
+
+    pub fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        self.with_storage_value(|s| s.compare_and_swap(storage, key, expected, new))
+    }
+
+    pub fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        self.with_storage_result(|s| s.put_many(storage, entries))
+    }
+
+    pub fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        self.with_storage_result(|s| s.put_values_many(storage, pairs))
+    }
+
+    pub fn copy_to_section(&mut self, from: StorageId, to: StorageId, key: &str) -> StorageResult<()> {
+        self.with_storage_result(|s| s.copy_to_section(from, to, key))
+    }
+
+    pub fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.with_storage_value(|s| s.contains(storage, key))
+    }
+
+    pub fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        self.with_storage_value(|s| s.exists_many(storage, keys))
+    }
+
+    /// Значения ключей в порядке keys; NotReady для каждого ключа, если хранилище пустое
+    pub fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        self.with_storage(keys.iter().map(|_| StorageResult::NotReady).collect(), |s| s.get_values_many(storage, keys))
+    }
+
+    pub fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        self.with_storage(ids.iter().map(|id| (id.to_string(), StorageResult::NotReady)).collect(), |s| s.get_individuals(storage, ids))
+    }
+
+    pub fn distinct_values(&mut self, storage: StorageId) -> StorageResult<HashMap<Vec<u8>, Vec<String>>> {
+        self.with_storage_value(|s| s.distinct_values(storage))
+    }
+
+    pub fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        self.with_storage_value(|s| s.count_by_prefix(storage, depth_separator))
+    }
+
+    pub fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.with_storage_value(|s| s.keys_with_prefix(storage, prefix))
+    }
+
+    /// Записи с ключами из [start, end) в лексикографическом порядке
+    pub fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.with_storage_value(|s| s.range(storage, start, end))
+    }
+
+    /// Значение из именованной коллекции, см. Storage::get_value_ns
+    pub fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        self.with_storage_value(|s| s.get_value_ns(namespace, key))
+    }
+
+    /// Запись в именованную коллекцию
+    pub fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        self.with_storage_result(|s| s.put_value_ns(namespace, key, val))
+    }
+
+    /// Удаление из именованной коллекции
+    pub fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        self.with_storage_result(|s| s.remove_value_ns(namespace, key))
+    }
+
+    /// Число записей именованной коллекции
+    pub fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.count_ns(namespace))
+    }
+
+    pub fn for_each_value_group(&mut self, storage: StorageId, f: &mut dyn FnMut(&[u8], &[String])) -> StorageResult<()> {
+        self.with_storage_result(|s| s.for_each_value_group(storage, f))
+    }
+
+    pub fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        self.with_storage_result(|s| s.replace_section(storage, entries))
+    }
+
+    pub fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.with_storage_result(|s| s.clear(storage))
+    }
+
+    pub fn sync(&mut self, force: bool) -> StorageResult<()> {
+        self.with_storage_result(|s| s.sync(force))
+    }
+
+    pub fn health_check(&mut self) -> StorageResult<()> {
+        self.with_storage_result(|s| s.health_check())
+    }
+
+    pub fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        self.with_storage_value(|s| s.non_empty_sections())
+    }
+
+    /// Обход всех записей всех секций (посекционно)
+    pub fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        self.with_storage(StorageResult::NotReady, |s| s.iter_all())
+    }
+
+    /// Пары (ключ, значение) секции за один проход
+    pub fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.with_storage(StorageResult::NotReady, |s| s.scan(storage))
+    }
+
     // ========================================================================================
     // DEPRECATED МЕТОДЫ ДЛЯ ОБРАТНОЙ СОВМЕСТИМОСТИ
     // ========================================================================================
@@ -286,6 +889,66 @@ impl VStorage {
     }
 }
 
+// ========================================================================================
+// MAP VIEW - ФАСАД С СЕМАНТИКОЙ MAP ДЛЯ ОДНОЙ СЕКЦИИ
+// ========================================================================================
+
+/// Представление одной секции VStorage в виде map
+///
+/// Методы возвращают Option/bool вместо StorageResult:
+/// NotFound, NotReady и Error сворачиваются в None/false.
+pub struct MapView<'a> {
+    storage: &'a mut VStorage,
+    section: StorageId,
+}
+
+impl MapView<'_> {
+    pub fn section(&self) -> &StorageId {
+        &self.section
+    }
+
+    pub fn insert(&mut self, key: &str, val: &str) -> bool {
+        self.storage.put_value(self.section.clone(), key, val).is_ok()
+    }
+
+    pub fn insert_raw(&mut self, key: &str, val: Vec<u8>) -> bool {
+        self.storage.put_raw_value(self.section.clone(), key, val).is_ok()
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<String> {
+        match self.storage.get_value(self.section.clone(), key) {
+            StorageResult::Ok(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_raw(&mut self, key: &str) -> Option<Vec<u8>> {
+        match self.storage.get_raw_value(self.section.clone(), key) {
+            StorageResult::Ok(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.storage.remove_value(self.section.clone(), key).is_ok()
+    }
+
+    pub fn contains_key(&mut self, key: &str) -> bool {
+        matches!(self.storage.contains(self.section.clone(), key), StorageResult::Ok(true))
+    }
+
+    pub fn len(&mut self) -> Option<usize> {
+        match self.storage.count(self.section.clone()) {
+            StorageResult::Ok(count) => Some(count),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == Some(0)
+    }
+}
+
 // ========================================================================================
 // GENERIC ВЕРСИЯ - VStorageGeneric<S>
 // ========================================================================================
@@ -335,6 +998,19 @@ impl<S: Storage> VStorageGeneric<S> {
         self.storage.is_none()
     }
 
+    /// Сохраняются ли данные между перезапусками (false для пустого хранилища)
+    pub fn is_persistent(&self) -> bool {
+        self.storage.as_ref().is_some_and(|s| s.is_persistent())
+    }
+
+    /// Снимок для согласованного чтения нескольких ключей, см. Storage::snapshot
+    pub fn snapshot(&self) -> StorageResult<Box<dyn SnapshotReader>> {
+        match self.storage.as_ref() {
+            Some(s) => s.snapshot(),
+            None => StorageResult::NotReady,
+        }
+    }
+
     /// Берет хранилище из структуры, оставляя None
     pub fn take_storage(mut self) -> Option<S> {
         self.storage.take()
@@ -374,6 +1050,16 @@ impl<S: Storage> VStorageGeneric<S> {
         self.with_storage_result(|s| s.put_value(storage, key, val))
     }
 
+    /// Читает значение и декодирует его как T (см. Storage::get_typed)
+    pub fn get_typed<T: FromMdbValue>(&mut self, storage: StorageId, key: &str) -> StorageResult<T> {
+        self.with_storage(StorageResult::NotReady, |s| s.get_typed(storage, key))
+    }
+
+    /// Записывает значение и возвращает число записанных байт (ключ + значение)
+    pub fn put_value_sized(&mut self, storage: StorageId, key: &str, val: &str) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.put_value_sized(storage, key, val))
+    }
+
     pub fn put_raw_value(&mut self, storage: StorageId, key: &str, val: Vec<u8>) -> StorageResult<()> {
         self.with_storage_result(|s| s.put_raw_value(storage, key, val))
     }
@@ -385,6 +1071,153 @@ impl<S: Storage> VStorageGeneric<S> {
     pub fn count(&mut self, storage: StorageId) -> StorageResult<usize> {
         self.with_storage_value(|s| s.count(storage))
     }
+
+    pub fn stats(&mut self, storage: StorageId) -> StorageResult<StorageStats> {
+        self.with_storage_value(|s| s.stats(storage))
+    }
+
+    pub fn fill_individuals(&mut self, storage: StorageId, ids: &[&str], out: &mut Vec<(String, Individual)>) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.fill_individuals(storage, ids, out))
+    }
+
+    pub fn remove_matching(&mut self, storage: StorageId, pred: &mut dyn FnMut(&[u8]) -> bool) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.remove_matching(storage, pred))
+    }
+
+    pub fn remove_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.remove_prefix(storage, prefix))
+    }
+
+    pub fn get_if(&mut self, storage: StorageId, key: &str, pred: impl FnOnce(&[u8]) -> bool) -> StorageResult<Option<Vec<u8>>> {
+        let mut pred = Some(pred);
+        self.with_storage_value(|s| s.get_if(storage, key, &mut |val| pred.take().is_some_and(|p| p(val))))
+    }
+
+    /// Результат f над значением без копирования в Vec (см. Storage::read_value)
+    pub fn with_value<R>(&mut self, storage: StorageId, key: &str, f: impl FnOnce(&[u8]) -> R) -> StorageResult<R> {
+        let mut f = Some(f);
+        let mut out = None;
+        self.with_storage_result(|s| {
+            s.read_value(storage, key, &mut |val| {
+                if let Some(f) = f.take() {
+                    out = Some(f(val));
+                }
+            })
+        })
+        .and_then(|_| out.map_or(StorageResult::NotFound, StorageResult::Ok))
+    }
+
+    pub fn increment_checked(&mut self, storage: StorageId, key: &str, delta: i64, limit: i64) -> StorageResult<(i64, bool)> {
+        self.with_storage_value(|s| s.increment_checked(storage, key, delta, limit))
+    }
+
+    pub fn increment(&mut self, storage: StorageId, key: &str, delta: i64) -> StorageResult<i64> {
+        self.with_storage_value(|s| s.increment(storage, key, delta))
+    }
+
+    pub fn compare_and_swap(&mut self, storage: StorageId, key: &str, expected: Option<&[u8]>, new: &[u8]) -> StorageResult<bool> {
+        self.with_storage_value(|s| s.compare_and_swap(storage, key, expected, new))
+    }
+
+    pub fn put_many(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        self.with_storage_result(|s| s.put_many(storage, entries))
+    }
+
+    pub fn put_values_many(&mut self, storage: StorageId, pairs: &[(&str, &str)]) -> StorageResult<()> {
+        self.with_storage_result(|s| s.put_values_many(storage, pairs))
+    }
+
+    pub fn copy_to_section(&mut self, from: StorageId, to: StorageId, key: &str) -> StorageResult<()> {
+        self.with_storage_result(|s| s.copy_to_section(from, to, key))
+    }
+
+    pub fn contains(&mut self, storage: StorageId, key: &str) -> StorageResult<bool> {
+        self.with_storage_value(|s| s.contains(storage, key))
+    }
+
+    pub fn exists_many(&mut self, storage: StorageId, keys: &[&str]) -> StorageResult<Vec<bool>> {
+        self.with_storage_value(|s| s.exists_many(storage, keys))
+    }
+
+    /// Значения ключей в порядке keys; NotReady для каждого ключа, если хранилище пустое
+    pub fn get_values_many(&mut self, storage: StorageId, keys: &[&str]) -> Vec<StorageResult<String>> {
+        self.with_storage(keys.iter().map(|_| StorageResult::NotReady).collect(), |s| s.get_values_many(storage, keys))
+    }
+
+    pub fn get_individuals(&mut self, storage: StorageId, ids: &[&str]) -> Vec<(String, StorageResult<Individual>)> {
+        self.with_storage(ids.iter().map(|id| (id.to_string(), StorageResult::NotReady)).collect(), |s| s.get_individuals(storage, ids))
+    }
+
+    pub fn distinct_values(&mut self, storage: StorageId) -> StorageResult<HashMap<Vec<u8>, Vec<String>>> {
+        self.with_storage_value(|s| s.distinct_values(storage))
+    }
+
+    pub fn count_by_prefix(&mut self, storage: StorageId, depth_separator: char) -> StorageResult<HashMap<String, usize>> {
+        self.with_storage_value(|s| s.count_by_prefix(storage, depth_separator))
+    }
+
+    pub fn keys_with_prefix(&mut self, storage: StorageId, prefix: &str) -> StorageResult<Vec<String>> {
+        self.with_storage_value(|s| s.keys_with_prefix(storage, prefix))
+    }
+
+    /// Записи с ключами из [start, end) в лексикографическом порядке
+    pub fn range(&mut self, storage: StorageId, start: &str, end: &str) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        self.with_storage_value(|s| s.range(storage, start, end))
+    }
+
+    /// Значение из именованной коллекции, см. Storage::get_value_ns
+    pub fn get_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<String> {
+        self.with_storage_value(|s| s.get_value_ns(namespace, key))
+    }
+
+    /// Запись в именованную коллекцию
+    pub fn put_value_ns(&mut self, namespace: &str, key: &str, val: &str) -> StorageResult<()> {
+        self.with_storage_result(|s| s.put_value_ns(namespace, key, val))
+    }
+
+    /// Удаление из именованной коллекции
+    pub fn remove_value_ns(&mut self, namespace: &str, key: &str) -> StorageResult<()> {
+        self.with_storage_result(|s| s.remove_value_ns(namespace, key))
+    }
+
+    /// Число записей именованной коллекции
+    pub fn count_ns(&mut self, namespace: &str) -> StorageResult<usize> {
+        self.with_storage_value(|s| s.count_ns(namespace))
+    }
+
+    pub fn for_each_value_group(&mut self, storage: StorageId, f: &mut dyn FnMut(&[u8], &[String])) -> StorageResult<()> {
+        self.with_storage_result(|s| s.for_each_value_group(storage, f))
+    }
+
+    pub fn replace_section(&mut self, storage: StorageId, entries: &[(&str, &[u8])]) -> StorageResult<()> {
+        self.with_storage_result(|s| s.replace_section(storage, entries))
+    }
+
+    pub fn clear(&mut self, storage: StorageId) -> StorageResult<()> {
+        self.with_storage_result(|s| s.clear(storage))
+    }
+
+    pub fn sync(&mut self, force: bool) -> StorageResult<()> {
+        self.with_storage_result(|s| s.sync(force))
+    }
+
+    pub fn health_check(&mut self) -> StorageResult<()> {
+        self.with_storage_result(|s| s.health_check())
+    }
+
+    pub fn non_empty_sections(&mut self) -> StorageResult<Vec<StorageId>> {
+        self.with_storage_value(|s| s.non_empty_sections())
+    }
+
+    /// Обход всех записей всех секций (посекционно)
+    pub fn iter_all(&mut self) -> StorageResult<Box<dyn Iterator<Item = SectionEntry>>> {
+        self.with_storage(StorageResult::NotReady, |s| s.iter_all())
+    }
+
+    /// Пары (ключ, значение) секции за один проход
+    pub fn scan(&mut self, storage: StorageId) -> StorageResult<Box<dyn Iterator<Item = ScanEntry>>> {
+        self.with_storage(StorageResult::NotReady, |s| s.scan(storage))
+    }
 }
 
 // Реализация Default для случаев, когда S реализует Default
@@ -452,6 +1285,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_map_view() {
+        let mut storage = VStorage::new(Box::new(crate::memory_storage::MemoryStorage::new()));
+
+        {
+            let mut map = storage.map_view(StorageId::Tickets);
+            assert!(map.is_empty());
+            assert!(map.insert("k1", "v1"));
+            assert!(map.insert_raw("k2", vec![1, 2, 3]));
+            assert!(map.contains_key("k1"));
+            assert!(!map.contains_key("missing"));
+            assert_eq!(map.get("k1"), Some("v1".to_string()));
+            assert_eq!(map.get_raw("k2"), Some(vec![1, 2, 3]));
+            assert_eq!(map.get("missing"), None);
+            assert_eq!(map.len(), Some(2));
+            assert!(map.remove("k1"));
+            assert!(!map.remove("k1"));
+            assert_eq!(map.len(), Some(1));
+        }
+
+        // The view writes through to the underlying section only
+        assert_eq!(storage.get_raw_value(StorageId::Tickets, "k2"), StorageResult::Ok(vec![1, 2, 3]));
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+        // Uninitialized storage collapses to None/false
+        let mut empty = VStorage::none();
+        let mut map = empty.map_view(StorageId::Individuals);
+        assert!(!map.insert("k", "v"));
+        assert_eq!(map.get("k"), None);
+        assert_eq!(map.len(), None);
+    }
+
     #[test]
     fn test_enum_storage_empty() {
         let mut storage = VStorageEnum::default();
@@ -506,6 +1371,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_storage_from_custom_config() {
+        use crate::storage_factory::{StorageBuilder, StorageRegistry};
+
+        let name = "test-custom-backend";
+        StorageRegistry::register(name, || {
+            let mut storage = crate::memory_storage::MemoryStorage::new();
+            let _ = storage.put_value(StorageId::Individuals, "backend", "custom");
+            Box::new(storage)
+        })
+        .unwrap();
+        assert!(StorageRegistry::is_registered(name).unwrap());
+
+        let mut storage = VStorage::from_config(StorageConfig::Custom { name: name.to_string() }).unwrap();
+        assert_eq!(storage.get_value(StorageId::Individuals, "backend"), StorageResult::Ok("custom".to_string()));
+
+        // Every build gets a fresh instance from the factory, read_only wraps it as usual
+        let mut read_only = VStorage::new(StorageBuilder::new().custom(name).read_only().build().unwrap());
+        assert_eq!(read_only.get_value(StorageId::Individuals, "backend"), StorageResult::Ok("custom".to_string()));
+        assert!(read_only.put_value(StorageId::Individuals, "k", "v").is_error());
+
+        assert!(StorageRegistry::unregister(name).unwrap());
+        assert!(matches!(
+            VStorage::from_config(StorageConfig::Custom { name: name.to_string() }),
+            Err(crate::storage_factory::StorageError::InvalidConfiguration(_))
+        ));
+    }
+
     #[test]
     fn test_empty_storage() {
         let storage = VStorage::none();
@@ -646,4 +1539,107 @@ mod tests {
             assert_eq!(c2, c3);
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_is_persistent() {
+        assert!(!VStorage::new(Box::new(crate::memory_storage::MemoryStorage::new())).is_persistent());
+        assert!(!VStorage::none().is_persistent());
+        assert!(!VStorageEnum::memory().is_persistent());
+        assert!(VStorageEnum::Remote(crate::remote_storage_client::StorageROClient::new("tcp://127.0.0.1:1")).is_persistent());
+
+        let temp_dir = format!("/tmp/test-is-persistent-{}", std::process::id());
+        let lmdb = crate::lmdb_storage::LMDBStorage::new(&format!("{}/lmdb", temp_dir), crate::common::StorageMode::ReadWrite, None, None);
+        let mdbx = crate::mdbx_storage::MDBXStorage::new(&format!("{}/mdbx", temp_dir), crate::common::StorageMode::ReadWrite, None);
+        assert!(VStorageGeneric::new(lmdb).is_persistent());
+        assert!(VStorage::new(Box::new(mdbx)).is_persistent());
+
+        // Wrappers follow the inner storage
+        let read_only = crate::read_only_storage::ReadOnlyStorage::new(crate::memory_storage::MemoryStorage::new());
+        assert!(!read_only.is_persistent());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_get_values_many_empty_storage() {
+        let mut storage = VStorage::none();
+        assert_eq!(storage.get_values_many(StorageId::Individuals, &["a", "b"]), vec![StorageResult::NotReady, StorageResult::NotReady]);
+    }
+
+    #[test]
+    fn test_enum_concrete_access() {
+        let mut memory = VStorageEnum::memory();
+        assert!(memory.as_memory_mut().is_some());
+        assert!(memory.as_lmdb_mut().is_none());
+        assert!(memory.as_mdbx_mut().is_none());
+        assert!(memory.as_remote_mut().is_none());
+
+        // Changes through the concrete backend are visible through the enum
+        memory.as_memory_mut().unwrap().insert_test_data(StorageId::Az, "key", b"value".to_vec());
+        assert_eq!(memory.get_value(StorageId::Az, "key"), StorageResult::Ok("value".to_string()));
+
+        let temp_dir = format!("/tmp/test-enum-concrete-{}", std::process::id());
+        let mut lmdb = VStorageEnum::lmdb(&temp_dir, crate::common::StorageMode::ReadWrite, None);
+        assert!(lmdb.as_memory_mut().is_none());
+        assert!(lmdb.as_lmdb_mut().is_some());
+
+        // Backend-specific API is reachable through the accessor
+        assert!(lmdb.put_value(StorageId::Individuals, "key", "value").is_ok());
+        let reads_before = lmdb.as_lmdb_mut().unwrap().txn_stats().reads;
+        assert!(lmdb.get_value(StorageId::Individuals, "key").is_ok());
+        assert!(lmdb.as_lmdb_mut().unwrap().txn_stats().reads > reads_before);
+
+        assert!(VStorageEnum::None.as_memory_mut().is_none());
+        assert!(VStorageEnum::remote("tcp://127.0.0.1:1").as_remote_mut().is_some());
+
+        // Old accessor names still work
+        #[allow(deprecated)]
+        {
+            assert!(memory.as_memory().is_some());
+            assert!(lmdb.as_lmdb().is_some());
+            assert!(lmdb.as_mdbx().is_none());
+        }
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_debug_output() {
+        let mut storage = VMemoryStorage::new(crate::memory_storage::MemoryStorage::new());
+        assert!(storage.put_value(StorageId::Individuals, "a", "secret-1").is_ok());
+        assert!(storage.put_value(StorageId::Individuals, "b", "secret-2").is_ok());
+        assert!(storage.put_value(StorageId::Tickets, "t", "secret-3").is_ok());
+
+        let printed = format!("{:?}", storage);
+        assert!(printed.contains("individuals: 2"), "{}", printed);
+        assert!(printed.contains("tickets: 1"), "{}", printed);
+        assert!(printed.contains("az: 0"), "{}", printed);
+        assert!(!printed.contains("secret"), "{}", printed);
+
+        let temp_dir = format!("/tmp/test-debug-output-{}", std::process::id());
+        let lmdb = crate::lmdb_storage::LMDBStorage::new(&temp_dir, crate::common::StorageMode::ReadWrite, None, None);
+        let printed = format!("{:?}", lmdb);
+        assert!(printed.contains("lmdb-individuals") && printed.contains("lmdb-tickets") && printed.contains("acl-indexes"), "{}", printed);
+        assert!(printed.contains("read_counter: 0"), "{}", printed);
+
+        drop(lmdb);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_get_typed() {
+        let mut storage = VMemoryStorage::new(crate::memory_storage::MemoryStorage::new());
+        assert!(storage.put_raw_value(StorageId::Az, "counter", 42u64.to_le_bytes().to_vec()).is_ok());
+        assert!(storage.put_value(StorageId::Az, "name", "value").is_ok());
+
+        assert_eq!(storage.get_typed::<u64>(StorageId::Az, "counter"), StorageResult::Ok(42));
+        assert_eq!(storage.get_typed::<String>(StorageId::Az, "name"), StorageResult::Ok("value".to_string()));
+        assert_eq!(storage.get_typed::<u64>(StorageId::Az, "name"), StorageResult::UnprocessableEntity);
+        assert_eq!(storage.get_typed::<u64>(StorageId::Az, "missing"), StorageResult::NotFound);
+
+        let mut dynamic = VStorage::new(Box::new(crate::memory_storage::MemoryStorage::new()));
+        assert!(dynamic.put_raw_value(StorageId::Tickets, "n", (-7i64).to_le_bytes().to_vec()).is_ok());
+        assert_eq!(dynamic.get_typed::<i64>(StorageId::Tickets, "n"), StorageResult::Ok(-7));
+        assert_eq!(VStorage::none().get_typed::<u64>(StorageId::Tickets, "n"), StorageResult::NotReady);
+    }
+}
@@ -1,6 +1,25 @@
 use v_storage::*;
 use v_individual_model::onto::individual::Individual;
 
+/// Temp directory holding the databases of all_backends(tag)
+fn backends_dir(tag: &str) -> String {
+    format!("/tmp/test-{}-{}", tag, std::process::id())
+}
+
+/// Every local backend, named for assertion messages; remove with cleanup_backends(tag)
+fn all_backends(tag: &str) -> Vec<(String, VStorageEnum)> {
+    let dir = backends_dir(tag);
+    vec![
+        ("memory".to_string(), VStorageEnum::memory()),
+        ("lmdb".to_string(), VStorageEnum::lmdb(&format!("{}/lmdb", dir), StorageMode::ReadWrite, None)),
+        ("mdbx".to_string(), VStorageEnum::mdbx(&format!("{}/mdbx", dir), StorageMode::ReadWrite, None)),
+    ]
+}
+
+fn cleanup_backends(tag: &str) {
+    let _ = std::fs::remove_dir_all(backends_dir(tag));
+}
+
 #[test]
 fn test_cross_storage_compatibility() {
     // Тестируем что данные сохраненные в одном типе хранилища могут быть прочитаны другим
@@ -189,12 +208,12 @@ fn test_error_conditions() {
     assert_eq!(memory_storage.remove_value(StorageId::Individuals, "nonexistent"), StorageResult::NotFound);
     assert_eq!(memory_storage.get_individual(StorageId::Individuals, "nonexistent", &mut individual), StorageResult::NotFound);
 
-    // Тест с пустыми строками
-    assert!(memory_storage.put_value(StorageId::Individuals, "", "empty_key").is_ok());
+    // Тест с пустыми строками: пустой ключ отклоняется, пустое значение допустимо
+    let empty_key = StorageResult::Error(v_storage::common::EMPTY_KEY_ERROR.to_string());
+    assert_eq!(memory_storage.put_value(StorageId::Individuals, "", "empty_key"), empty_key);
     assert!(memory_storage.put_value(StorageId::Individuals, "empty_value", "").is_ok());
     
-    let empty_key_result = memory_storage.get_value(StorageId::Individuals, "");
-    assert!(empty_key_result.is_ok());
+    assert_eq!(memory_storage.get_value(StorageId::Individuals, "").map(|_| ()), empty_key);
     
     let empty_value_result = memory_storage.get_value(StorageId::Individuals, "empty_value");
     assert!(empty_value_result.is_ok());
@@ -432,4 +451,390 @@ fn test_mdbx_config_pattern() {
     
     // Cleanup
     let _ = std::fs::remove_dir_all(&temp_dir);
-} 
\ No newline at end of file
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn test_lmdb_zstd_auto_decompress() {
+    use v_individual_model::onto::individual2msgpack::to_msgpack;
+
+    let temp_dir = format!("/tmp/test-lmdb-integration-zstd-{}", std::process::id());
+    let mut storage = LMDBStorage::new(&temp_dir, StorageMode::ReadWrite, None, None);
+
+    let mut source = Individual::default();
+    source.set_id("test:compressed");
+    source.add_uri("rdf:type", "test:Person");
+    let mut packed = Vec::new();
+    assert!(to_msgpack(&source, &mut packed).is_ok());
+
+    // Значение, сжатое внешним инструментом, без заголовка крейта
+    let compressed = zstd::stream::encode_all(packed.as_slice(), 3).unwrap();
+    assert!(storage.put_raw_value(StorageId::Individuals, "test:compressed", compressed.clone()).is_ok());
+    assert!(storage.put_raw_value(StorageId::Individuals, "test:plain", packed.clone()).is_ok());
+
+    // По умолчанию значение возвращается как есть
+    let mut individual = Individual::default();
+    assert_eq!(storage.get_individual(StorageId::Individuals, "test:compressed", &mut individual), StorageResult::UnprocessableEntity);
+    assert_eq!(storage.get_raw_value(StorageId::Individuals, "test:compressed"), StorageResult::Ok(compressed));
+
+    storage.set_auto_decompress(true);
+
+    let mut individual = Individual::default();
+    assert_eq!(storage.get_individual(StorageId::Individuals, "test:compressed", &mut individual), StorageResult::Ok(()));
+    assert_eq!(individual.get_id(), "test:compressed");
+    assert_eq!(storage.get_raw_value(StorageId::Individuals, "test:compressed"), StorageResult::Ok(packed.clone()));
+
+    // Несжатые значения не затрагиваются
+    let mut plain = Individual::default();
+    assert_eq!(storage.get_individual(StorageId::Individuals, "test:plain", &mut plain), StorageResult::Ok(()));
+    assert_eq!(storage.get_raw_value(StorageId::Individuals, "test:plain"), StorageResult::Ok(packed));
+
+    // Строковые и пакетные чтения тоже распаковывают значение
+    let text = zstd::stream::encode_all("compressed text".as_bytes(), 3).unwrap();
+    assert!(storage.put_raw_value(StorageId::Tickets, "text", text).is_ok());
+    assert_eq!(storage.get_value(StorageId::Tickets, "text"), StorageResult::Ok("compressed text".to_string()));
+    assert_eq!(storage.get_values_many(StorageId::Tickets, &["text"]), vec![StorageResult::Ok("compressed text".to_string())]);
+
+    // Поврежденный кадр - ошибка, а не сжатые байты
+    let broken = vec![0x28, 0xB5, 0x2F, 0xFD, 0x00];
+    assert!(storage.put_raw_value(StorageId::Individuals, "test:broken", broken).is_ok());
+    let mut individual = Individual::default();
+    assert_eq!(storage.get_individual(StorageId::Individuals, "test:broken", &mut individual), StorageResult::UnprocessableEntity);
+    assert_eq!(storage.get_raw_value(StorageId::Individuals, "test:broken"), StorageResult::UnprocessableEntity);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn test_empty_key_rejected_by_all_backends() {
+    let empty_key = StorageResult::Error(v_storage::common::EMPTY_KEY_ERROR.to_string());
+    let mut individual = Individual::default();
+
+    for (name, mut storage) in all_backends("empty-key") {
+        assert_eq!(storage.put_value(StorageId::Individuals, "", "value"), empty_key, "{}", name);
+        assert_eq!(storage.put_raw_value(StorageId::Individuals, "", b"value".to_vec()), empty_key, "{}", name);
+        assert_eq!(storage.get_value(StorageId::Individuals, "").map(|_| ()), empty_key, "{}", name);
+        assert_eq!(storage.get_raw_value(StorageId::Individuals, "").map(|_| ()), empty_key, "{}", name);
+        assert_eq!(storage.get_individual(StorageId::Individuals, "", &mut individual), empty_key, "{}", name);
+        assert_eq!(storage.remove_value(StorageId::Individuals, ""), empty_key, "{}", name);
+        assert_eq!(storage.replace_section(StorageId::Tickets, &[("", b"value")]), empty_key, "{}", name);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0), "{}", name);
+        assert_eq!(storage.count(StorageId::Tickets), StorageResult::Ok(0), "{}", name);
+
+        // An empty key can never be stored, so a batch check reports it as absent
+        assert!(storage.put_value(StorageId::Individuals, "a", "value").is_ok(), "{}", name);
+        assert_eq!(storage.exists_many(StorageId::Individuals, &["a", "", "b"]), StorageResult::Ok(vec![true, false, false]), "{}", name);
+    }
+
+    cleanup_backends("empty-key");
+}
+
+#[test]
+fn test_contains_across_backends() {
+    for (name, mut storage) in all_backends("contains") {
+        assert!(storage.put_raw_value(StorageId::Individuals, "blob", vec![0u8; 64 * 1024]).is_ok(), "{}", name);
+
+        assert_eq!(storage.contains(StorageId::Individuals, "blob"), StorageResult::Ok(true), "{}", name);
+        assert_eq!(storage.contains(StorageId::Individuals, "missing"), StorageResult::Ok(false), "{}", name);
+        assert_eq!(storage.contains(StorageId::Tickets, "blob"), StorageResult::Ok(false), "{}", name);
+
+        assert!(storage.remove_value(StorageId::Individuals, "blob").is_ok(), "{}", name);
+        assert_eq!(storage.contains(StorageId::Individuals, "blob"), StorageResult::Ok(false), "{}", name);
+    }
+
+    cleanup_backends("contains");
+}
+
+#[test]
+fn test_keys_with_prefix_across_backends() {
+    for (name, mut storage) in all_backends("keys-with-prefix") {
+        for key in ["ns2:c", "ns1:b", "ns1:a", "ns10:d"] {
+            assert!(storage.put_value(StorageId::Individuals, key, "v").is_ok(), "{}", name);
+        }
+
+        assert_eq!(storage.keys_with_prefix(StorageId::Individuals, "ns1:"), StorageResult::Ok(vec!["ns1:a".to_string(), "ns1:b".to_string()]), "{}", name);
+        assert_eq!(storage.keys_with_prefix(StorageId::Individuals, "ns3:"), StorageResult::Ok(Vec::new()), "{}", name);
+        assert_eq!(storage.keys_with_prefix(StorageId::Tickets, "ns1:"), StorageResult::Ok(Vec::new()), "{}", name);
+
+        // An empty prefix lists every key of the storage
+        let all = ["ns10:d", "ns1:a", "ns1:b", "ns2:c"].map(String::from).to_vec();
+        assert_eq!(storage.keys_with_prefix(StorageId::Individuals, ""), StorageResult::Ok(all), "{}", name);
+        assert_eq!(storage.keys_with_prefix(StorageId::Tickets, ""), StorageResult::Ok(Vec::new()), "{}", name);
+    }
+
+    cleanup_backends("keys-with-prefix");
+}
+
+#[test]
+fn test_clear_single_section() {
+    for (name, mut storage) in all_backends("clear") {
+        for key in ["a", "b", "c"] {
+            assert!(storage.put_value(StorageId::Individuals, key, "v").is_ok(), "{}", name);
+        }
+        assert!(storage.put_value(StorageId::Tickets, "t", "ticket").is_ok(), "{}", name);
+
+        assert_eq!(storage.clear(StorageId::Individuals), StorageResult::Ok(()), "{}", name);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0), "{}", name);
+        assert_eq!(storage.get_value(StorageId::Individuals, "a"), StorageResult::NotFound, "{}", name);
+        assert_eq!(storage.get_value(StorageId::Tickets, "t"), StorageResult::Ok("ticket".to_string()), "{}", name);
+
+        // The section stays usable after clearing
+        assert!(storage.put_value(StorageId::Individuals, "d", "v").is_ok(), "{}", name);
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(1), "{}", name);
+    }
+
+    assert!(VStorageEnum::Remote(remote_storage_client::StorageROClient::new("tcp://127.0.0.1:1")).clear(StorageId::Individuals).is_error());
+
+    cleanup_backends("clear");
+}
+
+#[test]
+fn test_remote_count() {
+    let addr = format!("inproc://test-remote-count-{}", std::process::id());
+
+    // Stub storage_manager replying with a fixed count for the AZ section
+    let server = nng::Socket::new(nng::Protocol::Rep0).unwrap();
+    server.listen(&addr).unwrap();
+    let responder = std::thread::spawn(move || {
+        for _ in 0..3 {
+            let req = server.recv().unwrap();
+            let reply: &[u8] = match req.as_slice() {
+                b"c,a" => b"42",
+                b"c,t" => b"not a number",
+                _ => b"0",
+            };
+            server.send(nng::Message::from(reply)).unwrap();
+        }
+        server.close();
+    });
+
+    let mut client = StorageROClient::new(&addr);
+    assert_eq!(client.count(StorageId::Az), StorageResult::Ok(42));
+    assert_eq!(client.count(StorageId::Tickets), StorageResult::UnprocessableEntity);
+
+    let mut storage = VStorage::new(Box::new(client));
+    assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(0));
+
+    responder.join().unwrap();
+}
+
+#[test]
+fn test_increment_concurrent() {
+    let temp_dir = format!("/tmp/test-increment-{}", std::process::id());
+    let lmdb_path = format!("{}/lmdb", temp_dir);
+    let mdbx_path = format!("{}/mdbx", temp_dir);
+    let threads: i64 = 4;
+    let increments: i64 = 100;
+
+    let open = |kind: &str| match kind {
+        "lmdb" => VStorage::new(Box::new(LMDBStorage::new(&lmdb_path, StorageMode::ReadWrite, None, None))),
+        _ => VStorage::new(Box::new(MDBXStorage::new(&mdbx_path, StorageMode::ReadWrite, None))),
+    };
+
+    for kind in ["lmdb", "mdbx"] {
+        // Thread i adds i + 1 on every increment
+        std::thread::scope(|scope| {
+            for i in 0..threads {
+                scope.spawn(move || {
+                    let mut storage = open(kind);
+                    for _ in 0..increments {
+                        assert!(storage.increment(StorageId::Tickets, "counter", i + 1).is_ok());
+                    }
+                });
+            }
+        });
+
+        let expected = increments * (1..=threads).sum::<i64>();
+        assert_eq!(open(kind).increment(StorageId::Tickets, "counter", 0), StorageResult::Ok(expected), "{}", kind);
+    }
+
+    let mut memory = VStorage::new(Box::new(MemoryStorage::new()));
+    assert_eq!(memory.increment(StorageId::Tickets, "counter", 5), StorageResult::Ok(5));
+    assert_eq!(memory.increment(StorageId::Tickets, "counter", -7), StorageResult::Ok(-2));
+    assert_eq!(memory.get_raw_value(StorageId::Tickets, "counter"), StorageResult::Ok((-2i64).to_le_bytes().to_vec()));
+
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn test_sync_after_writes() {
+    for (name, mut storage) in all_backends("sync") {
+        assert!(storage.put_value(StorageId::Individuals, "key", "value").is_ok(), "{}", name);
+        assert_eq!(storage.sync(true), StorageResult::Ok(()), "{}", name);
+        assert_eq!(storage.sync(false), StorageResult::Ok(()), "{}", name);
+    }
+
+    assert!(VStorageEnum::Remote(remote_storage_client::StorageROClient::new("tcp://127.0.0.1:1")).sync(true).is_error());
+    assert_eq!(VStorage::none().sync(true), StorageResult::NotReady);
+
+    cleanup_backends("sync");
+}
+
+#[test]
+fn test_stats_match_count() {
+    for (name, mut storage) in all_backends("stats") {
+        for i in 0..10 {
+            assert!(storage.put_value(StorageId::Individuals, &format!("key{}", i), "value").is_ok(), "{}", name);
+        }
+
+        let stats = match storage.stats(StorageId::Individuals) {
+            StorageResult::Ok(stats) => stats,
+            other => panic!("{}: stats failed: {:?}", name, other),
+        };
+        assert_eq!(storage.count(StorageId::Individuals), StorageResult::Ok(stats.entries), "{}", name);
+        assert_eq!(stats.entries, 10, "{}", name);
+        assert!(stats.size_bytes > 0, "{}", name);
+    }
+
+    cleanup_backends("stats");
+}
+
+#[test]
+fn test_with_value_matches_get_raw_value() {
+    let large: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+    for (name, mut storage) in all_backends("with-value") {
+        assert!(storage.put_raw_value(StorageId::Individuals, "large", large.clone()).is_ok(), "{}", name);
+
+        let raw = storage.get_raw_value(StorageId::Individuals, "large").unwrap_or_default();
+        let checksum = |val: &[u8]| (val.len(), val.iter().map(|b| *b as u64).sum::<u64>());
+        assert_eq!(storage.with_value(StorageId::Individuals, "large", &mut |val| checksum(val)), StorageResult::Ok(checksum(&raw)), "{}", name);
+        assert_eq!(storage.with_value(StorageId::Individuals, "large", &mut |val| val.to_vec()), StorageResult::Ok(raw), "{}", name);
+        assert_eq!(storage.with_value(StorageId::Individuals, "missing", &mut |val| val.len()), StorageResult::NotFound, "{}", name);
+    }
+
+    // Through dynamic dispatch
+    let mut boxed: Box<dyn Storage> = Box::new(VStorageEnum::memory());
+    assert!(boxed.put_value(StorageId::Tickets, "key", "value").is_ok());
+    assert_eq!(boxed.with_value(StorageId::Tickets, "key", &mut |val| val.len()), StorageResult::Ok(5));
+
+    let mut vstorage = VStorage::new(Box::new(VStorageEnum::memory()));
+    assert!(vstorage.put_value(StorageId::Az, "key", "value").is_ok());
+    assert_eq!(vstorage.with_value(StorageId::Az, "key", |val| val.to_vec()), StorageResult::Ok(b"value".to_vec()));
+
+    cleanup_backends("with-value");
+}
+
+#[test]
+fn test_get_individuals_mixed_ids() {
+    use v_individual_model::onto::individual2msgpack::to_msgpack;
+
+
+    for (name, mut storage) in all_backends("get-individuals") {
+        for id in ["test:a", "test:b"] {
+            let mut individual = Individual::default();
+            individual.set_id(id);
+            individual.add_uri("rdf:type", "test:Class");
+            let mut raw = Vec::new();
+            assert!(to_msgpack(&individual, &mut raw).is_ok(), "{}", name);
+            assert!(storage.put_raw_value(StorageId::Individuals, id, raw).is_ok(), "{}", name);
+        }
+        assert!(storage.put_value(StorageId::Individuals, "test:broken", "not an individual").is_ok(), "{}", name);
+
+        let res = storage.get_individuals(StorageId::Individuals, &["test:b", "test:missing", "test:broken", "test:a"]);
+        let ids: Vec<&str> = res.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["test:b", "test:missing", "test:broken", "test:a"], "{}", name);
+
+        match &res[0].1 {
+            StorageResult::Ok(individual) => assert_eq!(individual.get_id(), "test:b"),
+            _ => panic!("expected a parsed individual"),
+        }
+        assert!(matches!(res[1].1, StorageResult::NotFound), "{}", name);
+        assert!(matches!(res[2].1, StorageResult::UnprocessableEntity), "{}", name);
+        match &res[3].1 {
+            StorageResult::Ok(individual) => assert_eq!(individual.get_id(), "test:a"),
+            _ => panic!("expected a parsed individual"),
+        }
+
+        assert!(storage.get_individuals(StorageId::Individuals, &[]).is_empty(), "{}", name);
+    }
+
+    cleanup_backends("get-individuals");
+}
+
+#[test]
+fn test_put_value_sized() {
+    for (name, mut storage) in all_backends("put-sized") {
+        assert_eq!(storage.put_value_sized(StorageId::Individuals, "key", "value"), StorageResult::Ok(3 + 5), "{}", name);
+        assert_eq!(storage.get_value(StorageId::Individuals, "key"), StorageResult::Ok("value".to_string()), "{}", name);
+        // Byte length, not char count
+        assert_eq!(storage.put_value_sized(StorageId::Tickets, "ключ", "значение"), StorageResult::Ok("ключ".len() + "значение".len()), "{}", name);
+        assert!(storage.put_value_sized(StorageId::Tickets, "", "value").is_error(), "{}", name);
+    }
+
+    let mut vstorage = VStorage::new(Box::new(VStorageEnum::memory()));
+    assert_eq!(vstorage.put_value_sized(StorageId::Az, "a", "bc"), StorageResult::Ok(3));
+    assert_eq!(VStorage::none().put_value_sized(StorageId::Az, "a", "bc"), StorageResult::NotReady);
+
+    cleanup_backends("put-sized");
+}
+
+#[test]
+fn test_storage_id_names() {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    let all = StorageId::all();
+    assert_eq!(all, [StorageId::Individuals, StorageId::Tickets, StorageId::Az]);
+    assert_eq!(all.iter().collect::<HashSet<_>>().len(), 3);
+
+    for id in all {
+        assert_eq!(StorageId::from_str(id.as_str()).ok(), Some(id.clone()));
+        assert_eq!(id.to_string(), id.as_str());
+    }
+    assert_eq!("tickets".parse::<StorageId>().ok(), Some(StorageId::Tickets));
+    assert!("Tickets".parse::<StorageId>().is_err());
+    assert!("".parse::<StorageId>().is_err());
+
+    // Generic maintenance loop over every namespace
+    let mut storage = MemoryStorage::new();
+    assert!(storage.put_value(StorageId::Az, "key", "value").is_ok());
+    let counts: Vec<(String, usize)> = StorageId::all().into_iter().map(|id| (id.to_string(), storage.count(id).unwrap_or_default())).collect();
+    assert_eq!(counts, vec![("individuals".to_string(), 0), ("tickets".to_string(), 0), ("az".to_string(), 1)]);
+}
+
+#[test]
+fn test_scan_returns_all_pairs() {
+    for (name, mut storage) in all_backends("scan") {
+        let pairs = [("a", "1"), ("b", "22"), ("c", "333")];
+        assert!(storage.put_values_many(StorageId::Tickets, &pairs).is_ok(), "{}", name);
+        assert!(storage.put_value(StorageId::Az, "other", "section").is_ok(), "{}", name);
+
+        let mut scanned: Vec<ScanEntry> = match storage.scan(StorageId::Tickets) {
+            StorageResult::Ok(pairs) => pairs.collect(),
+            other => panic!("{}: scan failed: {:?}", name, other.map(|_| ())),
+        };
+        scanned.sort();
+        let expected: Vec<ScanEntry> = pairs.iter().map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec())).collect();
+        assert_eq!(scanned, expected, "{}", name);
+
+        assert_eq!(storage.scan(StorageId::Individuals).map(|pairs| pairs.count()), StorageResult::Ok(0), "{}", name);
+    }
+
+    assert!(VStorage::none().scan(StorageId::Az).map(|_| ()) == StorageResult::NotReady);
+
+    cleanup_backends("scan");
+}
+
+#[test]
+fn test_range_between_keys() {
+    let days = [("log:2023-12-31", "a"), ("log:2024-01-01", "b"), ("log:2024-01-15", "c"), ("log:2024-02-01", "d"), ("other", "e")];
+    let entries = |keys: &[(&str, &str)]| keys.iter().map(|(k, v)| (k.to_string(), v.as_bytes().to_vec())).collect::<Vec<_>>();
+
+    for (name, mut storage) in all_backends("range") {
+        assert!(storage.put_values_many(StorageId::Az, &days).is_ok(), "{}", name);
+
+        // Empty: nothing in between, and an inverted interval
+        assert_eq!(storage.range(StorageId::Az, "log:2024-01-02", "log:2024-01-10"), StorageResult::Ok(vec![]), "{}", name);
+        assert_eq!(storage.range(StorageId::Az, "log:2024-02-01", "log:2024-01-01"), StorageResult::Ok(vec![]), "{}", name);
+
+        // Partial: start is included, end is not
+        assert_eq!(storage.range(StorageId::Az, "log:2024-01-01", "log:2024-02-01"), StorageResult::Ok(entries(&days[1..3])), "{}", name);
+
+        // Full
+        assert_eq!(storage.range(StorageId::Az, "", "~"), StorageResult::Ok(entries(&days)), "{}", name);
+        assert_eq!(storage.range(StorageId::Tickets, "", "~"), StorageResult::Ok(vec![]), "{}", name);
+    }
+
+    cleanup_backends("range");
+}